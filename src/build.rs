@@ -0,0 +1,754 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+use crate::project_database::{find_project_for_dir, load_projects_db, save_project, NativeBuild, ProjectInfo};
+use crate::test_scaffold::JUNIT_CONSOLE_JAR;
+use crate::util::{clean_path, node_tool, project_state_dir};
+
+/// Artifacts produced by the last build of one profile, stored in `.upm/build.json`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BuildRecord {
+    pub profile: String,
+    pub artifacts: Vec<String>,
+    /// Program and arguments that run the main artifact.
+    pub run_command: Vec<String>,
+    /// Milliseconds since the Unix epoch when the build finished.
+    pub built_at: u64,
+}
+
+/// Directories holding generated files, which must not make a build look stale or trigger a watch restart.
+pub const GENERATED_DIRS: [&str; 12] = [".git", ".upm", "build", "dist", "target", "node_modules", "venv", "bin", "obj", ".build", ".gradle", "__pycache__"];
+
+pub fn build_command(release: bool, out: Option<&str>) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((_project_name, info)) = find_project_for_dir(&db, &current_dir_str) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+
+    match build_project(info, release, out.map(|out| current_dir.join(out))) {
+        Some(record) => {
+            println!("Built {} profile:", record.profile);
+            for artifact in &record.artifacts {
+                println!("  {}", artifact);
+            }
+        },
+        None => process::exit(1),
+    }
+}
+
+fn build_file(project_path: &Path) -> PathBuf {
+    project_state_dir(project_path).join("build.json")
+}
+
+fn load_build_records(project_path: &Path) -> HashMap<String, BuildRecord> {
+    fs::read_to_string(build_file(project_path))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_build_record(project_path: &Path, record: &BuildRecord) {
+    let mut records = load_build_records(project_path);
+    records.insert(record.profile.clone(), record.clone());
+    let contents = serde_json::to_string_pretty(&records).expect("Failed to serialize build records");
+    if let Err(err) = fs::write(build_file(project_path), contents) {
+        eprintln!("Failed to record build artifacts: {}", err);
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+fn newest_source_mtime(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                if GENERATED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                    0
+                } else {
+                    newest_source_mtime(&path)
+                }
+            } else {
+                entry
+                    .metadata()
+                    .and_then(|meta| meta.modified())
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0)
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the debug build of the project, rebuilding only when sources changed since the last build.
+pub fn ensure_built(info: &ProjectInfo) -> Option<BuildRecord> {
+    let project_path = Path::new(&info.project_path);
+    if let Some(record) = load_build_records(project_path).remove("debug") {
+        let artifacts_exist = record.artifacts.iter().all(|artifact| Path::new(artifact).exists());
+        if artifacts_exist && newest_source_mtime(project_path) <= record.built_at {
+            return Some(record);
+        }
+    }
+    build_project(info, false, None)
+}
+
+pub fn build_project(info: &ProjectInfo, release: bool, out: Option<PathBuf>) -> Option<BuildRecord> {
+    let project_path = Path::new(&info.project_path);
+    let profile = if release { "release" } else { "debug" };
+    let out_dir = out.unwrap_or_else(|| project_path.join("build").join(profile));
+    let project_name = project_path.file_name().unwrap().to_string_lossy().to_string();
+
+    let language = info.project_language.to_lowercase();
+    let (artifacts, run_command) = match language.as_str() {
+        "rust" | "rs" => build_rust(project_path, release, &out_dir, &project_name)?,
+        "c" | "cpp" | "c++" => build_c(project_path, info, &language, release, &out_dir, &project_name)?,
+        "ts" | "typescript" => build_typescript(project_path, info, release, &out_dir)?,
+        "go" => {
+            let binary = out_dir.join(executable_name(&project_name));
+            let mut command = Command::new("go");
+            command.arg("build").arg("-o").arg(&binary);
+            if release {
+                command.args(["-trimpath", "-ldflags", "-s -w"]);
+            } else {
+                command.args(["-gcflags", "all=-N -l"]);
+            }
+            run_build_step(command.arg(".").current_dir(project_path))?;
+            (vec![path_string(&binary)], vec![path_string(&binary)])
+        },
+        "cs" | "c#" => {
+            let configuration = if release { "Release" } else { "Debug" };
+            let mut command = Command::new("dotnet");
+            command.args(["publish", "-c", configuration, "-o"]).arg(&out_dir);
+            run_build_step(command.current_dir(project_path))?;
+            let dll = out_dir.join(format!("{}.dll", project_name));
+            (vec![path_string(&dll)], vec!["dotnet".to_string(), path_string(&dll)])
+        },
+        "java" => build_java(project_path, info, release, &out_dir, &project_name)?,
+        "swift" => {
+            let configuration = if release { "release" } else { "debug" };
+            run_build_step(Command::new("swift").args(["build", "-c", configuration]).current_dir(project_path))?;
+            let binary = project_path.join(".build").join(configuration).join(executable_name(&project_name));
+            let binary = copy_into(&binary, &out_dir)?;
+            (vec![path_string(&binary)], vec![path_string(&binary)])
+        },
+        _ => {
+            println!("{} projects have no build step; use 'upman run' directly.", info.project_language);
+            return None;
+        },
+    };
+
+    let record = BuildRecord {
+        profile: profile.to_string(),
+        artifacts,
+        run_command,
+        built_at: now_millis(),
+    };
+    save_build_record(project_path, &record);
+    Some(record)
+}
+
+fn executable_name(name: &str) -> String {
+    if cfg!(windows) { format!("{}.exe", name) } else { name.to_string() }
+}
+
+fn path_string(path: &Path) -> String {
+    clean_path(path)
+}
+
+fn run_build_step(command: &mut Command) -> Option<()> {
+    match command.status() {
+        Ok(status) if status.success() => Some(()),
+        Ok(_) => {
+            eprintln!("Build failed");
+            None
+        },
+        Err(err) => {
+            eprintln!("Failed to run build: {}", err);
+            None
+        },
+    }
+}
+
+fn copy_into(artifact: &Path, out_dir: &Path) -> Option<PathBuf> {
+    if let Err(err) = fs::create_dir_all(out_dir) {
+        eprintln!("Failed to create output directory: {}", err);
+        return None;
+    }
+    let destination = out_dir.join(artifact.file_name()?);
+    match fs::copy(artifact, &destination) {
+        Ok(_) => Some(destination),
+        Err(err) => {
+            eprintln!("Failed to copy {} to {}: {}", artifact.display(), out_dir.display(), err);
+            None
+        },
+    }
+}
+
+fn build_rust(project_path: &Path, release: bool, out_dir: &Path, project_name: &str) -> Option<(Vec<String>, Vec<String>)> {
+    let mut command = Command::new("cargo");
+    command.arg("build");
+    if release {
+        command.arg("--release");
+    }
+    run_build_step(command.current_dir(project_path))?;
+
+    let bin_name = fs::read_to_string(project_path.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+        .and_then(|manifest| manifest.get("package")?.get("name")?.as_str().map(String::from))
+        .unwrap_or_else(|| project_name.to_string());
+    let binary = project_path
+        .join("target")
+        .join(if release { "release" } else { "debug" })
+        .join(executable_name(&bin_name));
+    let binary = copy_into(&binary, out_dir)?;
+    Some((vec![path_string(&binary)], vec![path_string(&binary)]))
+}
+
+pub fn collect_sources(dir: &Path, extensions: &[&str], files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, extensions, files);
+        } else if path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| extensions.contains(&ext)) {
+            files.push(path);
+        }
+    }
+}
+
+/// The compiler and source extensions for a C or C++ project.
+pub fn native_toolchain(language: &str) -> (&'static str, &'static [&'static str]) {
+    if language == "c" {
+        ("gcc", &["c"])
+    } else {
+        ("g++", &["cpp", "cc", "cxx"])
+    }
+}
+
+/// Include directories and configured flags passed to every compile of a C or C++ project.
+pub fn native_compile_args(project_path: &Path, build: &NativeBuild) -> Vec<String> {
+    let mut args = Vec::new();
+    let default_includes = ["src", "include"].into_iter().filter(|dir| project_path.join(dir).is_dir()).map(String::from);
+    for dir in default_includes.chain(build.include_dirs.iter().cloned()) {
+        args.push(format!("-I{}", path_string(&project_path.join(dir))));
+    }
+    args.extend(build.flags.iter().cloned());
+    args
+}
+
+/// Linker arguments for the configured libraries; bare names such as `m` become `-lm`.
+pub fn native_link_args(build: &NativeBuild) -> Vec<String> {
+    build
+        .libs
+        .iter()
+        .map(|lib| if lib.starts_with('-') { lib.clone() } else { format!("-l{}", lib) })
+        .collect()
+}
+
+// The files a compiled object depends on, read from the depfile written by `-MMD`.
+fn depfile_inputs(depfile: &Path) -> Option<Vec<PathBuf>> {
+    let contents = fs::read_to_string(depfile).ok()?;
+    let contents = contents.replace("\\\r\n", " ").replace("\\\n", " ");
+    // The first rule lists the object's own prerequisites; `-MP` adds empty rules after it.
+    let rule = contents.lines().next()?;
+    let (_, prerequisites) = rule.split_once(": ")?;
+
+    let mut inputs = Vec::new();
+    let mut current = String::new();
+    let mut chars = prerequisites.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&' ') => current.push(chars.next().unwrap()),
+            ' ' | '\t' => {
+                if !current.is_empty() {
+                    inputs.push(PathBuf::from(std::mem::take(&mut current)));
+                }
+            },
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        inputs.push(PathBuf::from(current));
+    }
+    Some(inputs)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn object_is_stale(source: &Path, object: &Path, depfile: &Path, project_path: &Path) -> bool {
+    let Some(object_time) = modified(object) else {
+        return true;
+    };
+    let Some(inputs) = depfile_inputs(depfile) else {
+        return true;
+    };
+    std::iter::once(source.to_path_buf())
+        .chain(inputs)
+        .any(|input| modified(&project_path.join(input)).is_none_or(|time| time > object_time))
+}
+
+fn build_c(project_path: &Path, info: &ProjectInfo, language: &str, release: bool, out_dir: &Path, project_name: &str) -> Option<(Vec<String>, Vec<String>)> {
+    let (compiler, extensions) = native_toolchain(language);
+    let profile = if release { "release" } else { "debug" };
+    let object_dir = project_path.join("build").join("obj").join(profile);
+    for dir in [&object_dir, &out_dir.to_path_buf()] {
+        if let Err(err) = fs::create_dir_all(dir) {
+            eprintln!("Failed to create {}: {}", dir.display(), err);
+            return None;
+        }
+    }
+
+    let src_dir = project_path.join("src");
+    let mut sources = Vec::new();
+    collect_sources(&src_dir, extensions, &mut sources);
+    if sources.is_empty() {
+        sources.push(project_path.join(&info.project_main));
+    }
+    sources.sort();
+
+    let mut compile_args: Vec<String> = if release {
+        vec!["-O2".to_string(), "-DNDEBUG".to_string()]
+    } else {
+        vec!["-g".to_string(), "-O0".to_string()]
+    };
+    compile_args.extend(native_compile_args(project_path, &info.native_build));
+
+    // Objects built with different flags are stale even when no source changed.
+    let flags_file = object_dir.join("flags");
+    let flags = compile_args.join(" ");
+    let flags_changed = fs::read_to_string(&flags_file).map_or(true, |previous| previous != flags);
+
+    let mut objects = Vec::new();
+    let mut compiled = 0;
+    for source in &sources {
+        let relative = source.strip_prefix(&src_dir).or_else(|_| source.strip_prefix(project_path)).unwrap_or(source);
+        let object = object_dir.join(relative).with_extension("o");
+        let depfile = object.with_extension("d");
+        if flags_changed || object_is_stale(source, &object, &depfile, project_path) {
+            if let Some(parent) = object.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            println!("Compiling {}", relative.display());
+            let mut command = Command::new(compiler);
+            command
+                .args(&compile_args)
+                .args(["-MMD", "-MP", "-MF"])
+                .arg(&depfile)
+                .arg("-c")
+                .arg(source)
+                .arg("-o")
+                .arg(&object);
+            run_build_step(command.current_dir(project_path))?;
+            compiled += 1;
+        }
+        objects.push(object);
+    }
+    let _ = fs::write(&flags_file, &flags);
+
+    // Likewise the binary is stale when the libraries it links against change.
+    let link_args = native_link_args(&info.native_build);
+    let link_flags_file = object_dir.join("link-flags");
+    let link_flags = link_args.join(" ");
+    let link_flags_changed = fs::read_to_string(&link_flags_file).map_or(true, |previous| previous != link_flags);
+
+    let binary = out_dir.join(executable_name(project_name));
+    let binary_time = modified(&binary);
+    let relink = compiled > 0
+        || link_flags_changed
+        || binary_time.is_none()
+        || objects.iter().any(|object| modified(object) > binary_time);
+    if relink {
+        println!("Linking {}", binary.display());
+        let mut command = Command::new(compiler);
+        command.args(&objects).arg("-o").arg(&binary).args(&link_args);
+        run_build_step(command.current_dir(project_path))?;
+        let _ = fs::write(&link_flags_file, &link_flags);
+    } else {
+        println!("{} is up to date.", project_name);
+    }
+    Some((vec![path_string(&binary)], vec![path_string(&binary)]))
+}
+
+fn build_typescript(project_path: &Path, info: &ProjectInfo, release: bool, out_dir: &Path) -> Option<(Vec<String>, Vec<String>)> {
+    let src_dir = project_path.join("src");
+    let mut command = Command::new(node_tool("npx"));
+    command.arg("tsc").arg("--outDir").arg(out_dir);
+    if src_dir.is_dir() {
+        command.arg("--rootDir").arg(&src_dir);
+    }
+    command.arg("--sourceMap").arg(if release { "false" } else { "true" });
+    run_build_step(command.current_dir(project_path))?;
+
+    // tsc mirrors the layout below rootDir, so src/main.ts becomes <out>/main.js.
+    let main = Path::new(&info.project_main);
+    let main = project_path.join(main);
+    let relative = main.strip_prefix(&src_dir).or_else(|_| main.strip_prefix(project_path)).unwrap_or(&main).to_path_buf();
+    let script = out_dir.join(relative.with_extension("js"));
+    Some((vec![path_string(out_dir)], vec!["node".to_string(), path_string(&script)]))
+}
+
+/// The fully qualified class name of a Java source file, from its `package` declaration.
+pub fn java_class_name(source: &Path) -> String {
+    let class = source.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "Main".to_string());
+    let package = Regex::new(r"(?m)^\s*package\s+([\w.]+)\s*;").unwrap();
+    fs::read_to_string(source)
+        .ok()
+        .and_then(|contents| package.captures(&contents).map(|captures| format!("{}.{}", &captures[1], class)))
+        .unwrap_or(class)
+}
+
+/// Maven for projects with a pom.xml, preferring the project's `mvnw` wrapper.
+pub fn maven_command(project_path: &Path) -> Option<Command> {
+    if !project_path.join("pom.xml").exists() {
+        return None;
+    }
+    let wrapper = project_path.join(if cfg!(windows) { "mvnw.cmd" } else { "mvnw" });
+    let mut command = if wrapper.exists() { Command::new(wrapper) } else { Command::new(if cfg!(windows) { "mvn.cmd" } else { "mvn" }) };
+    command.current_dir(project_path);
+    Some(command)
+}
+
+/// Gradle for projects with a build.gradle(.kts), preferring the project's `gradlew` wrapper.
+pub fn gradle_command(project_path: &Path) -> Option<Command> {
+    if !project_path.join("build.gradle").exists() && !project_path.join("build.gradle.kts").exists() {
+        return None;
+    }
+    let wrapper = project_path.join(if cfg!(windows) { "gradlew.bat" } else { "gradlew" });
+    let mut command = if wrapper.exists() { Command::new(wrapper) } else { Command::new("gradle") };
+    command.current_dir(project_path);
+    Some(command)
+}
+
+fn build_java(project_path: &Path, info: &ProjectInfo, release: bool, out_dir: &Path, project_name: &str) -> Option<(Vec<String>, Vec<String>)> {
+    if let Some(maven) = maven_command(project_path) {
+        return build_java_maven(project_path, info, maven, out_dir);
+    }
+    if let Some(gradle) = gradle_command(project_path) {
+        return build_java_gradle(project_path, gradle, out_dir, project_name);
+    }
+
+    let classes = project_path.join("build").join("classes");
+    let _ = fs::remove_dir_all(&classes);
+
+    let mut sources = Vec::new();
+    collect_sources(&project_path.join("src"), &["java"], &mut sources);
+    let mut command = Command::new("javac");
+    command.arg("-d").arg(&classes).arg(if release { "-g:none" } else { "-g" });
+    let classpath = java_classpath(project_path, &[]);
+    if !classpath.is_empty() {
+        command.arg("-cp").arg(classpath);
+    }
+    run_build_step(command.args(&sources).current_dir(project_path))?;
+
+    if let Err(err) = fs::create_dir_all(out_dir) {
+        eprintln!("Failed to create output directory: {}", err);
+        return None;
+    }
+    let main_class = java_class_name(&project_path.join(&info.project_main));
+    let jar = out_dir.join(format!("{}.jar", project_name));
+    run_build_step(
+        Command::new("jar")
+            .arg("cfe")
+            .arg(&jar)
+            .arg(&main_class)
+            .arg("-C")
+            .arg(&classes)
+            .arg(".")
+            .current_dir(project_path),
+    )?;
+
+    let classpath = java_classpath(project_path, &[&jar]);
+    Some((vec![path_string(&jar)], vec!["java".to_string(), "-cp".to_string(), classpath, main_class]))
+}
+
+/// A class path of `entries` followed by the jars in the project's `lib` directory, when it has one.
+pub fn java_classpath(project_path: &Path, entries: &[&Path]) -> String {
+    let separator = if cfg!(windows) { ";" } else { ":" };
+    let mut classpath: Vec<String> = entries.iter().map(|entry| path_string(entry)).collect();
+    let lib = project_path.join("lib");
+    if lib.is_dir() {
+        classpath.push(path_string(&lib.join("*")));
+    }
+    classpath.join(separator)
+}
+
+// Maven has no separate debug profile, so both profiles package the same jar.
+fn build_java_maven(project_path: &Path, info: &ProjectInfo, mut maven: Command, out_dir: &Path) -> Option<(Vec<String>, Vec<String>)> {
+    run_build_step(maven.args(["-q", "-DskipTests", "package"]))?;
+
+    let target = project_path.join("target");
+    let mut jars: Vec<PathBuf> = fs::read_dir(&target)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    jars.retain(|path| {
+        let name = path.file_name().unwrap().to_string_lossy();
+        name.ends_with(".jar") && !name.ends_with("-sources.jar") && !name.ends_with("-javadoc.jar") && !name.ends_with("-tests.jar")
+    });
+    jars.sort();
+    let Some(jar) = jars.first() else {
+        eprintln!("Maven did not produce a jar in {}", target.display());
+        return None;
+    };
+    let jar = copy_into(jar, out_dir)?;
+
+    // Dependencies stay in the local Maven repository; ask Maven for their paths.
+    let classpath_file = target.join("classpath.txt");
+    let mut classpath = vec![path_string(&jar)];
+    let resolved = maven_command(project_path)?
+        .args(["-q", "dependency:build-classpath"])
+        .arg(format!("-Dmdep.outputFile={}", path_string(&classpath_file)))
+        .status()
+        .is_ok_and(|status| status.success());
+    match fs::read_to_string(&classpath_file) {
+        Ok(dependencies) if resolved && !dependencies.trim().is_empty() => classpath.push(dependencies.trim().to_string()),
+        Ok(_) => {},
+        Err(_) => eprintln!("Could not resolve the Maven dependency classpath; running with the project jar only."),
+    }
+
+    let separator = if cfg!(windows) { ";" } else { ":" };
+    let main_class = java_class_name(&project_path.join(&info.project_main));
+    Some((vec![path_string(&jar)], vec!["java".to_string(), "-cp".to_string(), classpath.join(separator), main_class]))
+}
+
+// Builds the `application` distribution, whose start script already carries the dependency classpath.
+fn build_java_gradle(project_path: &Path, mut gradle: Command, out_dir: &Path, project_name: &str) -> Option<(Vec<String>, Vec<String>)> {
+    run_build_step(gradle.args(["-q", "installDist"]))?;
+
+    let name = gradle_project_name(project_path).unwrap_or_else(|| project_name.to_string());
+    let distribution = project_path.join("build").join("install").join(&name);
+    if !distribution.is_dir() {
+        eprintln!("Gradle did not produce a distribution in {}", distribution.display());
+        return None;
+    }
+    if let Err(err) = copy_dir(&distribution, out_dir) {
+        eprintln!("Failed to copy {} to {}: {}", distribution.display(), out_dir.display(), err);
+        return None;
+    }
+    let script = out_dir.join("bin").join(if cfg!(windows) { format!("{}.bat", name) } else { name });
+    Some((vec![path_string(out_dir)], vec![path_string(&script)]))
+}
+
+fn gradle_project_name(project_path: &Path) -> Option<String> {
+    let pattern = Regex::new(r#"rootProject\.name\s*=\s*["']([^"']+)["']"#).unwrap();
+    ["settings.gradle", "settings.gradle.kts"]
+        .iter()
+        .filter_map(|file| fs::read_to_string(project_path.join(file)).ok())
+        .find_map(|contents| pattern.captures(&contents).map(|captures| captures[1].to_string()))
+}
+
+fn copy_dir(source: &Path, destination: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)?.flatten() {
+        let target = destination.join(entry.file_name());
+        if entry.path().is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a CMakeLists.txt or Makefile for a new C or C++ project, or a Maven or Gradle build for a Java one,
+/// for teams that build without upman.
+pub fn generate_build_files(project_path: &Path, language: &str, system: &str) {
+    let language = language.to_lowercase();
+    if language == "java" {
+        scaffold_java_build(project_path, system);
+        return;
+    }
+    if !matches!(language.as_str(), "c" | "cpp" | "c++") {
+        eprintln!("--build-system only applies to C, C++ and Java projects.");
+        return;
+    }
+    let project_name = project_path.file_name().unwrap().to_string_lossy().to_string();
+    let is_c = language == "c";
+
+    let (file_name, contents) = match system {
+        "cmake" => {
+            let (cmake_language, standard, extension) = if is_c { ("C", "C_STANDARD 11", "c") } else { ("CXX", "CXX_STANDARD 17", "cpp") };
+            (
+                "CMakeLists.txt",
+                format!(
+                    "cmake_minimum_required(VERSION 3.16)\nproject({name} LANGUAGES {language})\n\nset(CMAKE_{standard})\n\nfile(GLOB_RECURSE SOURCES CONFIGURE_DEPENDS src/*.{extension})\nadd_executable({name} ${{SOURCES}})\ntarget_include_directories({name} PRIVATE src include)\n",
+                    name = project_name,
+                    language = cmake_language,
+                    standard = standard,
+                    extension = extension,
+                ),
+            )
+        },
+        "make" | "makefile" => {
+            let (compiler, compiler_default, flags, extension) = if is_c { ("CC", "gcc", "CFLAGS", "c") } else { ("CXX", "g++", "CXXFLAGS", "cpp") };
+            (
+                "Makefile",
+                format!(
+                    "{compiler} ?= {compiler_default}\n{flags} ?= -Wall -g\nCPPFLAGS += -Isrc -Iinclude -MMD -MP\nLDLIBS ?=\n\nSRCS := $(shell find src -name '*.{extension}')\nOBJS := $(SRCS:src/%.{extension}=build/obj/%.o)\nTARGET := build/{name}\n\n$(TARGET): $(OBJS)\n\t@mkdir -p $(@D)\n\t$({compiler}) $(LDFLAGS) $^ -o $@ $(LDLIBS)\n\nbuild/obj/%.o: src/%.{extension}\n\t@mkdir -p $(@D)\n\t$({compiler}) $(CPPFLAGS) $({flags}) -c $< -o $@\n\n-include $(OBJS:.o=.d)\n\n.PHONY: clean\nclean:\n\trm -rf build\n",
+                    compiler = compiler,
+                    compiler_default = compiler_default,
+                    flags = flags,
+                    extension = extension,
+                    name = project_name,
+                ),
+            )
+        },
+        _ => {
+            eprintln!("C and C++ projects use 'cmake' or 'make', not '{}'.", system);
+            return;
+        },
+    };
+
+    let path = project_path.join(file_name);
+    if path.exists() {
+        println!("{} already exists; leaving it unchanged.", file_name);
+        return;
+    }
+    match fs::write(&path, contents) {
+        Ok(_) => println!("Generated {}.", file_name),
+        Err(err) => eprintln!("Failed to write {}: {}", file_name, err),
+    }
+}
+
+const JAVA_MAIN: &str = "./src/main/java/Main.java";
+
+// Moves a new Java project into the standard src/main/java layout and writes the Maven or Gradle build for it.
+fn scaffold_java_build(project_path: &Path, system: &str) {
+    let project_name = project_path.file_name().unwrap().to_string_lossy().to_string();
+    let (file_name, contents) = match system {
+        "maven" => (
+            "pom.xml",
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0"
+         xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+         xsi:schemaLocation="http://maven.apache.org/POM/4.0.0 https://maven.apache.org/xsd/maven-4.0.0.xsd">
+  <modelVersion>4.0.0</modelVersion>
+
+  <groupId>com.example</groupId>
+  <artifactId>{name}</artifactId>
+  <version>0.1.0</version>
+  <packaging>jar</packaging>
+
+  <properties>
+    <maven.compiler.release>17</maven.compiler.release>
+    <project.build.sourceEncoding>UTF-8</project.build.sourceEncoding>
+  </properties>
+
+  <dependencies>
+    <dependency>
+      <groupId>org.junit.jupiter</groupId>
+      <artifactId>junit-jupiter</artifactId>
+      <version>5.10.2</version>
+      <scope>test</scope>
+    </dependency>
+  </dependencies>
+
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>org.apache.maven.plugins</groupId>
+        <artifactId>maven-surefire-plugin</artifactId>
+        <version>3.2.5</version>
+      </plugin>
+      <plugin>
+        <groupId>org.apache.maven.plugins</groupId>
+        <artifactId>maven-jar-plugin</artifactId>
+        <version>3.4.1</version>
+        <configuration>
+          <archive>
+            <manifest>
+              <mainClass>Main</mainClass>
+            </manifest>
+          </archive>
+        </configuration>
+      </plugin>
+    </plugins>
+  </build>
+</project>
+"#,
+                name = project_name,
+            ),
+        ),
+        "gradle" => (
+            "build.gradle",
+            "plugins {\n    id 'application'\n}\n\nrepositories {\n    mavenCentral()\n}\n\ndependencies {\n    implementation fileTree(dir: 'lib', include: ['*.jar'])\n    testImplementation 'org.junit.jupiter:junit-jupiter:5.10.2'\n    testRuntimeOnly 'org.junit.platform:junit-platform-launcher'\n}\n\njava {\n    toolchain {\n        languageVersion = JavaLanguageVersion.of(17)\n    }\n}\n\napplication {\n    mainClass = 'Main'\n}\n\ntest {\n    useJUnitPlatform()\n}\n".to_string(),
+        ),
+        _ => {
+            eprintln!("Java projects use 'maven' or 'gradle', not '{}'.", system);
+            return;
+        },
+    };
+
+    if project_path.join(file_name).exists() {
+        println!("{} already exists; leaving it unchanged.", file_name);
+        return;
+    }
+
+    let moved = move_java_sources(&project_path.join("src"), &project_path.join("src").join("main").join("java"))
+        .and_then(|_| move_java_sources(&project_path.join("tests"), &project_path.join("src").join("test").join("java")));
+    if let Err(err) = moved {
+        eprintln!("Failed to move sources into the standard layout: {}", err);
+        return;
+    }
+    let _ = fs::remove_dir(project_path.join("tests"));
+    // The build tool provides JUnit, so the standalone launcher used by plain javac projects is not needed.
+    let _ = fs::remove_file(project_path.join(JUNIT_CONSOLE_JAR));
+    let _ = fs::remove_dir(project_path.join("lib"));
+
+    let written = match system {
+        "gradle" => fs::write(project_path.join("settings.gradle"), format!("rootProject.name = '{}'\n", project_name))
+            .and_then(|_| fs::write(project_path.join(file_name), &contents)),
+        _ => fs::write(project_path.join(file_name), &contents),
+    };
+    if let Err(err) = written {
+        eprintln!("Failed to write {}: {}", file_name, err);
+        return;
+    }
+
+    let mut db = load_projects_db();
+    let project_path_str = project_path.canonicalize().map(|path| clean_path(&path)).unwrap_or_default();
+    let key = db.projects.iter().find(|(_key, value)| value.project_path == project_path_str).map(|(key, _)| key.clone());
+    if let Some(key) = key {
+        db.projects.get_mut(&key).unwrap().project_main = JAVA_MAIN.to_string();
+        save_project(&db, &key);
+    }
+    println!("Generated {}.", file_name);
+}
+
+// Moves the top-level .java files of `from` into `to`; subdirectories are left where they are.
+fn move_java_sources(from: &Path, to: &Path) -> std::io::Result<()> {
+    let Ok(entries) = fs::read_dir(from) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() && path.extension().is_some_and(|extension| extension == "java") {
+            fs::create_dir_all(to)?;
+            fs::rename(&path, to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use signal_hook::consts::{SIGHUP, SIGINT, SIGQUIT, SIGTERM};
+#[cfg(unix)]
+use signal_hook::iterator::Signals;
+
+/// Runs `command` to completion while forwarding termination signals to it, returning its exit code.
+pub fn run_child(command: &mut Command) -> io::Result<i32> {
+    let mut child = command.spawn()?;
+
+    #[cfg(unix)]
+    let forwarder = forward_signals(child.id())?;
+    let status = child.wait();
+    #[cfg(unix)]
+    forwarder.close();
+
+    Ok(exit_code(status?))
+}
+
+// SIGINT and SIGQUIT typed at the terminal already reach the child through its process group, so
+// upman only has to survive them; signals sent to upman alone are passed on.
+#[cfg(unix)]
+fn forward_signals(pid: u32) -> io::Result<signal_hook::iterator::Handle> {
+    let mut signals = Signals::new([SIGINT, SIGQUIT, SIGTERM, SIGHUP])?;
+    let handle = signals.handle();
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            if signal == SIGTERM || signal == SIGHUP {
+                // SAFETY: kill has no memory-safety preconditions; a stale pid only makes it fail.
+                unsafe {
+                    libc::kill(pid as libc::pid_t, signal);
+                }
+            }
+        }
+    });
+    Ok(handle)
+}
+
+/// The exit code a shell would report for `status`, using 128 + N for a child killed by signal N.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// A shell command line; extra arguments are appended without being re-parsed by the shell.
+pub fn shell_command(command_line: &str, args: &[String]) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line).args(args);
+        command
+    } else {
+        // Extra arguments reach the command through "$@", so they are never re-parsed by the shell.
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(format!("{} \"$@\"", command_line)).arg("upman").args(args);
+        command
+    }
+}
+
+/// Spawns `command` in its own process group, so stopping it also stops everything it started.
+/// Terminal signals no longer reach the group; pair this with `interrupt_flag` and stop it explicitly.
+pub fn spawn_group(command: &mut Command) -> io::Result<Child> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn()
+}
+
+/// The signal a process group is first asked to exit with.
+#[derive(Clone, Copy)]
+pub enum StopSignal {
+    Interrupt,
+    Terminate,
+}
+
+pub fn signal_group(child: &Child, signal: StopSignal) {
+    #[cfg(unix)]
+    {
+        let signal = match signal {
+            StopSignal::Interrupt => libc::SIGINT,
+            StopSignal::Terminate => libc::SIGTERM,
+        };
+        // SAFETY: kill has no memory-safety preconditions; the group id is the child's pid.
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), signal);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (child, signal);
+}
+
+/// Signals every group, waits up to `timeout` for them to exit, then kills what is left.
+pub fn stop_groups(children: &mut [&mut Child], signal: StopSignal, timeout: Duration) {
+    for child in children.iter() {
+        signal_group(child, signal);
+    }
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline && children.iter_mut().any(|child| matches!(child.try_wait(), Ok(None))) {
+        thread::sleep(Duration::from_millis(50));
+    }
+    for child in children.iter_mut() {
+        if let Ok(None) = child.try_wait() {
+            #[cfg(unix)]
+            // SAFETY: as in signal_group.
+            unsafe {
+                libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+            }
+            let _ = child.kill();
+        }
+        let _ = child.wait();
+    }
+}
+
+/// A flag set when upman is asked to stop (Ctrl-C, SIGTERM or SIGHUP), instead of exiting immediately.
+pub fn interrupt_flag() -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    for signal in [SIGINT, SIGTERM, SIGHUP] {
+        if let Err(err) = signal_hook::flag::register(signal, Arc::clone(&flag)) {
+            eprintln!("Failed to handle signal {}: {}", signal, err);
+        }
+    }
+    flag
+}
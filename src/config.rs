@@ -2,6 +2,7 @@ use serde::{Serialize, Deserialize};
 use std::fs;
 use regex::Regex;
 use crate::util::get_install_path;
+use crate::license::parse_license_expression;
 use std::path::{PathBuf, Path};
 
 
@@ -28,6 +29,11 @@ pub struct DefaultFlags {
 pub struct Preferences {
     pub editor: String,
     pub license: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// How long an unlocked `.env.enc` key stays cached; 0 disables the cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets_cache_minutes: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -64,6 +70,8 @@ pub fn read_config_from() -> Config {
             preferences: Preferences {
                 editor: "VS Code".to_string(),
                 license: "MIT".to_string(),
+                author: None,
+                secrets_cache_minutes: None,
             },
             warnings: Warnings {
                 creation: true,
@@ -94,57 +102,45 @@ pub fn set_license(argument: &str) {
     // Read the current configuration from upmconfig.toml
     let mut config = read_config_from();
 
-    let mit_pattern = Regex::new(r#"(?i)mit"#).unwrap();
-    let apache_pattern = Regex::new(r#"(?i)apache"#).unwrap();
-    let gpl_v3_pattern = Regex::new(r#"(?i)gpl-?v?3(\.0)?"#).unwrap();
-    let bsd_3_clause_pattern = Regex::new(r#"(?i)bsd-?3(\.0)?"#).unwrap();
-    let agpl_v3_pattern = Regex::new(r#"(?i)agpl-?v?3(\.0)?"#).unwrap();
-    let mpl_2_pattern = Regex::new(r#"(?i)mpl-?2(\.0)?"#).unwrap();
-    let lgpl_v3_pattern = Regex::new(r#"(?i)lgpl-?v?3(\.0)?"#).unwrap();
-    let epl_2_pattern = Regex::new(r#"(?i)epl-?2(\.0)?"#).unwrap();
-    let unlicense_pattern = Regex::new(r#"(?i)unlicense"#).unwrap();
-    let gpl_v2_pattern = Regex::new(r#"(?i)gpl-?v?2(\.0)?"#).unwrap();
-
-    if mit_pattern.is_match(argument) {
-        config.preferences.license = "MIT".to_string();
-        println!("Default license updated to MIT");
-    } else if apache_pattern.is_match(argument) {
-        config.preferences.license = "Apache-2.0".to_string();
-        println!("Default license updated to Apache License 2.0");
-    } else if gpl_v3_pattern.is_match(argument) {
-        config.preferences.license = "GPL-3.0".to_string();
-        println!("Default license updated to GNU General Public License v3.0");
-    } else if bsd_3_clause_pattern.is_match(argument) {
-        config.preferences.license = "BSD-3-Clause".to_string();
-        println!("Default license updated to BSD 3-Clause License");
-    } else if agpl_v3_pattern.is_match(argument) {
-        config.preferences.license = "AGPL-3.0".to_string();
-        println!("Default license updated to GNU Affero General Public License v3.0");
-    } else if mpl_2_pattern.is_match(argument) {
-        config.preferences.license = "MPL-2.0".to_string();
-        println!("Default license updated to Mozilla Public License 2.0");
-    } else if lgpl_v3_pattern.is_match(argument) {
-        config.preferences.license = "LGPL-3.0".to_string();
-        println!("Default license updated to GNU Lesser General Public License v3.0");
-    } else if epl_2_pattern.is_match(argument) {
-        config.preferences.license = "EPL-2.0".to_string();
-        println!("Default license updated to Eclipse Public License 2.0");
-    } else if unlicense_pattern.is_match(argument) {
-        config.preferences.license = "Unlicense".to_string();
-        println!("Default license updated to Unlicense");
-    } else if gpl_v2_pattern.is_match(argument) {
-        config.preferences.license = "GPL-2.0".to_string();
-        println!("Default license updated to GNU General Public License v2.0");
-    } else {
-        println!("The license '{}' is not recognized or supported by UPM.", argument);
-        println!("To view a list of supported licenses, try 'upm list licenses'.");
-        return; 
+    match parse_license_expression(argument) {
+        Ok(expression) => {
+            config.preferences.license = expression;
+            println!("Default license updated to {}", config.preferences.license);
+        },
+        Err(unknown) => {
+            println!("The license '{}' is not recognized or supported by UPM.", unknown);
+            println!("To view a list of supported licenses, try 'upm list licenses'.");
+            println!("To register a custom license, try 'upm license add <LICENSE_ID> <LICENSE_FILE>'.");
+            return;
+        }
     }
 
     write_config_to(&config); 
     println!("License preference updated successfully.");
 }
 
+pub fn set_author(argument: &str) {
+    let mut config = read_config_from();
+    config.preferences.author = Some(argument.to_string());
+    write_config_to(&config);
+    println!("Author preference updated to '{}'.", argument);
+}
+
+pub fn set_secrets_cache(argument: &str) {
+    let Ok(minutes) = argument.parse::<u64>() else {
+        println!("Expected a number of minutes, e.g. 'upm config secrets-cache 30' (0 disables caching).");
+        return;
+    };
+    let mut config = read_config_from();
+    config.preferences.secrets_cache_minutes = Some(minutes);
+    write_config_to(&config);
+    if minutes == 0 {
+        println!("Unlocked secrets will no longer be cached.");
+    } else {
+        println!("Unlocked secrets will be cached for {} minutes.", minutes);
+    }
+}
+
 pub fn set_editor(argument: &str) {
     // Read the current configuration from upmconfig.toml
     let mut config = read_config_from();
@@ -0,0 +1,625 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::Path;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::project_database::{find_project_for_dir, load_projects_db};
+use crate::util::{clean_path, site_packages_dirs};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Runtime,
+    Dev,
+}
+
+/// One dependency of a project: what the manifest asks for and what the lockfile (or install) resolved it to.
+#[derive(Serialize, Clone)]
+pub struct Dependency {
+    pub name: String,
+    /// cargo, npm, pypi, go, rubygems or nuget. The license, outdated, SBOM and audit reports use the same names.
+    pub ecosystem: String,
+    pub requested: Option<String>,
+    pub resolved: Option<String>,
+    pub kind: DependencyKind,
+    /// Transitive dependencies from the lockfile, filled in for `--tree`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Dependency>,
+    /// Set when the package already appears earlier in the tree, so its dependencies are not repeated.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+}
+
+/// A package pinned by a lockfile and the keys of the packages it depends on.
+#[derive(Clone)]
+pub struct LockedPackage {
+    pub version: String,
+    pub dependencies: Vec<String>,
+}
+
+/// Locked packages keyed both by name and by `name version`, since a lockfile may hold several versions of one package.
+pub type LockGraph = HashMap<String, LockedPackage>;
+
+/// The direct dependencies one manifest declares, with the lockfile that resolves them.
+type Ecosystem = (Vec<Dependency>, LockGraph);
+
+pub fn deps_command(tree: bool, format: &str) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((_project_name, info)) = find_project_for_dir(&db, &current_dir_str) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+
+    let dependencies = project_dependencies(Path::new(&info.project_path), tree);
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&dependencies).expect("Failed to serialize dependencies"));
+    } else if dependencies.is_empty() {
+        println!("No dependencies found in the project's manifests.");
+    } else if tree {
+        for dependency in &dependencies {
+            println!("{}", tree_label(dependency, true));
+            print_children(&dependency.dependencies, "");
+        }
+    } else {
+        print_table(&dependencies);
+    }
+}
+
+/// The direct dependencies declared in every manifest found in the project root, sorted by ecosystem, kind and name.
+/// With `tree`, each one carries its transitive dependencies from the lockfile.
+pub fn project_dependencies(project_path: &Path, tree: bool) -> Vec<Dependency> {
+    let mut dependencies = Vec::new();
+    let ecosystems: [fn(&Path) -> Option<Ecosystem>; 6] =
+        [cargo_dependencies, npm_dependencies, python_dependencies, go_dependencies, ruby_dependencies, dotnet_dependencies];
+    for read in ecosystems {
+        let Some((mut direct, graph)) = read(project_path) else {
+            continue;
+        };
+        let mut expanded = HashSet::new();
+        for dependency in direct.iter_mut() {
+            if dependency.resolved.is_none() {
+                dependency.resolved = graph.get(&dependency.name).map(|package| package.version.clone());
+            }
+            if tree {
+                let key = format!("{} {}", dependency.name, dependency.resolved.as_deref().unwrap_or_default());
+                let children = graph.get(&key).or_else(|| graph.get(&dependency.name)).map(|package| package.dependencies.clone()).unwrap_or_default();
+                dependency.dependencies = locked_children(&children, dependency, &graph, &mut expanded, &mut vec![key]);
+            }
+        }
+        dependencies.extend(direct);
+    }
+    dependencies.sort_by(|a, b| a.ecosystem.cmp(&b.ecosystem).then(a.kind.cmp(&b.kind)).then_with(|| a.name.cmp(&b.name)));
+    dependencies
+}
+
+fn locked_children(keys: &[String], parent: &Dependency, graph: &LockGraph, expanded: &mut HashSet<String>, path: &mut Vec<String>) -> Vec<Dependency> {
+    let mut children = Vec::new();
+    for key in keys {
+        let Some(package) = graph.get(key) else {
+            continue;
+        };
+        let name = key.split(' ').next().unwrap_or(key).to_string();
+        let full_key = format!("{} {}", name, package.version);
+        let mut child = Dependency {
+            name,
+            ecosystem: parent.ecosystem.clone(),
+            requested: None,
+            resolved: Some(package.version.clone()),
+            kind: parent.kind,
+            dependencies: Vec::new(),
+            repeated: false,
+        };
+        // Each package is expanded once per ecosystem; cycles and repeats are marked instead.
+        if path.contains(&full_key) || !expanded.insert(full_key.clone()) {
+            child.repeated = !package.dependencies.is_empty();
+        } else {
+            path.push(full_key);
+            child.dependencies = locked_children(&package.dependencies, &child, graph, expanded, path);
+            path.pop();
+        }
+        children.push(child);
+    }
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    children
+}
+
+fn tree_label(dependency: &Dependency, root: bool) -> String {
+    let mut label = dependency.name.clone();
+    if root {
+        if let Some(requested) = &dependency.requested {
+            label.push_str(&format!(" {}", requested));
+        }
+        if let Some(resolved) = &dependency.resolved {
+            label.push_str(&format!(" -> {}", resolved));
+        }
+        label.push_str(&format!(" [{}]", dependency.ecosystem));
+        if dependency.kind == DependencyKind::Dev {
+            label.push_str(" (dev)");
+        }
+    } else if let Some(resolved) = &dependency.resolved {
+        label.push_str(&format!(" {}", resolved));
+    }
+    if dependency.repeated {
+        label.push_str(" (*)");
+    }
+    label
+}
+
+fn print_children(children: &[Dependency], prefix: &str) {
+    for (index, child) in children.iter().enumerate() {
+        let last = index + 1 == children.len();
+        println!("{}{}{}", prefix, if last { "└── " } else { "├── " }, tree_label(child, false));
+        print_children(&child.dependencies, &format!("{}{}", prefix, if last { "    " } else { "│   " }));
+    }
+}
+
+fn print_table(dependencies: &[Dependency]) {
+    let name_width = dependencies.iter().map(|d| d.name.len()).max().unwrap_or(0).max("PACKAGE".len());
+    let requested_width = dependencies.iter().map(|d| d.requested.as_deref().unwrap_or("-").len()).max().unwrap_or(0).max("REQUESTED".len());
+    let resolved_width = dependencies.iter().map(|d| d.resolved.as_deref().unwrap_or("-").len()).max().unwrap_or(0).max("RESOLVED".len());
+    println!("{:<name_width$}  {:<requested_width$}  {:<resolved_width$}  {:<7}  ECOSYSTEM", "PACKAGE", "REQUESTED", "RESOLVED", "KIND");
+    for dependency in dependencies {
+        let kind = match dependency.kind {
+            DependencyKind::Runtime => "runtime",
+            DependencyKind::Dev => "dev",
+        };
+        println!(
+            "{:<name_width$}  {:<requested_width$}  {:<resolved_width$}  {:<7}  {}",
+            dependency.name,
+            dependency.requested.as_deref().unwrap_or("-"),
+            dependency.resolved.as_deref().unwrap_or("-"),
+            kind,
+            dependency.ecosystem
+        );
+    }
+}
+
+fn direct(name: &str, ecosystem: &str, requested: Option<String>, resolved: Option<String>, kind: DependencyKind) -> Dependency {
+    Dependency {
+        name: name.to_string(),
+        ecosystem: ecosystem.to_string(),
+        requested: requested.filter(|requested| !requested.is_empty()),
+        resolved,
+        kind,
+        dependencies: Vec::new(),
+        repeated: false,
+    }
+}
+
+fn insert_locked(graph: &mut LockGraph, name: &str, version: &str, dependencies: Vec<String>) {
+    let package = LockedPackage { version: version.to_string(), dependencies };
+    graph.insert(format!("{} {}", name, version), package.clone());
+    graph.entry(name.to_string()).or_insert(package);
+}
+
+fn cargo_dependencies(project_path: &Path) -> Option<Ecosystem> {
+    let contents = fs::read_to_string(project_path.join("Cargo.toml")).ok()?;
+    let manifest = match contents.parse::<toml::Table>() {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("Failed to parse Cargo.toml: {}", err);
+            return None;
+        },
+    };
+
+    // Platform-specific tables (`[target.'cfg(unix)'.dependencies]`) hold the same sections.
+    let mut tables = vec![&manifest];
+    if let Some(targets) = manifest.get("target").and_then(|targets| targets.as_table()) {
+        tables.extend(targets.values().filter_map(|target| target.as_table()));
+    }
+    let mut dependencies = Vec::new();
+    for table in tables {
+        for (section, kind) in [("dependencies", DependencyKind::Runtime), ("build-dependencies", DependencyKind::Runtime), ("dev-dependencies", DependencyKind::Dev)] {
+            let Some(section) = table.get(section).and_then(|section| section.as_table()) else {
+                continue;
+            };
+            for (key, spec) in section {
+                let (name, requested) = match spec {
+                    toml::Value::String(version) => (key.as_str(), Some(version.clone())),
+                    toml::Value::Table(spec) => {
+                        let field = |field: &str| spec.get(field).and_then(|value| value.as_str());
+                        let requested = field("version")
+                            .map(String::from)
+                            .or_else(|| field("git").map(|git| format!("git+{}", git)))
+                            .or_else(|| field("path").map(|path| format!("path:{}", path)))
+                            .or_else(|| spec.get("workspace").map(|_| "workspace".to_string()));
+                        (field("package").unwrap_or(key), requested)
+                    },
+                    _ => continue,
+                };
+                dependencies.push(direct(name, "cargo", requested, None, kind));
+            }
+        }
+    }
+
+    let mut graph = LockGraph::new();
+    let lock = fs::read_to_string(project_path.join("Cargo.lock")).ok().and_then(|contents| contents.parse::<toml::Table>().ok());
+    let packages = lock.as_ref().and_then(|lock| lock.get("package")).and_then(|packages| packages.as_array()).cloned().unwrap_or_default();
+    for package in &packages {
+        let (Some(name), Some(version)) = (package.get("name").and_then(|v| v.as_str()), package.get("version").and_then(|v| v.as_str())) else {
+            continue;
+        };
+        // Entries are `name`, or `name version (source)` when several versions are locked.
+        let requires = package
+            .get("dependencies")
+            .and_then(|requires| requires.as_array())
+            .map(|requires| requires.iter().filter_map(|entry| entry.as_str()).map(|entry| entry.split(' ').take(2).collect::<Vec<_>>().join(" ")).collect())
+            .unwrap_or_default();
+        insert_locked(&mut graph, name, version, requires);
+    }
+    Some((dependencies, graph))
+}
+
+fn npm_dependencies(project_path: &Path) -> Option<Ecosystem> {
+    let contents = fs::read_to_string(project_path.join("package.json")).ok()?;
+    let manifest: serde_json::Value = match serde_json::from_str(&contents) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("Failed to parse package.json: {}", err);
+            return None;
+        },
+    };
+
+    let mut dependencies = Vec::new();
+    for (section, kind) in [("dependencies", DependencyKind::Runtime), ("optionalDependencies", DependencyKind::Runtime), ("devDependencies", DependencyKind::Dev)] {
+        if let Some(section) = manifest.get(section).and_then(|section| section.as_object()) {
+            for (name, requested) in section {
+                dependencies.push(direct(name, "npm", requested.as_str().map(String::from), None, kind));
+            }
+        }
+    }
+
+    let mut graph = LockGraph::new();
+    let lock: Option<serde_json::Value> = fs::read_to_string(project_path.join("package-lock.json")).ok().and_then(|contents| serde_json::from_str(&contents).ok());
+    let requires = |package: &serde_json::Value, fields: &[&str]| -> Vec<String> {
+        fields.iter().filter_map(|field| package.get(*field)?.as_object()).flat_map(|map| map.keys().cloned()).collect()
+    };
+    if let Some(packages) = lock.as_ref().and_then(|lock| lock.get("packages")).and_then(|packages| packages.as_object()) {
+        // Lockfile v2 and v3: only hoisted packages, since nested copies would shadow each other by name.
+        for (path, package) in packages {
+            let Some(name) = path.strip_prefix("node_modules/").filter(|name| !name.contains("/node_modules/")) else {
+                continue;
+            };
+            let Some(version) = package.get("version").and_then(|version| version.as_str()) else {
+                continue;
+            };
+            insert_locked(&mut graph, name, version, requires(package, &["dependencies", "optionalDependencies"]));
+        }
+    } else if let Some(packages) = lock.as_ref().and_then(|lock| lock.get("dependencies")).and_then(|packages| packages.as_object()) {
+        for (name, package) in packages {
+            if let Some(version) = package.get("version").and_then(|version| version.as_str()) {
+                insert_locked(&mut graph, name, version, requires(package, &["requires"]));
+            }
+        }
+    }
+    // pnpm, yarn and bun lockfiles are not read; their installed packages resolve the versions instead.
+    for dependency in &dependencies {
+        if graph.contains_key(&dependency.name) {
+            continue;
+        }
+        let installed: Option<serde_json::Value> = fs::read_to_string(project_path.join("node_modules").join(&dependency.name).join("package.json"))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        if let Some(version) = installed.as_ref().and_then(|package| package.get("version")).and_then(|version| version.as_str()) {
+            insert_locked(&mut graph, &dependency.name, version, Vec::new());
+        }
+    }
+    Some((dependencies, graph))
+}
+
+/// PEP 503 normalization, so `Foo_Bar` in requirements.txt matches `foo-bar` in the installed metadata.
+pub fn normalize_python_name(name: &str) -> String {
+    let separators = Regex::new(r"[-_.]+").unwrap();
+    separators.replace_all(&name.to_lowercase(), "-").to_string()
+}
+
+/// The name two spellings of the same package share within `ecosystem`: PyPI names are compared after PEP 503
+/// normalization and NuGet ids case-insensitively; other registries treat names as exact.
+pub fn package_key(ecosystem: &str, name: &str) -> String {
+    match ecosystem {
+        "pypi" => normalize_python_name(name),
+        "nuget" => name.to_lowercase(),
+        _ => name.to_string(),
+    }
+}
+
+/// Splits a requirement such as `requests[socks]>=2.31 ; python_version > "3.8"` into its name and version specifier.
+pub fn parse_requirement(line: &str) -> Option<(String, String)> {
+    let line = line.split(" #").next().unwrap_or(line).trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+        return None;
+    }
+    let pattern = Regex::new(r"^([A-Za-z0-9][A-Za-z0-9._-]*)\s*(\[[^\]]*\])?\s*\(?([^;)]*)").unwrap();
+    let captures = pattern.captures(line)?;
+    Some((captures[1].to_string(), captures[3].trim().to_string()))
+}
+
+fn python_dependencies(project_path: &Path) -> Option<Ecosystem> {
+    let mut dependencies = Vec::new();
+    let mut found = false;
+    for (file, kind) in [("requirements.txt", DependencyKind::Runtime), ("requirements-dev.txt", DependencyKind::Dev)] {
+        let Ok(contents) = fs::read_to_string(project_path.join(file)) else {
+            continue;
+        };
+        found = true;
+        for (name, requested) in contents.lines().filter_map(parse_requirement) {
+            dependencies.push(direct(&normalize_python_name(&name), "pypi", Some(requested), None, kind));
+        }
+    }
+    let read_toml = |file: &str| fs::read_to_string(project_path.join(file)).ok().and_then(|contents| contents.parse::<toml::Table>().ok());
+    if let Some(pyproject) = read_toml("pyproject.toml") {
+        found = true;
+        dependencies.extend(pyproject_dependencies(&pyproject));
+    }
+    if let Some(pipfile) = read_toml("Pipfile") {
+        found = true;
+        for (section, kind) in [("packages", DependencyKind::Runtime), ("dev-packages", DependencyKind::Dev)] {
+            dependencies.extend(python_table_dependencies(pipfile.get(section), kind));
+        }
+    }
+    if !found {
+        return None;
+    }
+    // The same package may be listed by requirements.txt and pyproject.toml.
+    let mut seen = HashSet::new();
+    dependencies.retain(|dependency| seen.insert((dependency.name.clone(), dependency.kind)));
+
+    let mut graph = LockGraph::new();
+    // poetry.lock and uv.lock share the [[package]] layout; only the dependency lists differ.
+    for lock in ["poetry.lock", "uv.lock"].iter().filter_map(|file| read_toml(file)) {
+        for package in lock.get("package").and_then(|packages| packages.as_array()).into_iter().flatten() {
+            let (Some(name), Some(version)) = (package.get("name").and_then(|v| v.as_str()), package.get("version").and_then(|v| v.as_str())) else {
+                continue;
+            };
+            let requires = match package.get("dependencies") {
+                Some(toml::Value::Table(requires)) => requires.keys().map(|name| normalize_python_name(name)).collect(),
+                Some(toml::Value::Array(requires)) => requires.iter().filter_map(|require| require.get("name")?.as_str()).map(normalize_python_name).collect(),
+                _ => Vec::new(),
+            };
+            insert_locked(&mut graph, &normalize_python_name(name), version, requires);
+        }
+    }
+    let pipfile_lock: Option<serde_json::Value> = fs::read_to_string(project_path.join("Pipfile.lock")).ok().and_then(|contents| serde_json::from_str(&contents).ok());
+    for section in ["default", "develop"] {
+        let packages = pipfile_lock.as_ref().and_then(|lock| lock.get(section)).and_then(|packages| packages.as_object());
+        for (name, package) in packages.into_iter().flatten() {
+            if let Some(version) = package.get("version").and_then(|version| version.as_str()) {
+                insert_locked(&mut graph, &normalize_python_name(name), version.trim_start_matches("=="), Vec::new());
+            }
+        }
+    }
+
+    // The venv is the closest thing to a lockfile: its metadata has every installed version and requirement.
+    for site_packages in site_packages_dirs(project_path) {
+        let Ok(entries) = fs::read_dir(site_packages) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().ends_with(".dist-info") {
+                continue;
+            }
+            let Ok(metadata) = fs::read_to_string(entry.path().join("METADATA")) else {
+                continue;
+            };
+            let field = |prefix: &str| metadata.lines().find_map(|line| line.strip_prefix(prefix)).map(str::trim);
+            let (Some(name), Some(version)) = (field("Name:"), field("Version:")) else {
+                continue;
+            };
+            // Requirements that only apply to an extra are not installed unless asked for.
+            let requires = metadata
+                .lines()
+                .filter_map(|line| line.strip_prefix("Requires-Dist:"))
+                .filter(|requirement| !requirement.contains("extra =="))
+                .filter_map(parse_requirement)
+                .map(|(name, _)| normalize_python_name(&name))
+                .collect();
+            let name = normalize_python_name(name);
+            if !graph.contains_key(&name) {
+                insert_locked(&mut graph, &name, version, requires);
+            }
+        }
+    }
+    Some((dependencies, graph))
+}
+
+// PEP 621 `[project]` dependencies, PEP 735 `[dependency-groups]`, uv's dev-dependencies and Poetry's tables.
+fn pyproject_dependencies(pyproject: &toml::Table) -> Vec<Dependency> {
+    let requirements = |value: Option<&toml::Value>, kind: DependencyKind| -> Vec<Dependency> {
+        value
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|requirement| parse_requirement(requirement.as_str()?))
+            .map(|(name, requested)| direct(&normalize_python_name(&name), "pypi", Some(requested), None, kind))
+            .collect()
+    };
+
+    let mut dependencies = requirements(pyproject.get("project").and_then(|project| project.get("dependencies")), DependencyKind::Runtime);
+    if let Some(groups) = pyproject.get("dependency-groups").and_then(|groups| groups.as_table()) {
+        for group in groups.values() {
+            dependencies.extend(requirements(Some(group), DependencyKind::Dev));
+        }
+    }
+    let tool = pyproject.get("tool");
+    dependencies.extend(requirements(tool.and_then(|tool| tool.get("uv")).and_then(|uv| uv.get("dev-dependencies")), DependencyKind::Dev));
+
+    let poetry = tool.and_then(|tool| tool.get("poetry"));
+    dependencies.extend(python_table_dependencies(poetry.and_then(|poetry| poetry.get("dependencies")), DependencyKind::Runtime));
+    dependencies.extend(python_table_dependencies(poetry.and_then(|poetry| poetry.get("dev-dependencies")), DependencyKind::Dev));
+    if let Some(groups) = poetry.and_then(|poetry| poetry.get("group")).and_then(|groups| groups.as_table()) {
+        for group in groups.values() {
+            dependencies.extend(python_table_dependencies(group.get("dependencies"), DependencyKind::Dev));
+        }
+    }
+    dependencies
+}
+
+// Poetry and Pipfile tables map names to `"^1.2"`, `"*"` or `{ version = "^1.2", extras = [...] }`.
+fn python_table_dependencies(table: Option<&toml::Value>, kind: DependencyKind) -> Vec<Dependency> {
+    table
+        .and_then(|table| table.as_table())
+        .into_iter()
+        .flatten()
+        .filter(|(name, _)| name.as_str() != "python")
+        .map(|(name, spec)| {
+            let requested = match spec {
+                toml::Value::String(version) => Some(version.clone()),
+                toml::Value::Table(spec) => spec.get("version").and_then(|version| version.as_str()).map(String::from),
+                _ => None,
+            };
+            direct(&normalize_python_name(name), "pypi", requested.filter(|requested| requested != "*"), None, kind)
+        })
+        .collect()
+}
+
+fn go_dependencies(project_path: &Path) -> Option<Ecosystem> {
+    let contents = fs::read_to_string(project_path.join("go.mod")).ok()?;
+    let mut dependencies = Vec::new();
+    let mut graph = LockGraph::new();
+    let mut in_block = false;
+    for line in contents.lines().map(str::trim) {
+        let requirement = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(requirement) = line.strip_prefix("require ") {
+            requirement
+        } else {
+            continue;
+        };
+
+        let indirect = requirement.contains("// indirect");
+        let mut parts = requirement.split("//").next().unwrap_or_default().split_whitespace();
+        let (Some(module), Some(version)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        // go.mod holds the versions minimal version selection settled on; edges need `go mod graph`.
+        insert_locked(&mut graph, module, version, Vec::new());
+        if !indirect {
+            dependencies.push(direct(module, "go", Some(version.to_string()), Some(version.to_string()), DependencyKind::Runtime));
+        }
+    }
+    Some((dependencies, graph))
+}
+
+fn ruby_dependencies(project_path: &Path) -> Option<Ecosystem> {
+    let gemfile = fs::read_to_string(project_path.join("Gemfile")).ok()?;
+    let gem = Regex::new(r#"^\s*gem\s+["']([^"']+)["']((?:\s*,\s*["'][^"']*["'])*)(.*)$"#).unwrap();
+    let quoted = Regex::new(r#"["']([^"']*)["']"#).unwrap();
+    let group = Regex::new(r"^\s*group\s+(.*?)\s+do\b").unwrap();
+
+    let mut dependencies = Vec::new();
+    // Gems only in :development or :test groups (block or `group:` option) are dev dependencies.
+    let dev_groups = |groups: &str| {
+        let groups: Vec<&str> = groups.split(',').map(|group| group.trim().trim_start_matches(':').trim_matches(|c| c == '"' || c == '\'' || c == '[' || c == ']')).collect();
+        !groups.is_empty() && groups.iter().all(|group| matches!(*group, "development" | "test"))
+    };
+    let mut block_dev = Vec::new();
+    for line in gemfile.lines() {
+        if let Some(captures) = group.captures(line) {
+            block_dev.push(dev_groups(&captures[1]));
+            continue;
+        }
+        if line.trim() == "end" {
+            block_dev.pop();
+            continue;
+        }
+        let Some(captures) = gem.captures(line) else {
+            continue;
+        };
+        let constraints: Vec<String> = quoted.captures_iter(&captures[2]).map(|c| c[1].to_string()).collect();
+        let option_dev = captures[3].split_once("group:").or_else(|| captures[3].split_once("groups:")).is_some_and(|(_, groups)| dev_groups(groups));
+        let kind = if option_dev || block_dev.last().copied().unwrap_or(false) { DependencyKind::Dev } else { DependencyKind::Runtime };
+        dependencies.push(direct(&captures[1], "rubygems", Some(constraints.join(", ")), None, kind));
+    }
+
+    let mut graph = LockGraph::new();
+    let lock = fs::read_to_string(project_path.join("Gemfile.lock")).unwrap_or_default();
+    let spec = Regex::new(r"^    (\S+) \(([^)]+)\)$").unwrap();
+    let requirement = Regex::new(r"^      (\S+)").unwrap();
+    let mut current: Option<(String, String, Vec<String>)> = None;
+    for line in lock.lines() {
+        if let Some(captures) = spec.captures(line) {
+            if let Some((name, version, requires)) = current.take() {
+                insert_locked(&mut graph, &name, &version, requires);
+            }
+            current = Some((captures[1].to_string(), captures[2].to_string(), Vec::new()));
+        } else if let (Some(captures), Some((_, _, requires))) = (requirement.captures(line), current.as_mut()) {
+            requires.push(captures[1].to_string());
+        } else if !line.starts_with("    ") {
+            if let Some((name, version, requires)) = current.take() {
+                insert_locked(&mut graph, &name, &version, requires);
+            }
+        }
+    }
+    if let Some((name, version, requires)) = current.take() {
+        insert_locked(&mut graph, &name, &version, requires);
+    }
+    Some((dependencies, graph))
+}
+
+fn dotnet_dependencies(project_path: &Path) -> Option<Ecosystem> {
+    let projects: Vec<_> = fs::read_dir(project_path)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "csproj"))
+        .collect();
+    if projects.is_empty() {
+        return None;
+    }
+
+    let mut dependencies = Vec::new();
+    for project in &projects {
+        let Ok(contents) = fs::read_to_string(project) else {
+            continue;
+        };
+        let document = match roxmltree::Document::parse(&contents) {
+            Ok(document) => document,
+            Err(err) => {
+                eprintln!("Failed to parse {}: {}", project.display(), err);
+                continue;
+            },
+        };
+        for reference in document.descendants().filter(|node| node.has_tag_name("PackageReference")) {
+            let Some(name) = reference.attribute("Include").or_else(|| reference.attribute("Update")) else {
+                continue;
+            };
+            // Attributes and child elements are interchangeable in MSBuild.
+            let property = |property: &str| {
+                reference
+                    .attribute(property)
+                    .map(String::from)
+                    .or_else(|| reference.children().find(|child| child.has_tag_name(property)).and_then(|child| child.text()).map(|text| text.trim().to_string()))
+            };
+            // Analyzers and build tools are marked PrivateAssets="all" and do not flow to consumers.
+            let kind = if property("PrivateAssets").is_some_and(|assets| assets.eq_ignore_ascii_case("all")) { DependencyKind::Dev } else { DependencyKind::Runtime };
+            dependencies.push(direct(name, "nuget", property("Version"), None, kind));
+        }
+    }
+
+    let mut graph = LockGraph::new();
+    let assets: Option<serde_json::Value> = fs::read_to_string(project_path.join("obj").join("project.assets.json")).ok().and_then(|contents| serde_json::from_str(&contents).ok());
+    let target = assets.as_ref().and_then(|assets| assets.get("targets")).and_then(|targets| targets.as_object()).and_then(|targets| targets.values().next()).and_then(|target| target.as_object());
+    for (key, package) in target.into_iter().flatten() {
+        if package.get("type").and_then(|kind| kind.as_str()) != Some("package") {
+            continue;
+        }
+        let Some((name, version)) = key.split_once('/') else {
+            continue;
+        };
+        let requires = package.get("dependencies").and_then(|requires| requires.as_object()).map(|requires| requires.keys().cloned().collect()).unwrap_or_default();
+        insert_locked(&mut graph, name, version, requires);
+    }
+    Some((dependencies, graph))
+}
@@ -0,0 +1,376 @@
+use std::fs;
+use std::path::Path;
+use regex::Regex;
+
+/// A Maven coordinate, `group:artifact:version`. The version may be left out when removing.
+pub struct Coordinates {
+    pub group: String,
+    pub artifact: String,
+    pub version: Option<String>,
+}
+
+pub fn parse_coordinates(spec: &str) -> Option<Coordinates> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    match parts.as_slice() {
+        [group, artifact] if !group.is_empty() && !artifact.is_empty() => Some(Coordinates { group: group.to_string(), artifact: artifact.to_string(), version: None }),
+        [group, artifact, version] if !group.is_empty() && !artifact.is_empty() && !version.is_empty() => {
+            Some(Coordinates { group: group.to_string(), artifact: artifact.to_string(), version: Some(version.to_string()) })
+        },
+        _ => None,
+    }
+}
+
+/// Adds `configuration 'group:artifact:version'` to the top-level `dependencies` block of a Gradle build,
+/// or updates the version of an existing declaration. Returns false when the build file can't be changed.
+pub fn add_gradle_dependency(build_file: &Path, coordinates: &Coordinates, configuration: &str) -> bool {
+    let Some(contents) = read(build_file) else {
+        return false;
+    };
+    let kotlin = build_file.extension().is_some_and(|extension| extension == "kts");
+    let notation = format!("{}:{}:{}", coordinates.group, coordinates.artifact, coordinates.version.as_deref().unwrap_or_default());
+    let declaration = if kotlin { format!("{}(\"{}\")", configuration, notation) } else { format!("{} '{}'", configuration, notation) };
+
+    let existing = gradle_declaration(coordinates);
+    let updated = if let Some(line) = contents.lines().position(|line| existing.is_match(line)) {
+        // Only the coordinates change, so the configuration and any trailing comment stay as written.
+        let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+        let old_notation = Regex::new(&format!(r#"{}:{}(:[^"']*)?"#, regex::escape(&coordinates.group), regex::escape(&coordinates.artifact))).unwrap();
+        lines[line] = old_notation.replace(&lines[line], regex::NoExpand(&notation)).to_string();
+        join_lines(&lines, &contents)
+    } else {
+        match top_level_block(&contents, "dependencies") {
+            Some((_, close)) => {
+                let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+                let indent = format!("{}    ", leading_whitespace(&lines[close]));
+                lines.insert(close, format!("{}{}", indent, declaration));
+                join_lines(&lines, &contents)
+            },
+            None => format!("{}\n\ndependencies {{\n    {}\n}}\n", contents.trim_end(), declaration),
+        }
+    };
+    write(build_file, &updated)
+}
+
+/// Removes every declaration of `group:artifact` from a Gradle build. Returns true if one was found.
+pub fn remove_gradle_dependency(build_file: &Path, coordinates: &Coordinates) -> bool {
+    let Some(contents) = read(build_file) else {
+        return false;
+    };
+    let existing = gradle_declaration(coordinates);
+    let lines: Vec<String> = contents.lines().filter(|line| !existing.is_match(line)).map(String::from).collect();
+    if lines.len() == contents.lines().count() {
+        return false;
+    }
+    write(build_file, &join_lines(&lines, &contents))
+}
+
+fn gradle_declaration(coordinates: &Coordinates) -> Regex {
+    Regex::new(&format!(r#"^\s*\w+\s*\(?\s*["']{}:{}(:[^"']*)?["']"#, regex::escape(&coordinates.group), regex::escape(&coordinates.artifact))).unwrap()
+}
+
+/// Finds `name {` at brace depth zero and returns the line numbers of its opening and closing braces,
+/// so `dependencies` inside `buildscript` or `subprojects` is left alone.
+fn top_level_block(contents: &str, name: &str) -> Option<(usize, usize)> {
+    let opening = Regex::new(&format!(r"^\s*{}\s*\{{", regex::escape(name))).unwrap();
+    let mut depth = 0i32;
+    let mut start = None;
+    for (index, line) in contents.lines().enumerate() {
+        let code = line.split("//").next().unwrap_or_default();
+        if start.is_none() && depth == 0 && opening.is_match(code) {
+            start = Some(index);
+        }
+        depth += code.matches('{').count() as i32 - code.matches('}').count() as i32;
+        if let Some(start) = start {
+            if depth == 0 {
+                return Some((start, index));
+            }
+        }
+    }
+    None
+}
+
+/// Adds a `<dependency>` to the project's own `<dependencies>` (not `<dependencyManagement>`),
+/// or updates the version of an existing one.
+pub fn add_maven_dependency(pom: &Path, coordinates: &Coordinates, scope: Option<&str>) -> bool {
+    let Some(contents) = read(pom) else {
+        return false;
+    };
+    let document = match roxmltree::Document::parse(&contents) {
+        Ok(document) => document,
+        Err(err) => {
+            eprintln!("Failed to parse {}: {}", pom.display(), err);
+            return false;
+        },
+    };
+    let project = document.root_element();
+
+    let dependencies = project.children().find(|node| node.has_tag_name("dependencies"));
+    let existing = dependencies.and_then(|dependencies| find_maven_dependency(dependencies, coordinates));
+    let unit = indent_unit(&contents, project);
+
+    let updated = if let Some(dependency) = existing {
+        // Rewrite the element in place, keeping exclusions and other children that are not ours.
+        let version = dependency.children().find(|node| node.has_tag_name("version"));
+        let mut element = contents[dependency.range()].to_string();
+        match (version, &coordinates.version) {
+            (Some(version), Some(new_version)) => {
+                let start = version.range().start - dependency.range().start;
+                let end = version.range().end - dependency.range().start;
+                element.replace_range(start..end, &format!("<version>{}</version>", new_version));
+            },
+            (None, Some(new_version)) => {
+                let artifact = dependency.children().find(|node| node.has_tag_name("artifactId")).unwrap();
+                let end = artifact.range().end - dependency.range().start;
+                let indent = line_indent(&contents, artifact.range().start);
+                element.insert_str(end, &format!("\n{}<version>{}</version>", indent, new_version));
+            },
+            _ => {},
+        }
+        let mut updated = contents.clone();
+        updated.replace_range(dependency.range(), &element);
+        updated
+    } else {
+        match dependencies {
+            Some(dependencies) => {
+                let indent = format!("{}{}", line_indent(&contents, dependencies.range().start), unit);
+                let close = contents[..dependencies.range().end].rfind("</").unwrap();
+                let line_start = contents[..close].rfind('\n').map_or(0, |newline| newline + 1);
+                let mut updated = contents.clone();
+                updated.insert_str(line_start, &format!("{}\n", maven_element(coordinates, scope, &indent, unit)));
+                updated
+            },
+            None => {
+                let indent = unit.to_string();
+                let close = contents[..project.range().end].rfind("</").unwrap();
+                let line_start = contents[..close].rfind('\n').map_or(0, |newline| newline + 1);
+                let mut updated = contents.clone();
+                let block = format!("\n{}<dependencies>\n{}\n{}</dependencies>\n", indent, maven_element(coordinates, scope, &format!("{}{}", indent, unit), unit), indent);
+                updated.insert_str(line_start, &block);
+                updated
+            },
+        }
+    };
+    write(pom, &updated)
+}
+
+/// Removes the project's `<dependency>` on `group:artifact`, with the line it sat on.
+pub fn remove_maven_dependency(pom: &Path, coordinates: &Coordinates) -> bool {
+    let Some(contents) = read(pom) else {
+        return false;
+    };
+    let Ok(document) = roxmltree::Document::parse(&contents) else {
+        eprintln!("Failed to parse {}", pom.display());
+        return false;
+    };
+    let Some(range) = document
+        .root_element()
+        .children()
+        .find(|node| node.has_tag_name("dependencies"))
+        .and_then(|dependencies| find_maven_dependency(dependencies, coordinates))
+        .map(|dependency| dependency.range())
+    else {
+        return false;
+    };
+
+    let start = contents[..range.start].rfind('\n').filter(|&newline| contents[newline + 1..range.start].trim().is_empty()).unwrap_or(range.start);
+    let mut updated = contents.clone();
+    updated.replace_range(start..range.end, "");
+    write(pom, &updated)
+}
+
+fn find_maven_dependency<'a, 'input>(dependencies: roxmltree::Node<'a, 'input>, coordinates: &Coordinates) -> Option<roxmltree::Node<'a, 'input>> {
+    dependencies.children().filter(|node| node.has_tag_name("dependency")).find(|dependency| {
+        let text = |tag: &str| dependency.children().find(|node| node.has_tag_name(tag)).and_then(|node| node.text()).map(str::trim);
+        text("groupId") == Some(coordinates.group.as_str()) && text("artifactId") == Some(coordinates.artifact.as_str())
+    })
+}
+
+fn maven_element(coordinates: &Coordinates, scope: Option<&str>, indent: &str, unit: &str) -> String {
+    let inner = format!("{}{}", indent, unit);
+    let mut element = format!("{}<dependency>\n{}<groupId>{}</groupId>\n{}<artifactId>{}</artifactId>\n", indent, inner, coordinates.group, inner, coordinates.artifact);
+    if let Some(version) = &coordinates.version {
+        element.push_str(&format!("{}<version>{}</version>\n", inner, version));
+    }
+    if let Some(scope) = scope {
+        element.push_str(&format!("{}<scope>{}</scope>\n", inner, scope));
+    }
+    element.push_str(&format!("{}</dependency>", indent));
+    element
+}
+
+// The file's indentation step, taken from the root element's first child.
+fn indent_unit(contents: &str, root: roxmltree::Node) -> &'static str {
+    let indent = root.children().find(|node| node.is_element()).map(|child| line_indent(contents, child.range().start)).unwrap_or_default();
+    match indent.len() - line_indent(contents, root.range().start).len().min(indent.len()) {
+        _ if indent.starts_with('\t') => "\t",
+        width if width >= 4 => "    ",
+        _ => "  ",
+    }
+}
+
+fn line_indent(contents: &str, position: usize) -> &str {
+    let line_start = contents[..position].rfind('\n').map_or(0, |newline| newline + 1);
+    leading_whitespace(&contents[line_start..])
+}
+
+/// A Swift package dependency, `url@version`, added as `.package(url: url, from: version)`.
+pub struct SwiftPackage {
+    pub url: String,
+    pub version: Option<String>,
+}
+
+pub fn parse_swift_package(spec: &str) -> SwiftPackage {
+    // `git@host:repo` URLs contain an @ too, so only a trailing version-like part is split off.
+    // `from:` already allows everything up to the next major version, which is what a caret asks for.
+    match spec.rsplit_once('@').map(|(url, version)| (url, version.trim_start_matches('^'))) {
+        Some((url, version)) if version.chars().next().is_some_and(|c| c.is_ascii_digit()) => SwiftPackage { url: url.to_string(), version: Some(version.to_string()) },
+        _ => SwiftPackage { url: spec.to_string(), version: None },
+    }
+}
+
+/// The name SwiftPM gives a package: the last path component of its URL, without `.git`.
+pub fn swift_package_name(url: &str) -> String {
+    let last = url.trim_end_matches('/').rsplit(['/', ':']).next().unwrap_or(url);
+    last.trim_end_matches(".git").to_string()
+}
+
+/// Adds the package to the `dependencies:` argument of `Package(...)`, creating it before `targets:` if needed,
+/// or replaces an existing `.package(url:)` entry for the same URL.
+pub fn add_swift_dependency(manifest: &Path, package: &SwiftPackage) -> bool {
+    let Some(contents) = read(manifest) else {
+        return false;
+    };
+    let requirement = match &package.version {
+        Some(version) => format!("from: \"{}\"", version),
+        None => "branch: \"main\"".to_string(),
+    };
+    let entry = format!(".package(url: \"{}\", {})", package.url, requirement);
+
+    let existing = Regex::new(&format!(r#"\.package\(\s*url:\s*"{}(\.git)?"[^)]*\)"#, regex::escape(package.url.trim_end_matches(".git")))).unwrap();
+    let updated = if existing.is_match(&contents) {
+        existing.replace(&contents, entry.as_str()).to_string()
+    } else {
+        let Some(arguments) = package_arguments(&contents) else {
+            eprintln!("Could not find Package(...) in {}", manifest.display());
+            return false;
+        };
+        let mut updated = contents.clone();
+        if let Some(&(_, open)) = arguments.iter().find(|(label, _)| label == "dependencies") {
+            let close = matching_bracket(&contents, open);
+            let body = contents[open + 1..close].trim_end();
+            let label_indent = line_indent(&contents, open);
+            let separator = if body.trim().is_empty() || body.ends_with(',') { "" } else { "," };
+            updated.replace_range(open + 1..close, &format!("{}{}\n{}    {},\n{}", body, separator, label_indent, entry, label_indent));
+        } else if let Some(&(_, targets)) = arguments.iter().find(|(label, _)| label == "targets") {
+            let label_start = contents[..targets].rfind("targets").unwrap();
+            let indent = line_indent(&contents, label_start).to_string();
+            updated.insert_str(label_start, &format!("dependencies: [\n{}    {},\n{}],\n{}", indent, entry, indent, indent));
+        } else {
+            eprintln!("Package.swift has no dependencies or targets argument to add to.");
+            return false;
+        }
+        updated
+    };
+    write(manifest, &updated)
+}
+
+/// Removes the `.package(url:)` entry for the URL (or package name) from Package.swift.
+pub fn remove_swift_dependency(manifest: &Path, spec: &str) -> bool {
+    let Some(contents) = read(manifest) else {
+        return false;
+    };
+    let url = parse_swift_package(spec).url;
+    let target = swift_package_name(&url);
+    let entry = Regex::new(r#"(?m)^[ \t]*\.package\(\s*(?:name:\s*"[^"]*",\s*)?url:\s*"([^"]+)"[^)]*\),?[ \t]*\n?"#).unwrap();
+    let found = entry.captures_iter(&contents).find(|captures| swift_package_name(&captures[1]) == target).map(|captures| captures.get(0).unwrap().range());
+    let Some(range) = found else {
+        return false;
+    };
+    let mut updated = contents.clone();
+    updated.replace_range(range, "");
+    write(manifest, &updated)
+}
+
+// Labels of the top-level arguments of `Package(`, with the offset of the `[` that opens each array argument.
+fn package_arguments(contents: &str) -> Option<Vec<(String, usize)>> {
+    let start = contents.find("Package(")? + "Package(".len();
+    let label = Regex::new(r"^(\w+)\s*:\s*\[").unwrap();
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut chars = contents[start..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        let position = start + offset;
+        match c {
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                chars.next();
+            },
+            _ if in_string => {},
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => break,
+            ')' | ']' | '}' => depth -= 1,
+            _ if depth == 0 && c.is_alphabetic() && !contents[..position].ends_with(|p: char| p.is_alphanumeric() || p == '_' || p == '.') => {
+                if let Some(captures) = label.captures(&contents[position..]) {
+                    let open = position + captures.get(0).unwrap().end() - 1;
+                    arguments.push((captures[1].to_string(), open));
+                }
+            },
+            _ => {},
+        }
+    }
+    Some(arguments)
+}
+
+fn matching_bracket(contents: &str, open: usize) -> usize {
+    let mut depth = 0;
+    let mut in_string = false;
+    for (offset, c) in contents[open..].char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            _ if in_string => {},
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return open + offset;
+                }
+            },
+            _ => {},
+        }
+    }
+    contents.len()
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+// Joins edited lines, keeping the file's trailing newline.
+fn join_lines(lines: &[String], original: &str) -> String {
+    let mut joined = lines.join("\n");
+    if original.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined
+}
+
+fn read(path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Some(contents),
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            None
+        },
+    }
+}
+
+fn write(path: &Path, contents: &str) -> bool {
+    match fs::write(path, contents) {
+        Ok(_) => true,
+        Err(err) => {
+            eprintln!("Failed to write {}: {}", path.display(), err);
+            false
+        },
+    }
+}
@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde::Serialize;
+use crate::dependencies::{normalize_python_name, package_key, project_dependencies, DependencyKind};
+use crate::project_database::load_projects_db;
+use crate::util::compare_versions;
+
+/// One package across every registered project that uses it.
+#[derive(Serialize)]
+pub struct InventoryEntry {
+    pub name: String,
+    pub ecosystem: String,
+    /// The newest resolved version any project uses.
+    pub newest: Option<String>,
+    /// Set when projects resolve the package to different versions.
+    pub skew: bool,
+    pub projects: Vec<ProjectUsage>,
+}
+
+#[derive(Serialize)]
+pub struct ProjectUsage {
+    pub project: String,
+    pub requested: Option<String>,
+    pub resolved: Option<String>,
+    pub kind: DependencyKind,
+    /// Set when the project resolves an older version than `newest`.
+    pub behind: bool,
+}
+
+pub fn inventory_command(package: Option<&str>, format: &str) {
+    let inventory = dependency_inventory(package);
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&inventory).expect("Failed to serialize the dependency inventory"));
+        return;
+    }
+    if inventory.is_empty() {
+        match package {
+            Some(package) => println!("No registered project depends on '{}'.", package),
+            None => println!("No dependencies found in the registered projects."),
+        }
+        return;
+    }
+
+    for entry in &inventory {
+        let versions = entry.projects.iter().filter_map(|usage| usage.resolved.as_deref()).collect::<std::collections::HashSet<_>>().len();
+        let skew = if entry.skew { format!(", {} versions in use", versions) } else { String::new() };
+        println!("{} ({}){}", entry.name, entry.ecosystem, skew);
+        let version_width = entry.projects.iter().map(|usage| usage_version(usage).len()).max().unwrap_or(0);
+        for usage in &entry.projects {
+            let mut notes = Vec::new();
+            if usage.kind == DependencyKind::Dev {
+                notes.push("dev".to_string());
+            }
+            if usage.behind {
+                notes.push(format!("behind {}", entry.newest.as_deref().unwrap_or_default()));
+            }
+            let notes = if notes.is_empty() { String::new() } else { format!("  ({})", notes.join(", ")) };
+            println!("  {:<version_width$}  {}{}", usage_version(usage), usage.project, notes);
+        }
+    }
+
+    let skewed = inventory.iter().filter(|entry| entry.skew).count();
+    let shared = inventory.iter().filter(|entry| entry.projects.len() > 1).count();
+    println!();
+    let packages = if inventory.len() == 1 { "1 package".to_string() } else { format!("{} packages", inventory.len()) };
+    println!("{}, {} used by more than one project, {} with version skew.", packages, shared, skewed);
+}
+
+// The resolved version, or the requested one in brackets when nothing is locked or installed.
+fn usage_version(usage: &ProjectUsage) -> String {
+    match (&usage.resolved, &usage.requested) {
+        (Some(resolved), _) => resolved.clone(),
+        (None, Some(requested)) => format!("[{}]", requested),
+        (None, None) => "-".to_string(),
+    }
+}
+
+/// Every dependency of every registered project, grouped by package, newest version first within each package.
+/// With `package`, only that package (matched case-insensitively, and by normalized name on PyPI).
+pub fn dependency_inventory(package: Option<&str>) -> Vec<InventoryEntry> {
+    let db = load_projects_db();
+    let mut projects: Vec<_> = db.projects.iter().collect();
+    projects.sort_by(|a, b| a.0.cmp(b.0));
+
+    // Keyed by the normalized name, so `Flask_Login` and `flask-login` in two projects are one package; the
+    // first spelling seen is the one reported.
+    let mut packages: BTreeMap<(String, String), (String, Vec<ProjectUsage>)> = BTreeMap::new();
+    for (project_name, info) in projects {
+        let project_path = Path::new(&info.project_path);
+        if !project_path.exists() {
+            continue;
+        }
+        for dependency in project_dependencies(project_path, false) {
+            if package.is_some_and(|package| !same_package(package, &dependency.name, &dependency.ecosystem)) {
+                continue;
+            }
+            let key = package_key(&dependency.ecosystem, &dependency.name);
+            let (_, usages) = packages.entry((dependency.ecosystem, key)).or_insert_with(|| (dependency.name, Vec::new()));
+            // A package listed twice in one project (e.g. runtime and dev) is reported once.
+            if usages.iter().any(|usage| usage.project == *project_name) {
+                continue;
+            }
+            usages.push(ProjectUsage {
+                project: project_name.clone(),
+                requested: dependency.requested,
+                resolved: dependency.resolved,
+                kind: dependency.kind,
+                behind: false,
+            });
+        }
+    }
+
+    packages
+        .into_iter()
+        .map(|((ecosystem, _), (name, mut projects))| {
+            let newest = projects.iter().filter_map(|usage| usage.resolved.clone()).max_by(|a, b| compare_versions(a, b));
+            let mut skew = false;
+            for usage in projects.iter_mut() {
+                if let (Some(resolved), Some(newest)) = (&usage.resolved, &newest) {
+                    usage.behind = compare_versions(resolved, newest) == Ordering::Less;
+                    skew |= usage.behind;
+                }
+            }
+            projects.sort_by(|a, b| match (&a.resolved, &b.resolved) {
+                (Some(a_version), Some(b_version)) => compare_versions(b_version, a_version).then_with(|| a.project.cmp(&b.project)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => a.project.cmp(&b.project),
+            });
+            InventoryEntry { name, ecosystem, newest, skew, projects }
+        })
+        .collect()
+}
+
+fn same_package(query: &str, name: &str, ecosystem: &str) -> bool {
+    if ecosystem == "pypi" {
+        normalize_python_name(query) == normalize_python_name(name)
+    } else {
+        query.eq_ignore_ascii_case(name)
+    }
+}
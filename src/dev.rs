@@ -0,0 +1,174 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, IsTerminal, Read};
+use std::path::Path;
+use std::process::{self, Child, Stdio};
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::child::{exit_code, interrupt_flag, shell_command, spawn_group, stop_groups, StopSignal};
+use crate::project_database::load_projects_db;
+use crate::secrets::project_env;
+use crate::util::clean_path;
+
+const COLORS: [u8; 6] = [36, 33, 32, 35, 34, 31];
+// A crashed process is started again after this delay when running with --restart.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct DevProcess {
+    name: String,
+    command: String,
+    prefix: String,
+    child: Option<Child>,
+    restart_at: Option<Instant>,
+}
+
+/// Starts every process from the Procfile (or `[processes]` in upm.toml) and streams their output until they exit.
+pub fn dev_command(restart: bool) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((_, info)) = db.projects.iter().find(|(_key, value)| current_dir_str.starts_with(&value.project_path)) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
+
+    let definitions = match fs::read_to_string(project_path.join("Procfile")) {
+        Ok(contents) => parse_procfile(&contents),
+        Err(_) => info.processes.iter().map(|(name, command)| (name.clone(), command.clone())).collect(),
+    };
+    if definitions.is_empty() {
+        println!("No processes defined. Add a Procfile or a [processes] table to upm.toml.");
+        return;
+    }
+
+    let width = definitions.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let mut processes: Vec<DevProcess> = definitions
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, command))| {
+            let label = format!("{:<width$} |", name, width = width);
+            let prefix = if color { format!("\x1b[{}m{}\x1b[0m", COLORS[index % COLORS.len()], label) } else { label };
+            DevProcess { name, command, prefix, child: None, restart_at: None }
+        })
+        .collect();
+
+    // Processes run in their own groups, so Ctrl-C reaches upman, which passes it on to every process.
+    let interrupted = interrupt_flag();
+    let mut failure: Option<i32> = None;
+    for dev_process in processes.iter_mut() {
+        start(dev_process, project_path);
+        if dev_process.child.is_none() {
+            failure.get_or_insert(1);
+        }
+    }
+
+    loop {
+        if interrupted.load(Ordering::SeqCst) {
+            println!("Stopping all processes...");
+            stop_all(&mut processes, StopSignal::Interrupt);
+            break;
+        }
+
+        let mut shutdown = false;
+        for dev_process in processes.iter_mut() {
+            let Some(status) = dev_process.child.as_mut().and_then(|child| child.try_wait().ok().flatten()) else {
+                continue;
+            };
+            dev_process.child = None;
+            let code = exit_code(status);
+            println!("{} exited with code {}", dev_process.prefix, code);
+            if code == 0 {
+                continue;
+            }
+            failure.get_or_insert(code);
+            if restart {
+                println!("{} restarting in {}s", dev_process.prefix, RESTART_DELAY.as_secs());
+                dev_process.restart_at = Some(Instant::now() + RESTART_DELAY);
+            } else {
+                shutdown = true;
+            }
+        }
+        if shutdown {
+            println!("A process failed; stopping the others.");
+            stop_all(&mut processes, StopSignal::Terminate);
+            break;
+        }
+
+        for dev_process in processes.iter_mut() {
+            if dev_process.restart_at.is_some_and(|restart_at| Instant::now() >= restart_at) {
+                start(dev_process, project_path);
+            }
+        }
+
+        if processes.iter().all(|dev_process| dev_process.child.is_none() && dev_process.restart_at.is_none()) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    if let Some(code) = failure {
+        process::exit(code);
+    }
+}
+
+/// `name: command` lines; blank lines and `#` comments are skipped.
+fn parse_procfile(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (name, command) = line.split_once(':')?;
+            Some((name.trim().to_string(), command.trim().to_string()))
+        })
+        .filter(|(name, command)| !name.is_empty() && !command.is_empty())
+        .collect()
+}
+
+fn start(dev_process: &mut DevProcess, project_path: &Path) {
+    dev_process.restart_at = None;
+    let mut command = shell_command(&dev_process.command, &[]);
+    command
+        .current_dir(project_path)
+        .envs(project_env(project_path))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    match spawn_group(&mut command) {
+        Ok(mut child) => {
+            println!("{} started: {}", dev_process.prefix, dev_process.command);
+            if let Some(stdout) = child.stdout.take() {
+                forward_output(stdout, dev_process.prefix.clone());
+            }
+            if let Some(stderr) = child.stderr.take() {
+                forward_output(stderr, dev_process.prefix.clone());
+            }
+            dev_process.child = Some(child);
+        },
+        Err(err) => eprintln!("{} failed to start '{}': {}", dev_process.prefix, dev_process.name, err),
+    }
+}
+
+fn forward_output(stream: impl Read + Send + 'static, prefix: String) {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            println!("{} {}", prefix, line);
+        }
+    });
+}
+
+fn stop_all(processes: &mut [DevProcess], signal: StopSignal) {
+    let mut running: Vec<&mut Child> = processes.iter_mut().filter_map(|dev_process| dev_process.child.as_mut()).collect();
+    stop_groups(&mut running, signal, STOP_TIMEOUT);
+    for dev_process in processes.iter_mut() {
+        dev_process.child = None;
+        dev_process.restart_at = None;
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::util::write_atomic;
+
+/// A `.env` file kept line by line, so rewriting it preserves comments, blank lines, ordering,
+/// `export` prefixes and the quoting of values that weren't changed.
+pub struct EnvFile {
+    lines: Vec<Line>,
+    trailing_newline: bool,
+}
+
+enum Line {
+    /// A comment, a blank line or anything else that isn't an assignment, kept verbatim.
+    Other(String),
+    Entry(Entry),
+}
+
+struct Entry {
+    key: String,
+    /// Everything before the value, e.g. `export KEY = `.
+    prefix: String,
+    /// The value as written, quotes included. Quoted values can span several lines.
+    raw: String,
+    /// Whitespace and an inline comment after the value.
+    suffix: String,
+}
+
+impl EnvFile {
+    /// Reads `path`; a missing file is an empty one.
+    pub fn load(path: &Path) -> EnvFile {
+        EnvFile::parse(&fs::read_to_string(path).unwrap_or_default())
+    }
+
+    pub fn parse(text: &str) -> EnvFile {
+        let trailing_newline = text.ends_with('\n');
+        let source: Vec<&str> = if text.is_empty() { Vec::new() } else { text.strip_suffix('\n').unwrap_or(text).split('\n').collect() };
+
+        let mut lines = Vec::new();
+        let mut index = 0;
+        while index < source.len() {
+            let line = source[index];
+            let Some((key, prefix_len)) = parse_assignment(line) else {
+                lines.push(Line::Other(line.to_string()));
+                index += 1;
+                continue;
+            };
+            let rest = &line[prefix_len..];
+            let (raw, suffix) = match rest.chars().next().filter(|c| matches!(c, '"' | '\'' | '`')) {
+                Some(quote) => {
+                    // A quoted value runs to its closing quote, on this line or a later one. Without one,
+                    // only this line is taken so a stray quote doesn't swallow the rest of the file.
+                    let mut value = rest.to_string();
+                    let mut end = index;
+                    loop {
+                        if let Some(close) = closing_quote(&value, quote) {
+                            index = end;
+                            break (value[..=close].to_string(), value[close + 1..].to_string());
+                        }
+                        if end + 1 >= source.len() {
+                            break (rest.to_string(), String::new());
+                        }
+                        end += 1;
+                        value.push('\n');
+                        value.push_str(source[end]);
+                    }
+                },
+                None => {
+                    // An unquoted value ends at a `#` that starts the line's value or follows whitespace.
+                    let comment = rest.char_indices().find(|(i, c)| *c == '#' && (*i == 0 || rest[..*i].ends_with([' ', '\t']))).map(|(i, _)| i);
+                    let raw = rest[..comment.unwrap_or(rest.len())].trim_end();
+                    (raw.to_string(), rest[raw.len()..].to_string())
+                },
+            };
+            lines.push(Line::Entry(Entry { key, prefix: line[..prefix_len].to_string(), raw, suffix }));
+            index += 1;
+        }
+        EnvFile { lines, trailing_newline }
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = self
+            .lines
+            .iter()
+            .map(|line| match line {
+                Line::Other(text) => text.clone(),
+                Line::Entry(entry) => format!("{}{}{}", entry.prefix, entry.raw, entry.suffix),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if self.trailing_newline && !self.lines.is_empty() {
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_atomic(path, self.render().as_bytes())
+    }
+
+    /// The value of `key` with `${VAR}` references expanded; the last assignment wins.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.variables(&HashMap::new()).into_iter().rev().find(|(name, _)| name == key).map(|(_, value)| value)
+    }
+
+    /// Every assignment in file order, with quotes and escapes decoded and `$VAR`, `${VAR}` and
+    /// `${VAR:-default}` expanded from earlier assignments, then `base`, then the process environment.
+    /// Single-quoted values are taken literally.
+    pub fn variables(&self, base: &HashMap<String, String>) -> Vec<(String, String)> {
+        let mut resolved: HashMap<String, String> = HashMap::new();
+        let mut variables = Vec::new();
+        for entry in self.entries() {
+            let lookup = |name: &str| resolved.get(name).or(base.get(name)).cloned().or_else(|| std::env::var(name).ok());
+            let value = decode(&entry.raw, &lookup);
+            resolved.insert(entry.key.clone(), value.clone());
+            variables.push((entry.key.clone(), value));
+        }
+        variables
+    }
+
+    /// Sets `key` in place, keeping its `export` prefix, inline comment and (where the value allows) its
+    /// quoting. Earlier duplicate assignments are dropped; a new key is appended.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let mut positions = self.lines.iter().enumerate().filter(|(_, line)| matches!(line, Line::Entry(entry) if entry.key == key)).map(|(i, _)| i).collect::<Vec<_>>();
+        let Some(last) = positions.pop() else {
+            self.lines.push(Line::Entry(Entry { key: key.to_string(), prefix: format!("{}=", key), raw: encode(value, None), suffix: String::new() }));
+            self.trailing_newline = true;
+            return;
+        };
+        if let Line::Entry(entry) = &mut self.lines[last] {
+            entry.raw = encode(value, entry.raw.chars().next());
+        }
+        for position in positions.into_iter().rev() {
+            self.lines.remove(position);
+        }
+    }
+
+    /// Removes every assignment of `key`, returning whether there was one.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines.retain(|line| !matches!(line, Line::Entry(entry) if entry.key == key));
+        self.lines.len() != before
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry(entry) => Some(entry),
+            Line::Other(_) => None,
+        })
+    }
+}
+
+// `KEY=`, `KEY = ` or `export KEY=`, returning the key and the length of everything before the value.
+/// Whether `key` can be set as an environment variable by every shell: `[A-Za-z_][A-Za-z0-9_]*`. Existing
+/// files may use looser keys, which are still read.
+pub fn is_variable_name(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_assignment(line: &str) -> Option<(String, usize)> {
+    let trimmed = line.trim_start();
+    let body = match trimmed.strip_prefix("export") {
+        Some(rest) if rest.starts_with([' ', '\t']) => rest.trim_start(),
+        _ => trimmed,
+    };
+    let key_len = body.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')).unwrap_or(body.len());
+    if key_len == 0 {
+        return None;
+    }
+    let value = body[key_len..].trim_start_matches([' ', '\t']).strip_prefix('=')?.trim_start_matches([' ', '\t']);
+    Some((body[..key_len].to_string(), line.len() - value.len()))
+}
+
+// The byte index of the quote closing `value` (which starts with `quote`). Only double quotes have escapes.
+fn closing_quote(value: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in value.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn decode(raw: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    match raw.chars().next() {
+        Some(quote @ ('"' | '\'' | '`')) => {
+            let body = &raw[1..];
+            let body = body.strip_suffix(quote).unwrap_or(body);
+            if quote == '"' {
+                expand(body, true, lookup)
+            } else {
+                body.to_string()
+            }
+        },
+        _ => expand(raw, false, lookup),
+    }
+}
+
+// Expands variable references. With `escapes` (double quotes) `\n`, `\r`, `\t` and `\<char>` are decoded;
+// otherwise only `\$` is, so a literal `$` can still be written.
+fn expand(text: &str, escapes: bool, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        match chars[i] {
+            '\\' if escapes && next.is_some() => {
+                result.push(match next.unwrap() {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    other => other,
+                });
+                i += 2;
+            },
+            '\\' if next == Some('$') => {
+                result.push('$');
+                i += 2;
+            },
+            '$' if next == Some('{') && chars[i + 2..].contains(&'}') => {
+                let close = i + 2 + chars[i + 2..].iter().position(|c| *c == '}').unwrap();
+                let reference: String = chars[i + 2..close].iter().collect();
+                let (name, default) = match reference.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (reference.as_str(), None),
+                };
+                match (lookup(name), default) {
+                    (Some(value), Some(default)) if value.is_empty() => result.push_str(&expand(default, escapes, lookup)),
+                    (Some(value), _) => result.push_str(&value),
+                    (None, Some(default)) => result.push_str(&expand(default, escapes, lookup)),
+                    (None, None) => {},
+                }
+                i = close + 1;
+            },
+            '$' if next.is_some_and(|c| c.is_ascii_alphabetic() || c == '_') => {
+                let end = chars[i + 1..].iter().position(|c| !(c.is_ascii_alphanumeric() || *c == '_')).map_or(chars.len(), |position| i + 1 + position);
+                let name: String = chars[i + 1..end].iter().collect();
+                result.push_str(&lookup(&name).unwrap_or_default());
+                i = end;
+            },
+            c => {
+                result.push(c);
+                i += 1;
+            },
+        }
+    }
+    result
+}
+
+// Writes `value` so it reads back unchanged: bare when that's safe, otherwise in the quotes the
+// assignment already used, falling back to single quotes (literal) or double quotes (escaped).
+fn encode(value: &str, current_quote: Option<char>) -> String {
+    let plain = value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:@,+=%".contains(c));
+    let single_quotable = !value.contains(['\'', '\n', '\r']);
+    match current_quote {
+        Some('"') => double_quoted(value),
+        Some('\'') if single_quotable => format!("'{}'", value),
+        _ if plain => value.to_string(),
+        _ if single_quotable => format!("'{}'", value),
+        _ => double_quoted(value),
+    }
+}
+
+fn double_quoted(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\\' | '"' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            },
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variable_names() {
+        for key in ["API_KEY", "_private", "a1"] {
+            assert!(is_variable_name(key), "{}", key);
+        }
+        for key in ["", "1KEY", "MY-KEY", "my.key", "KEY=1", "KEY NAME", "ÄPI"] {
+            assert!(!is_variable_name(key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn quoting_and_escapes_round_trip() {
+        let text = "# settings\nA=\"line\\nnext \\\"q\\\" \\$HOME\"\nB='lit $X \\n'\nC=plain # comment\n\nD=`tick`\n";
+        let mut file = EnvFile::parse(text);
+        assert_eq!(file.render(), text);
+        assert_eq!(file.get("A").unwrap(), "line\nnext \"q\" $HOME");
+        assert_eq!(file.get("B").unwrap(), "lit $X \\n");
+        assert_eq!(file.get("C").unwrap(), "plain");
+        assert_eq!(file.get("D").unwrap(), "tick");
+
+        file.set("A", "x\"y$z");
+        file.set("B", "it's");
+        file.set("C", "two words");
+        file.set("D", "simple");
+        assert_eq!(file.render(), "# settings\nA=\"x\\\"y\\$z\"\nB=\"it's\"\nC='two words' # comment\n\nD=simple\n");
+        let reparsed = EnvFile::parse(&file.render());
+        assert_eq!(reparsed.get("A").unwrap(), "x\"y$z");
+        assert_eq!(reparsed.get("B").unwrap(), "it's");
+        assert_eq!(reparsed.get("C").unwrap(), "two words");
+    }
+
+    #[test]
+    fn export_prefix_is_kept() {
+        let mut file = EnvFile::parse("export TOKEN=abc\nexport  SPACED = 'x' # note\nexported=1\n");
+        assert_eq!(file.get("SPACED").unwrap(), "x");
+        assert_eq!(file.get("exported").unwrap(), "1");
+
+        file.set("TOKEN", "new");
+        file.set("SPACED", "y z");
+        assert_eq!(file.render(), "export TOKEN=new\nexport  SPACED = 'y z' # note\nexported=1\n");
+        assert!(file.remove("SPACED"));
+        assert!(!file.remove("SPACED"));
+        assert_eq!(file.render(), "export TOKEN=new\nexported=1\n");
+    }
+
+    #[test]
+    fn multi_line_values() {
+        let text = "KEY=\"first\nsecond # not a comment\"\nSINGLE='a\nb'\nNEXT=1";
+        let mut file = EnvFile::parse(text);
+        assert_eq!(file.render(), text);
+        assert_eq!(file.get("KEY").unwrap(), "first\nsecond # not a comment");
+        assert_eq!(file.get("SINGLE").unwrap(), "a\nb");
+        assert_eq!(file.get("NEXT").unwrap(), "1");
+
+        file.set("KEY", "a\nb");
+        file.set("SINGLE", "c\nd");
+        file.remove("NEXT");
+        assert_eq!(file.render(), "KEY=\"a\\nb\"\nSINGLE=\"c\\nd\"");
+
+        // An unterminated quote only takes its own line.
+        let file = EnvFile::parse("BAD=\"oops\nNEXT=1\n");
+        assert_eq!(file.get("BAD").unwrap(), "oops");
+        assert_eq!(file.get("NEXT").unwrap(), "1");
+    }
+
+    #[test]
+    fn references_and_defaults() {
+        let text = "EMPTY_HOST=\nPORT=${UPMAN_TEST_UNSET_PORT:-8080}\nURL=http://${EMPTY_HOST:-localhost}:$PORT/\nLITERAL=\\$PORT\nQUOTED='${PORT}'\nBASED=${UPMAN_TEST_BASE}-${UPMAN_TEST_UNSET}\n";
+        let mut file = EnvFile::parse(text);
+        let base = HashMap::from([("UPMAN_TEST_BASE".to_string(), "from-base".to_string())]);
+        let variables: HashMap<String, String> = file.variables(&base).into_iter().collect();
+        assert_eq!(variables["PORT"], "8080");
+        assert_eq!(variables["URL"], "http://localhost:8080/");
+        assert_eq!(variables["LITERAL"], "$PORT");
+        assert_eq!(variables["QUOTED"], "${PORT}");
+        assert_eq!(variables["BASED"], "from-base-");
+
+        file.set("EMPTY_HOST", "example.com");
+        assert_eq!(file.get("URL").unwrap(), "http://example.com:8080/");
+        assert_eq!(file.render(), text.replacen("EMPTY_HOST=", "EMPTY_HOST=example.com", 1));
+    }
+
+    #[test]
+    fn set_replaces_duplicates_and_appends_new_keys() {
+        let mut file = EnvFile::parse("A=1\nB=2\nA=3");
+        assert_eq!(file.get("A").unwrap(), "3");
+        file.set("A", "4");
+        file.set("C", "new value");
+        assert_eq!(file.render(), "B=2\nA=4\nC='new value'\n");
+        assert_eq!(EnvFile::parse("").render(), "");
+    }
+}
@@ -0,0 +1,417 @@
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+use crate::config::read_config_from;
+use crate::dotenv::EnvFile;
+use crate::util::{clean_path, sha256_hex, unix_seconds, user_runtime_dir, write_atomic, write_private_file};
+
+/// Set to unlock `.env.enc` without a prompt, e.g. in CI.
+pub const PASSPHRASE_VARIABLE: &str = "UPMAN_SECRETS_PASSPHRASE";
+const DEFAULT_CACHE_MINUTES: u64 = 15;
+
+// Argon2id with 64 MiB and 3 passes, per the second recommended option of RFC 9106.
+const MEMORY_KIB: u32 = 64 * 1024;
+const ITERATIONS: u32 = 3;
+const PARALLELISM: u32 = 1;
+
+/// The contents of `.env.enc`. The KDF parameters are stored so they can be raised later without
+/// breaking existing files.
+#[derive(Serialize, Deserialize)]
+struct EncryptedEnv {
+    version: u32,
+    kdf: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// An unlocked `.env.enc`: the derived key and the KDF settings it came from, so the file can be
+/// written back without asking for the passphrase again.
+pub struct SecretsKey {
+    key: Zeroizing<[u8; 32]>,
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    salt: String,
+    key: String,
+    expires: i64,
+}
+
+pub fn encrypted_env_path(project_path: &Path) -> PathBuf {
+    project_path.join(".env.enc")
+}
+
+pub fn is_encrypted(project_path: &Path) -> bool {
+    encrypted_env_path(project_path).exists()
+}
+
+/// `upm secrets encrypt`: replaces the project's `.env` with `.env.enc`.
+pub fn encrypt_env(project_path: &Path) {
+    let env_path = project_path.join(".env");
+    if is_encrypted(project_path) {
+        println!("Secrets are already encrypted in .env.enc.");
+        return;
+    }
+    let Ok(contents) = fs::read_to_string(&env_path) else {
+        println!("No .env file to encrypt.");
+        return;
+    };
+    let passphrase = match new_passphrase() {
+        Ok(passphrase) => passphrase,
+        Err(error) => fail(&error),
+    };
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let salt = BASE64.encode(salt);
+    let key = derive_key(&passphrase, &salt, MEMORY_KIB, ITERATIONS, PARALLELISM).unwrap_or_else(|error| fail(&error));
+    let dotenv = EnvFile::parse(&contents);
+    if let Err(error) = save_encrypted(project_path, &dotenv, &key) {
+        fail(&format!("Failed to write .env.enc: {}", error));
+    }
+    if let Err(error) = fs::remove_file(&env_path) {
+        fail(&format!("Encrypted to .env.enc, but failed to remove .env: {}", error));
+    }
+    cache_key(project_path, &key);
+    println!("Encrypted .env to .env.enc and removed the plaintext file. Keep the passphrase safe: it can't be recovered.");
+}
+
+/// `upm secrets decrypt`: restores the plaintext `.env` and removes `.env.enc`.
+pub fn decrypt_env(project_path: &Path) {
+    let env_path = project_path.join(".env");
+    if !is_encrypted(project_path) {
+        println!("No .env.enc file to decrypt.");
+        return;
+    }
+    if env_path.exists() {
+        fail("A .env file already exists; move it away before decrypting.");
+    }
+    let (dotenv, _key) = unlock(project_path, true).unwrap_or_else(|error| fail(&error));
+    if let Err(error) = dotenv.save(&env_path) {
+        fail(&format!("Failed to write .env: {}", error));
+    }
+    if let Err(error) = fs::remove_file(encrypted_env_path(project_path)) {
+        eprintln!("Failed to remove .env.enc: {}", error);
+    }
+    forget_key(project_path);
+    println!("Decrypted .env.enc to .env.");
+}
+
+/// `upm secrets unlock`: asks for the passphrase now, so later commands can use the cached key.
+pub fn unlock_env(project_path: &Path) {
+    if !is_encrypted(project_path) {
+        println!("No .env.enc file to unlock.");
+        return;
+    }
+    unlock(project_path, true).unwrap_or_else(|error| fail(&error));
+    match cache_minutes() {
+        0 => println!("Passphrase accepted. Key caching is disabled (`upm config secrets-cache`)."),
+        minutes => println!("Secrets unlocked for {} minutes.", minutes),
+    }
+}
+
+/// `upm secrets lock`: drops the cached key, so the next access asks for the passphrase.
+pub fn lock_env(project_path: &Path) {
+    forget_key(project_path);
+    println!("Secrets locked.");
+}
+
+/// Decrypts `.env.enc` with the cached key, the passphrase in `UPMAN_SECRETS_PASSPHRASE`, or (with
+/// `prompt` and a terminal) a passphrase typed in. The key is cached for the configured time.
+pub fn unlock(project_path: &Path, prompt: bool) -> Result<(EnvFile, SecretsKey), String> {
+    let contents = fs::read_to_string(encrypted_env_path(project_path)).map_err(|error| format!("Failed to read .env.enc: {}", error))?;
+    let file: EncryptedEnv = toml::from_str(&contents).map_err(|_| "The .env.enc file is malformed.".to_string())?;
+    if file.version != 1 || file.kdf != "argon2id" {
+        return Err(format!("The .env.enc file uses an unsupported format (version {}, {}).", file.version, file.kdf));
+    }
+
+    if let Some(key) = cached_key(project_path, &file) {
+        if let Ok(dotenv) = decrypt(&file, &key) {
+            return Ok((dotenv, key));
+        }
+        forget_key(project_path);
+    }
+
+    let passphrase = match env_passphrase()? {
+        Some(passphrase) => passphrase,
+        None if prompt && io::stdin().is_terminal() => read_passphrase("Passphrase for .env.enc: ")?,
+        None => return Err(format!("The project's secrets are encrypted. Run `upm secrets unlock` in a terminal or set {}.", PASSPHRASE_VARIABLE)),
+    };
+    let key = derive_key(&passphrase, &file.salt, file.memory_kib, file.iterations, file.parallelism)?;
+    let dotenv = decrypt(&file, &key).map_err(|_| "Wrong passphrase, or .env.enc has been modified.".to_string())?;
+    cache_key(project_path, &key);
+    Ok((dotenv, key))
+}
+
+/// Writes `dotenv` to `.env.enc` under `key`, with a fresh nonce.
+pub fn save_encrypted(project_path: &Path, dotenv: &EnvFile, key: &SecretsKey) -> io::Result<()> {
+    let cipher = XChaCha20Poly1305::new(key.key.as_ref().into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = Zeroizing::new(dotenv.render());
+    let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes()).map_err(|_| io::Error::other("encryption failed"))?;
+    let file = EncryptedEnv {
+        version: 1,
+        kdf: "argon2id".to_string(),
+        memory_kib: key.memory_kib,
+        iterations: key.iterations,
+        parallelism: key.parallelism,
+        salt: key.salt.clone(),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    let header = "# Secrets encrypted by upman. Edit with `upm secrets add/remove`, or `upm secrets decrypt`.\n";
+    write_atomic(&encrypted_env_path(project_path), format!("{}{}", header, toml::to_string(&file).expect("Failed to serialize .env.enc")).as_bytes())
+}
+
+fn decrypt(file: &EncryptedEnv, key: &SecretsKey) -> Result<EnvFile, ()> {
+    let nonce = BASE64.decode(&file.nonce).map_err(|_| ())?;
+    let ciphertext = BASE64.decode(&file.ciphertext).map_err(|_| ())?;
+    if nonce.len() != 24 {
+        return Err(());
+    }
+    let cipher = XChaCha20Poly1305::new(key.key.as_ref().into());
+    let plaintext = Zeroizing::new(cipher.decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice()).map_err(|_| ())?);
+    let text = Zeroizing::new(String::from_utf8(plaintext.to_vec()).map_err(|_| ())?);
+    Ok(EnvFile::parse(&text))
+}
+
+fn derive_key(passphrase: &str, salt: &str, memory_kib: u32, iterations: u32, parallelism: u32) -> Result<SecretsKey, String> {
+    let salt_bytes = BASE64.decode(salt).map_err(|_| "The .env.enc salt is malformed.".to_string())?;
+    let params = Params::new(memory_kib, iterations, parallelism, Some(32)).map_err(|error| format!("Invalid key derivation parameters: {}", error))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt_bytes, key.as_mut())
+        .map_err(|error| format!("Key derivation failed: {}", error))?;
+    Ok(SecretsKey { key, salt: salt.to_string(), memory_kib, iterations, parallelism })
+}
+
+fn new_passphrase() -> Result<Zeroizing<String>, String> {
+    if let Some(passphrase) = env_passphrase()? {
+        return Ok(passphrase);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(format!("Run this in a terminal to choose a passphrase, or set {}.", PASSPHRASE_VARIABLE));
+    }
+    let passphrase = read_passphrase("New passphrase for .env.enc: ")?;
+    if passphrase.is_empty() {
+        return Err("The passphrase can't be empty.".to_string());
+    }
+    if *read_passphrase("Repeat the passphrase: ")? != *passphrase {
+        return Err("The passphrases don't match.".to_string());
+    }
+    Ok(passphrase)
+}
+
+// The passphrase in `UPMAN_SECRETS_PASSPHRASE`. An empty one is an error rather than a key derived from nothing.
+fn env_passphrase() -> Result<Option<Zeroizing<String>>, String> {
+    match std::env::var(PASSPHRASE_VARIABLE) {
+        Ok(passphrase) if passphrase.is_empty() => Err(format!("{} is set but empty.", PASSPHRASE_VARIABLE)),
+        Ok(passphrase) => Ok(Some(Zeroizing::new(passphrase))),
+        Err(_) => Ok(None),
+    }
+}
+
+fn read_passphrase(prompt: &str) -> Result<Zeroizing<String>, String> {
+    rpassword::prompt_password(prompt).map(Zeroizing::new).map_err(|error| format!("Failed to read the passphrase: {}", error))
+}
+
+// Sessions live in the user's runtime directory, one file per project, readable only by the user.
+fn session_path(project_path: &Path) -> Option<PathBuf> {
+    let dir = user_runtime_dir()?.join("sessions");
+    Some(dir.join(format!("{}.toml", sha256_hex(clean_path(project_path).as_bytes()))))
+}
+
+fn cached_key(project_path: &Path, file: &EncryptedEnv) -> Option<SecretsKey> {
+    let path = session_path(project_path)?;
+    let session: Session = toml::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+    if session.expires <= unix_seconds() || session.salt != file.salt {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    let bytes = Zeroizing::new(BASE64.decode(&session.key).ok()?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    if bytes.len() != key.len() {
+        return None;
+    }
+    key.copy_from_slice(&bytes);
+    Some(SecretsKey { key, salt: file.salt.clone(), memory_kib: file.memory_kib, iterations: file.iterations, parallelism: file.parallelism })
+}
+
+fn cache_key(project_path: &Path, key: &SecretsKey) {
+    let minutes = cache_minutes();
+    let Some(path) = session_path(project_path).filter(|_| minutes > 0) else {
+        return;
+    };
+    let session = Session { salt: key.salt.clone(), key: BASE64.encode(key.key.as_ref()), expires: unix_seconds() + minutes as i64 * 60 };
+    let contents = Zeroizing::new(toml::to_string(&session).expect("Failed to serialize the session"));
+    if let Err(error) = write_private_file(&path, contents.as_bytes()) {
+        eprintln!("Failed to cache the secrets key: {}", error);
+    }
+}
+
+fn cache_minutes() -> u64 {
+    read_config_from().preferences.secrets_cache_minutes.unwrap_or(DEFAULT_CACHE_MINUTES)
+}
+
+fn forget_key(project_path: &Path) {
+    if let Some(path) = session_path(project_path) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // Held by tests that set UPMAN_SECRETS_PASSPHRASE or XDG_RUNTIME_DIR.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    // 16 bytes, base64-encoded.
+    const SALT: &str = "c2FsdHNhbHRzYWx0c2FsdA==";
+
+    // Argon2's minimum cost, so the tests don't spend seconds deriving keys.
+    fn test_key(passphrase: &str) -> SecretsKey {
+        derive_key(passphrase, SALT, 8, 1, 1).unwrap()
+    }
+
+    fn scratch_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("upman-env-encryption-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read_encrypted(project: &Path) -> EncryptedEnv {
+        toml::from_str(&fs::read_to_string(encrypted_env_path(project)).unwrap()).unwrap()
+    }
+
+    fn encrypt_sample(project: &Path, passphrase: &str) {
+        let dotenv = EnvFile::parse("API_TOKEN=\"s3cr3t value\"\nDATABASE_URL=postgres://localhost/app\n");
+        save_encrypted(project, &dotenv, &test_key(passphrase)).unwrap();
+    }
+
+    fn flip_first_byte(encoded: &str) -> String {
+        let mut bytes = BASE64.decode(encoded).unwrap();
+        bytes[0] ^= 1;
+        BASE64.encode(bytes)
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let project = scratch_project("round-trip");
+        encrypt_sample(&project, "correct horse");
+        let file = read_encrypted(&project);
+        assert_eq!((file.version, file.kdf.as_str(), file.memory_kib, file.iterations, file.salt.as_str()), (1, "argon2id", 8, 1, SALT));
+        assert!(!fs::read_to_string(encrypted_env_path(&project)).unwrap().contains("s3cr3t"));
+
+        let dotenv = decrypt(&file, &test_key("correct horse")).unwrap();
+        assert_eq!(dotenv.get("API_TOKEN").as_deref(), Some("s3cr3t value"));
+        assert_eq!(dotenv.get("DATABASE_URL").as_deref(), Some("postgres://localhost/app"));
+        fs::remove_dir_all(project).unwrap();
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_cleanly() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let project = scratch_project("wrong-passphrase");
+        encrypt_sample(&project, "correct horse");
+        std::env::set_var("XDG_RUNTIME_DIR", project.join("runtime"));
+        std::env::set_var(PASSPHRASE_VARIABLE, "battery staple");
+
+        let result = unlock(&project, false);
+        std::env::remove_var(PASSPHRASE_VARIABLE);
+        assert_eq!(result.err().as_deref(), Some("Wrong passphrase, or .env.enc has been modified."));
+        // Nothing is cached for a failed unlock.
+        assert!(!session_path(&project).unwrap().exists());
+        fs::remove_dir_all(project).unwrap();
+    }
+
+    #[test]
+    fn modified_ciphertext_or_nonce_fails_authentication() {
+        let project = scratch_project("modified");
+        encrypt_sample(&project, "correct horse");
+        let key = test_key("correct horse");
+
+        let mut file = read_encrypted(&project);
+        file.ciphertext = flip_first_byte(&file.ciphertext);
+        assert!(decrypt(&file, &key).is_err());
+
+        let mut file = read_encrypted(&project);
+        file.nonce = flip_first_byte(&file.nonce);
+        assert!(decrypt(&file, &key).is_err());
+
+        let mut file = read_encrypted(&project);
+        file.nonce = BASE64.encode([0u8; 12]);
+        assert!(decrypt(&file, &key).is_err());
+        fs::remove_dir_all(project).unwrap();
+    }
+
+    #[test]
+    fn empty_passphrase_variable_is_rejected() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let project = scratch_project("empty-passphrase");
+        encrypt_sample(&project, "correct horse");
+        std::env::set_var("XDG_RUNTIME_DIR", project.join("runtime"));
+        std::env::set_var(PASSPHRASE_VARIABLE, "");
+
+        let unlocked = unlock(&project, false).err();
+        let chosen = new_passphrase().err();
+        std::env::remove_var(PASSPHRASE_VARIABLE);
+        assert_eq!(unlocked.as_deref(), Some("UPMAN_SECRETS_PASSPHRASE is set but empty."));
+        assert_eq!(chosen.as_deref(), Some("UPMAN_SECRETS_PASSPHRASE is set but empty."));
+        fs::remove_dir_all(project).unwrap();
+    }
+
+    #[test]
+    fn session_is_private_and_expires() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let project = scratch_project("session");
+        encrypt_sample(&project, "correct horse");
+        std::env::set_var("XDG_RUNTIME_DIR", project.join("runtime"));
+        std::env::set_var(PASSPHRASE_VARIABLE, "correct horse");
+        let unlocked = unlock(&project, false);
+        std::env::remove_var(PASSPHRASE_VARIABLE);
+        assert!(unlocked.is_ok());
+
+        let path = session_path(&project).unwrap();
+        assert!(path.starts_with(project.join("runtime").join("upman").join("sessions")));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        // With the key cached, no passphrase is needed.
+        assert!(unlock(&project, false).is_ok());
+
+        // An expired session is discarded rather than used.
+        let mut session: Session = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        session.expires = unix_seconds() - 1;
+        fs::write(&path, toml::to_string(&session).unwrap()).unwrap();
+        assert!(cached_key(&project, &read_encrypted(&project)).is_none());
+        assert!(!path.exists());
+        assert!(unlock(&project, false).is_err());
+        fs::remove_dir_all(project).unwrap();
+    }
+}
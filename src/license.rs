@@ -0,0 +1,780 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+use crate::config::read_config_from;
+use crate::license_audit::{audit_licenses, identify_license_text};
+use crate::manifest::load_manifest;
+use crate::project_database::{find_project_for_dir, load_projects_db};
+use crate::util::{clean_path, current_year, get_install_path};
+
+const YEAR_PLACEHOLDERS: [&str; 4] = ["[year]", "[yyyy]", "<year>", "<YEAR>"];
+const HOLDER_PLACEHOLDERS: [&str; 4] = ["[fullname]", "[name of copyright owner]", "<name of author>", "<COPYRIGHT HOLDER>"];
+
+// Directories that never contain first-party sources worth stamping.
+const SKIPPED_DIRS: [&str; 12] = [".git", "target", "node_modules", "venv", ".venv", "build", "dist", ".build", "bin", "obj", "__pycache__", ".dart_tool"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LicenseEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    #[serde(default)]
+    pub conditions: Vec<String>,
+    #[serde(default)]
+    pub limitations: Vec<String>,
+    #[serde(default)]
+    pub kind: LicenseKind,
+    /// Project licenses that may incorporate code under this (copyleft) license.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compatible_with: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LicenseKind {
+    Permissive,
+    WeakCopyleft,
+    StrongCopyleft,
+    NetworkCopyleft,
+    #[default]
+    Unknown,
+}
+
+impl LicenseKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LicenseKind::Permissive => "permissive",
+            LicenseKind::WeakCopyleft => "weak copyleft",
+            LicenseKind::StrongCopyleft => "strong copyleft",
+            LicenseKind::NetworkCopyleft => "network copyleft",
+            LicenseKind::Unknown => "unclassified",
+        }
+    }
+}
+
+/// Outcome of using a dependency under one license in a project under another, ordered from best to worst.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Compatibility {
+    Ok,
+    Review,
+    Unknown,
+    Violation,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CustomLicenses {
+    licenses: Vec<LicenseEntry>,
+}
+
+fn entry(id: &str, name: &str, aliases: &[&str], permissions: &[&str], conditions: &[&str], limitations: &[&str]) -> LicenseEntry {
+    let owned = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    LicenseEntry {
+        id: id.to_string(),
+        name: name.to_string(),
+        aliases: owned(aliases),
+        permissions: owned(permissions),
+        conditions: owned(conditions),
+        limitations: owned(limitations),
+        kind: LicenseKind::Permissive,
+        compatible_with: Vec::new(),
+    }
+}
+
+impl LicenseEntry {
+    fn copyleft(mut self, kind: LicenseKind, compatible_with: &[&str]) -> Self {
+        self.kind = kind;
+        self.compatible_with = compatible_with.iter().map(|id| id.to_string()).collect();
+        self
+    }
+}
+
+// Metadata follows choosealicense.com's permissions/conditions/limitations vocabulary.
+fn builtin_licenses() -> Vec<LicenseEntry> {
+    const BASIC: [&str; 4] = ["commercial-use", "modifications", "distribution", "private-use"];
+    const PATENT: [&str; 5] = ["commercial-use", "modifications", "distribution", "patent-use", "private-use"];
+    const NO_WARRANTY: [&str; 2] = ["liability", "warranty"];
+
+    const GPL_FAMILY: [&str; 6] = ["GPL-2.0-only", "GPL-2.0-or-later", "GPL-3.0", "LGPL-2.1", "LGPL-3.0", "AGPL-3.0"];
+
+    vec![
+        entry("MIT", "MIT License", &["mit", "expat"], &BASIC, &["include-copyright"], &NO_WARRANTY),
+        entry("Apache-2.0", "Apache License 2.0", &["apache", "apache2", "apache license", "apache software license"], &PATENT, &["include-copyright", "document-changes"], &["trademark-use", "liability", "warranty"]),
+        entry("BSD-3-Clause", "BSD 3-Clause \"New\" or \"Revised\" License", &["bsd", "bsd3", "new bsd", "revised bsd", "bsd license"], &BASIC, &["include-copyright"], &NO_WARRANTY),
+        entry("GPL-2.0-only", "GNU General Public License v2.0 only", &["GPL-2.0", "gpl2", "gplv2", "gnu general public license v2 (gplv2)"], &BASIC, &["include-copyright", "document-changes", "disclose-source", "same-license"], &NO_WARRANTY)
+            .copyleft(LicenseKind::StrongCopyleft, &["GPL-2.0-only", "GPL-2.0-or-later"]),
+        // "Or later" code can be taken under GPL-3.0, which GPL-2.0-only code can't.
+        entry("GPL-2.0-or-later", "GNU General Public License v2.0 or later", &["gpl-2.0+", "gplv2+", "gnu general public license v2 or later (gplv2+)"], &BASIC, &["include-copyright", "document-changes", "disclose-source", "same-license"], &NO_WARRANTY)
+            .copyleft(LicenseKind::StrongCopyleft, &["GPL-2.0-only", "GPL-2.0-or-later", "GPL-3.0", "AGPL-3.0"]),
+        entry("GPL-3.0", "GNU General Public License v3.0", &["gpl", "gpl3", "gplv3", "gpl-3.0-only", "gpl-3.0-or-later", "gpl-3.0+", "gnu general public license v3 (gplv3)"], &PATENT, &["include-copyright", "document-changes", "disclose-source", "same-license"], &NO_WARRANTY)
+            .copyleft(LicenseKind::StrongCopyleft, &["GPL-3.0", "AGPL-3.0"]),
+        entry("LGPL-3.0", "GNU Lesser General Public License v3.0", &["lgpl", "lgpl3", "lgplv3", "lgpl-3.0-only", "lgpl-3.0-or-later", "gnu lesser general public license v3 (lgplv3)"], &PATENT, &["include-copyright", "document-changes", "disclose-source", "same-license--library"], &NO_WARRANTY)
+            .copyleft(LicenseKind::WeakCopyleft, &GPL_FAMILY),
+        entry("AGPL-3.0", "GNU Affero General Public License v3.0", &["agpl", "agpl3", "agplv3", "agpl-3.0-only", "agpl-3.0-or-later"], &PATENT, &["include-copyright", "document-changes", "disclose-source", "network-use-disclose", "same-license"], &NO_WARRANTY)
+            .copyleft(LicenseKind::NetworkCopyleft, &["AGPL-3.0"]),
+        entry("MPL-2.0", "Mozilla Public License 2.0", &["mpl", "mpl2", "mozilla", "mozilla public license 2.0 (mpl 2.0)"], &PATENT, &["include-copyright", "disclose-source", "same-license--file"], &["trademark-use", "liability", "warranty"])
+            .copyleft(LicenseKind::WeakCopyleft, &["MPL-2.0", "GPL-2.0-only", "GPL-2.0-or-later", "GPL-3.0", "LGPL-2.1", "LGPL-3.0", "AGPL-3.0"]),
+        entry("EPL-2.0", "Eclipse Public License 2.0", &["epl", "epl2", "eclipse"], &PATENT, &["include-copyright", "disclose-source", "same-license"], &NO_WARRANTY)
+            .copyleft(LicenseKind::WeakCopyleft, &["EPL-2.0"]),
+        entry("Unlicense", "The Unlicense", &["unlicense", "public domain"], &BASIC, &[], &NO_WARRANTY),
+        // Common dependency licenses without a bundled text, used by 'upman license audit'.
+        entry("ISC", "ISC License", &["isc", "iscl", "isc license (iscl)"], &BASIC, &["include-copyright"], &NO_WARRANTY),
+        entry("BSD-2-Clause", "BSD 2-Clause \"Simplified\" License", &["bsd2", "simplified bsd", "freebsd"], &BASIC, &["include-copyright"], &NO_WARRANTY),
+        entry("0BSD", "BSD Zero Clause License", &["zero-clause bsd"], &BASIC, &[], &NO_WARRANTY),
+        entry("MIT-0", "MIT No Attribution", &["mit no attribution"], &BASIC, &[], &NO_WARRANTY),
+        entry("Zlib", "zlib License", &["zlib/libpng"], &BASIC, &["document-changes"], &NO_WARRANTY),
+        entry("BSL-1.0", "Boost Software License 1.0", &["boost"], &BASIC, &["include-copyright--source"], &NO_WARRANTY),
+        entry("CC0-1.0", "Creative Commons Zero v1.0 Universal", &["cc0"], &BASIC, &[], &["liability", "trademark-use", "patent-use", "warranty"]),
+        entry("Unicode-3.0", "Unicode License v3", &[], &BASIC, &["include-copyright"], &NO_WARRANTY),
+        entry("Unicode-DFS-2016", "Unicode License Agreement - Data Files and Software (2016)", &[], &BASIC, &["include-copyright"], &NO_WARRANTY),
+        entry("PSF-2.0", "Python Software Foundation License 2.0", &["psf", "python software foundation license", "python-2.0"], &BASIC, &["include-copyright", "document-changes"], &NO_WARRANTY),
+        entry("LGPL-2.1", "GNU Lesser General Public License v2.1", &["lgpl2", "lgpl-2.1-only", "lgpl-2.1-or-later", "lgpl-2.1+", "gnu lesser general public license v2 or later (lgplv2+)"], &BASIC, &["include-copyright", "disclose-source", "same-license--library"], &NO_WARRANTY)
+            .copyleft(LicenseKind::WeakCopyleft, &GPL_FAMILY),
+    ]
+}
+
+fn licenses_dir() -> PathBuf {
+    let install_path = get_install_path().expect("Failed to get install path");
+    Path::new(&install_path).join("licenses")
+}
+
+fn custom_registry_path() -> PathBuf {
+    licenses_dir().join("registry.json")
+}
+
+fn load_custom_licenses() -> CustomLicenses {
+    fs::read_to_string(custom_registry_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Built-in licenses followed by user-registered ones. Custom entries replace built-ins with the same id.
+pub fn license_registry() -> Vec<LicenseEntry> {
+    let custom = load_custom_licenses().licenses;
+    let mut registry: Vec<LicenseEntry> = builtin_licenses()
+        .into_iter()
+        .filter(|builtin| !custom.iter().any(|license| license.id.eq_ignore_ascii_case(&builtin.id)))
+        .collect();
+    registry.extend(custom);
+    registry
+}
+
+// Lowercase alphanumerics only, with a trailing ".0" version dropped, so "GPL-v3.0" and "gpl3" compare equal.
+// A trailing "+" reads as "or later", so "GPL-2.0+" and "GPL-2.0-or-later" compare equal but differ from "GPL-2.0".
+fn normalize_license_name(name: &str) -> String {
+    let mut normalized: String = name
+        .to_lowercase()
+        .replace('+', "orlater")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '.')
+        .collect();
+    if normalized.ends_with(".0") {
+        normalized.truncate(normalized.len() - 2);
+    }
+    normalized.replace("gplv", "gpl")
+}
+
+pub fn resolve_license(name: &str) -> Option<LicenseEntry> {
+    let wanted = normalize_license_name(name);
+    license_registry().into_iter().find(|license| {
+        normalize_license_name(&license.id) == wanted
+            || normalize_license_name(&license.name) == wanted
+            || license.aliases.iter().any(|alias| normalize_license_name(alias) == wanted)
+    })
+}
+
+/// Canonicalizes an SPDX expression such as `mit or apache2`, returning the
+/// first unrecognized license on failure.
+pub fn parse_license_expression(input: &str) -> Result<String, String> {
+    if let Some(license) = resolve_license(input) {
+        return Ok(license.id);
+    }
+
+    let spaced = input.replace('(', " ( ").replace(')', " ) ");
+    let mut parts: Vec<String> = Vec::new();
+    let mut after_with = false;
+    for token in spaced.split_whitespace() {
+        let upper = token.to_uppercase();
+        if matches!(upper.as_str(), "OR" | "AND" | "WITH") {
+            after_with = upper == "WITH";
+            parts.push(upper);
+        } else if token == "(" || token == ")" || after_with {
+            after_with = false;
+            parts.push(token.to_string());
+        } else {
+            match resolve_license(token) {
+                Some(license) => parts.push(license.id),
+                None => return Err(token.to_string()),
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        return Err(input.to_string());
+    }
+    Ok(parts.join(" ").replace("( ", "(").replace(" )", ")"))
+}
+
+/// Whether a dependency under `dependency_expression` may be used in a project licensed under
+/// `project_expression`. The expression is evaluated as written, with `AND` binding tighter than `OR`:
+/// `OR` alternatives take the best outcome and `AND` terms the worst; every license the project
+/// offers must accept a copyleft dependency for it to pass.
+pub fn check_compatibility(dependency_expression: &str, project_expression: &str) -> Compatibility {
+    let project_ids = expression_license_ids(project_expression);
+    let expression = parse_license_expression(dependency_expression).unwrap_or_else(|_| dependency_expression.to_string());
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut position = 0;
+    evaluate_or(&tokens, &mut position, &project_ids)
+}
+
+fn evaluate_or(tokens: &[&str], position: &mut usize, project_ids: &[String]) -> Compatibility {
+    let mut result = evaluate_and(tokens, position, project_ids);
+    while tokens.get(*position).is_some_and(|token| token.eq_ignore_ascii_case("OR")) {
+        *position += 1;
+        result = result.min(evaluate_and(tokens, position, project_ids));
+    }
+    result
+}
+
+fn evaluate_and(tokens: &[&str], position: &mut usize, project_ids: &[String]) -> Compatibility {
+    let mut result = evaluate_term(tokens, position, project_ids);
+    while tokens.get(*position).is_some_and(|token| token.eq_ignore_ascii_case("AND")) {
+        *position += 1;
+        result = result.max(evaluate_term(tokens, position, project_ids));
+    }
+    result
+}
+
+// A parenthesized expression, or a license id with an optional `WITH` exception.
+fn evaluate_term(tokens: &[&str], position: &mut usize, project_ids: &[String]) -> Compatibility {
+    match tokens.get(*position) {
+        Some(&"(") => {
+            *position += 1;
+            let result = evaluate_or(tokens, position, project_ids);
+            if tokens.get(*position) == Some(&")") {
+                *position += 1;
+            }
+            result
+        },
+        Some(id) => {
+            *position += 1;
+            if tokens.get(*position).is_some_and(|token| token.eq_ignore_ascii_case("WITH")) {
+                *position += 2;
+            }
+            license_compatibility(id, project_ids)
+        },
+        None => Compatibility::Unknown,
+    }
+}
+
+fn license_compatibility(dependency_id: &str, project_ids: &[String]) -> Compatibility {
+    let Some(license) = resolve_license(dependency_id) else {
+        return Compatibility::Unknown;
+    };
+    let accepted = !project_ids.is_empty()
+        && project_ids.iter().all(|project_id| {
+            let project_id = resolve_license(project_id).map(|project_license| project_license.id).unwrap_or_else(|| project_id.clone());
+            license.compatible_with.iter().any(|id| id.eq_ignore_ascii_case(&project_id))
+        });
+
+    match license.kind {
+        LicenseKind::Permissive => Compatibility::Ok,
+        _ if accepted => Compatibility::Ok,
+        LicenseKind::WeakCopyleft | LicenseKind::Unknown => Compatibility::Review,
+        LicenseKind::StrongCopyleft | LicenseKind::NetworkCopyleft => Compatibility::Violation,
+    }
+}
+
+/// The license ids referenced by an expression, ignoring operators and `WITH` exceptions.
+pub fn expression_license_ids(expression: &str) -> Vec<String> {
+    let spaced = expression.replace(['(', ')'], " ");
+    let mut ids: Vec<String> = Vec::new();
+    let mut after_with = false;
+    for token in spaced.split_whitespace() {
+        match token.to_uppercase().as_str() {
+            "OR" | "AND" => {},
+            "WITH" => after_with = true,
+            _ if after_with => after_with = false,
+            _ => {
+                if !ids.iter().any(|id| id == token) {
+                    ids.push(token.to_string());
+                }
+            },
+        }
+    }
+    ids
+}
+
+fn add_custom_license(id: &str, file: &str) {
+    let source = Path::new(file);
+    if !source.is_file() {
+        eprintln!("License file '{}' not found.", file);
+        return;
+    }
+    if id.is_empty() || id.contains(char::is_whitespace) || id.contains(['/', '\\', '(', ')']) {
+        eprintln!("'{}' is not a valid license identifier.", id);
+        return;
+    }
+
+    let dir = licenses_dir();
+    if let Err(err) = fs::create_dir_all(&dir) {
+        eprintln!("Failed to create licenses directory: {}", err);
+        return;
+    }
+    if let Err(err) = fs::copy(source, dir.join(id)) {
+        eprintln!("Failed to save license text: {}", err);
+        return;
+    }
+
+    let mut custom = load_custom_licenses();
+    custom.licenses.retain(|license| !license.id.eq_ignore_ascii_case(id));
+    custom.licenses.push(LicenseEntry {
+        id: id.to_string(),
+        name: id.to_string(),
+        aliases: Vec::new(),
+        permissions: Vec::new(),
+        conditions: Vec::new(),
+        limitations: Vec::new(),
+        kind: LicenseKind::Unknown,
+        compatible_with: Vec::new(),
+    });
+    let contents = serde_json::to_string_pretty(&custom).expect("Failed to serialize license registry");
+    if let Err(err) = fs::write(custom_registry_path(), contents) {
+        eprintln!("Failed to write license registry: {}", err);
+        return;
+    }
+    println!("Registered custom license '{}'.", id);
+}
+
+pub fn print_license_registry() {
+    let dir = licenses_dir();
+    for license in license_registry() {
+        let has_text = license_text_path(&dir, &license.id).is_some();
+        println!("{} - {}{}", license.id, license.name, if has_text { "" } else { " (license text missing)" });
+        println!("    kind:        {}", license.kind.label());
+        if !license.aliases.is_empty() {
+            println!("    aliases:     {}", license.aliases.join(", "));
+        }
+        if !license.permissions.is_empty() {
+            println!("    permissions: {}", license.permissions.join(", "));
+        }
+        if !license.conditions.is_empty() {
+            println!("    conditions:  {}", license.conditions.join(", "));
+        }
+        if !license.limitations.is_empty() {
+            println!("    limitations: {}", license.limitations.join(", "));
+        }
+    }
+}
+
+// GPL-2.0-only and GPL-2.0-or-later share the GPL-2.0 text.
+fn license_text_path(dir: &Path, id: &str) -> Option<PathBuf> {
+    let base_id = id.trim_end_matches("-only").trim_end_matches("-or-later");
+    let entries: Vec<_> = fs::read_dir(dir).ok()?.flatten().collect();
+    [id, base_id]
+        .iter()
+        .find_map(|wanted| entries.iter().find(|entry| entry.file_name().to_string_lossy().eq_ignore_ascii_case(wanted)))
+        .map(|entry| entry.path())
+}
+
+pub fn license_manager(action: &str, license_id: Option<&str>, license_file: Option<&str>, check: bool, format: &str) {
+    if action == "add" {
+        match (license_id, license_file) {
+            (Some(id), Some(file)) => add_custom_license(id, file),
+            _ => eprintln!("Usage: upman license add <LICENSE_ID> <LICENSE_FILE>"),
+        }
+        return;
+    }
+
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((_project_name, info)) = find_project_for_dir(&db, &current_dir_str) else {
+        println!("This directory is not recognized as a UPM project.");
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
+
+    match action {
+        "headers" => {
+            let license_id = project_license(project_path);
+            let (missing, updated) = apply_headers(project_path, &license_id, check);
+            if check {
+                if missing.is_empty() {
+                    println!("All source files carry 'SPDX-License-Identifier: {}'.", license_id);
+                } else {
+                    for file in &missing {
+                        println!("Missing or incorrect SPDX header: {}", file.display());
+                    }
+                    eprintln!("{} file(s) do not carry 'SPDX-License-Identifier: {}'.", missing.len(), license_id);
+                    process::exit(1);
+                }
+            } else {
+                println!("Added or updated SPDX headers in {} file(s).", updated);
+            }
+        },
+        "audit" => {
+            let license = project_license(project_path);
+            if !audit_licenses(project_path, &license, format) {
+                process::exit(1);
+            }
+        },
+        _ => println!("Unsupported action '{}'. Use 'headers', 'audit' or 'add'.", action),
+    }
+}
+
+/// Fills in the year and copyright holder placeholders used by the bundled license texts.
+pub fn render_license(template: &str, year: i64, holder: Option<&str>) -> String {
+    let mut rendered = template.to_string();
+    for placeholder in YEAR_PLACEHOLDERS {
+        rendered = rendered.replace(placeholder, &year.to_string());
+    }
+    if let Some(holder) = holder {
+        for placeholder in HOLDER_PLACEHOLDERS {
+            rendered = rendered.replace(placeholder, holder);
+        }
+    }
+    rendered
+}
+
+/// The configured author, falling back to `git config user.name`.
+pub fn copyright_holder() -> Option<String> {
+    let config = read_config_from();
+    if let Some(author) = config.preferences.author.filter(|author| !author.trim().is_empty()) {
+        return Some(author);
+    }
+
+    let output = Command::new("git").args(["config", "user.name"]).output().ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !name.is_empty() {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Writes the license text for every license in `expression`. A single license goes to
+/// `LICENSE`; dual licenses go to `LICENSE-<ID>` files, as is conventional for Rust crates.
+pub fn initialize_project_license(project_path: &Path, expression: &str) {
+    let expression = match parse_license_expression(expression) {
+        Ok(expression) => expression,
+        Err(unknown) => {
+            eprintln!("The license '{}' is not recognized. Try 'upman list licenses'.", unknown);
+            return;
+        }
+    };
+
+    let holder = copyright_holder();
+    if holder.is_none() {
+        eprintln!("No author configured. Set one with 'upman config author <NAME>' or 'git config user.name'.");
+    }
+
+    let dir = licenses_dir();
+    let ids = expression_license_ids(&expression);
+    let version_suffix = Regex::new(r"-\d+(\.\d+)*$").unwrap();
+    for id in &ids {
+        let Some(text_path) = license_text_path(&dir, id) else {
+            eprintln!("No license text found for '{}' in {}.", id, dir.display());
+            continue;
+        };
+        let template = fs::read_to_string(text_path).expect("Failed to read license file");
+        let file_name = if ids.len() == 1 {
+            "LICENSE".to_string()
+        } else {
+            format!("LICENSE-{}", version_suffix.replace(id, "").to_uppercase())
+        };
+
+        let content = render_license(&template, current_year(), holder.as_deref());
+        fs::write(project_path.join(&file_name), content).expect("Failed to create LICENSE file");
+        println!("Initialized {} with the {} license.", file_name, id);
+    }
+
+    set_manifest_license(project_path, &expression);
+}
+
+/// Records the license in whichever package manifests the project has.
+pub fn set_manifest_license(project_path: &Path, license_id: &str) {
+    let cargo_toml = project_path.join("Cargo.toml");
+    if let Ok(contents) = fs::read_to_string(&cargo_toml) {
+        let updated = set_toml_key(&contents, "package", "license", &format!("\"{}\"", license_id));
+        if fs::write(&cargo_toml, updated).is_ok() {
+            println!("Set license in Cargo.toml.");
+        }
+    }
+
+    let pyproject = project_path.join("pyproject.toml");
+    if let Ok(contents) = fs::read_to_string(&pyproject) {
+        let section = if contents.contains("[tool.poetry]") { "tool.poetry" } else { "project" };
+        let updated = set_toml_key(&contents, section, "license", &format!("\"{}\"", license_id));
+        if fs::write(&pyproject, updated).is_ok() {
+            println!("Set license in pyproject.toml.");
+        }
+    }
+
+    let package_json = project_path.join("package.json");
+    if let Ok(contents) = fs::read_to_string(&package_json) {
+        let updated = set_json_license(&contents, license_id);
+        if fs::write(&package_json, updated).is_ok() {
+            println!("Set license in package.json.");
+        }
+    }
+}
+
+/// Reads the project's license from its manifest, falling back to the preferred license.
+pub fn project_license(project_path: &Path) -> String {
+    if let Some(license) = load_manifest(project_path).and_then(|manifest| manifest.project.license) {
+        return license;
+    }
+
+    for (manifest, table) in [("Cargo.toml", "package"), ("pyproject.toml", "project")] {
+        let Some(manifest) = fs::read_to_string(project_path.join(manifest)).ok().and_then(|contents| contents.parse::<toml::Table>().ok()) else {
+            continue;
+        };
+        let license = match manifest.get(table).and_then(|table| table.get("license")) {
+            Some(toml::Value::String(license)) => Some(license.clone()),
+            // pyproject.toml may also give `{ text = "..." }` or `{ file = "LICENSE" }`.
+            Some(toml::Value::Table(license)) => match (license.get("text").and_then(|text| text.as_str()), license.get("file").and_then(|file| file.as_str())) {
+                (Some(text), _) if !text.trim().contains('\n') => Some(text.trim().to_string()),
+                (Some(text), _) => identify_license_text(text),
+                (None, Some(file)) => fs::read_to_string(project_path.join(file)).ok().and_then(|text| identify_license_text(&text)),
+                (None, None) => None,
+            },
+            _ => None,
+        };
+        if let Some(license) = license.filter(|license| !license.is_empty()) {
+            return license;
+        }
+    }
+
+    if let Ok(contents) = fs::read_to_string(project_path.join("package.json")) {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&contents) {
+            if let Some(license) = json.get("license").and_then(|license| license.as_str()) {
+                return license.to_string();
+            }
+        }
+    }
+
+    read_config_from().preferences.license
+}
+
+/// Sets `key = value` inside `[section]`, replacing an existing entry or appending one.
+pub fn set_toml_key(contents: &str, section: &str, key: &str, value: &str) -> String {
+    let header = format!("[{}]", section);
+    let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+    let entry = format!("{} = {}", key, value);
+
+    let Some(start) = lines.iter().position(|line| line.trim() == header) else {
+        let mut updated = contents.trim_end().to_string();
+        if !updated.is_empty() {
+            updated.push_str("\n\n");
+        }
+        updated.push_str(&format!("{}\n{}\n", header, entry));
+        return updated;
+    };
+
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map(|offset| start + 1 + offset)
+        .unwrap_or(lines.len());
+
+    let key_prefix = Regex::new(&format!(r"^\s*{}\s*=", regex::escape(key))).unwrap();
+    if let Some(existing) = (start + 1..end).find(|&i| key_prefix.is_match(&lines[i])) {
+        lines[existing] = entry;
+    } else {
+        // Insert after the last non-blank line of the section so spacing is kept.
+        let mut insert_at = end;
+        while insert_at > start + 1 && lines[insert_at - 1].trim().is_empty() {
+            insert_at -= 1;
+        }
+        lines.insert(insert_at, entry);
+    }
+
+    let mut updated = lines.join("\n");
+    if contents.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated
+}
+
+fn set_json_license(contents: &str, license_id: &str) -> String {
+    let existing = Regex::new(r#"("license"\s*:\s*)"[^"]*""#).unwrap();
+    if existing.is_match(contents) {
+        return existing.replace(contents, format!("${{1}}\"{}\"", license_id)).to_string();
+    }
+
+    let version = Regex::new(r#"(?m)^(\s*)("version"\s*:\s*"[^"]*")(,?)[ \t]*$"#).unwrap();
+    if let Some(captures) = version.captures(contents) {
+        let whole = captures.get(0).unwrap();
+        let (indent, entry, comma) = (&captures[1], &captures[2], &captures[3]);
+        let replacement = format!("{}{},\n{}\"license\": \"{}\"{}", indent, entry, indent, license_id, comma);
+        return format!("{}{}{}", &contents[..whole.start()], replacement, &contents[whole.end()..]);
+    }
+
+    contents.to_string()
+}
+
+/// Line comment prefix and suffix used for the SPDX header of a given source file.
+fn comment_syntax(path: &Path) -> Option<(&'static str, &'static str)> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "rs" | "c" | "h" | "cpp" | "cc" | "cxx" | "hpp" | "hh" | "java" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "go" | "cs" | "swift" | "dart" | "scala" | "kt" | "kts" => Some(("// ", "")),
+        "py" | "rb" | "sh" | "bash" | "r" | "pl" | "pm" => Some(("# ", "")),
+        "html" | "htm" | "xml" | "vue" => Some(("<!-- ", " -->")),
+        "css" | "scss" => Some(("/* ", " */")),
+        "sql" | "lua" | "hs" => Some(("-- ", "")),
+        _ => None,
+    }
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let name = entry.file_name();
+            if !SKIPPED_DIRS.contains(&name.to_string_lossy().as_ref()) {
+                collect_sources(&path, files);
+            }
+        } else if comment_syntax(&path).is_some() {
+            files.push(path);
+        }
+    }
+}
+
+/// Inserts or verifies SPDX headers. Returns the files lacking a correct header and
+/// the number of files written.
+fn apply_headers(project_path: &Path, license_id: &str, check: bool) -> (Vec<PathBuf>, usize) {
+    let mut files = Vec::new();
+    collect_sources(project_path, &mut files);
+    files.sort();
+
+    let mut missing = Vec::new();
+    let mut updated = 0;
+    for file in files {
+        let Ok(contents) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let (prefix, suffix) = comment_syntax(&file).unwrap();
+        let header = format!("{}SPDX-License-Identifier: {}{}", prefix, license_id, suffix);
+
+        let Some(rewritten) = with_header(&contents, &header) else {
+            continue;
+        };
+        missing.push(file.clone());
+        if check {
+            continue;
+        }
+        match fs::write(&file, rewritten) {
+            Ok(_) => updated += 1,
+            Err(err) => eprintln!("Failed to write header to {}: {}", file.display(), err),
+        }
+    }
+
+    (missing, updated)
+}
+
+// The file with `header` added or its outdated SPDX line replaced, or `None` when it already has the header. The
+// file's line endings are kept.
+fn with_header(contents: &str, header: &str) -> Option<String> {
+    let mut lines: Vec<&str> = contents.lines().collect();
+    // Leave shebangs (and a Python encoding line after one) at the top of the file.
+    let mut insert_at = 0;
+    if lines.first().is_some_and(|line| line.starts_with("#!")) {
+        insert_at = 1;
+    }
+    if lines.get(insert_at).is_some_and(|line| line.starts_with("# -*-") || line.starts_with("<?xml") || line.to_lowercase().starts_with("<!doctype")) {
+        insert_at += 1;
+    }
+
+    match lines.iter().take(insert_at + 5).position(|line| line.contains("SPDX-License-Identifier:")) {
+        Some(index) if lines[index].trim() == header.trim() => return None,
+        Some(index) => lines[index] = header,
+        None => lines.insert(insert_at, header),
+    }
+    let newline = if contents.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut rewritten = lines.join(newline);
+    if contents.ends_with('\n') || contents.is_empty() {
+        rewritten.push_str(newline);
+    }
+    Some(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_license_comes_from_the_package_table() {
+        let fixtures = Path::new("tests/fixtures/license");
+        assert_eq!(project_license(&fixtures.join("cargo")), "MIT OR Apache-2.0");
+        assert_eq!(project_license(&fixtures.join("pyproject-string")), "BSD-3-Clause");
+        assert_eq!(project_license(&fixtures.join("pyproject-text")), "Apache-2.0");
+        assert_eq!(project_license(&fixtures.join("pyproject-file")), "MIT");
+    }
+
+    #[test]
+    fn headers_keep_line_endings() {
+        let header = "// SPDX-License-Identifier: MIT";
+        assert_eq!(with_header("fn main() {}\n", header).unwrap(), "// SPDX-License-Identifier: MIT\nfn main() {}\n");
+        assert_eq!(
+            with_header("fn main() {\r\n}\r\n", header).unwrap(),
+            "// SPDX-License-Identifier: MIT\r\nfn main() {\r\n}\r\n"
+        );
+        assert_eq!(
+            with_header("#!/usr/bin/env python3\r\n# SPDX-License-Identifier: GPL-3.0\r\nprint(1)", "# SPDX-License-Identifier: MIT").unwrap(),
+            "#!/usr/bin/env python3\r\n# SPDX-License-Identifier: MIT\r\nprint(1)"
+        );
+        assert_eq!(with_header("// SPDX-License-Identifier: MIT\r\nint x;\r\n", header), None);
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(check_compatibility("MIT OR Apache-2.0 AND GPL-3.0", "MIT"), Compatibility::Ok);
+        assert_eq!(check_compatibility("GPL-3.0 AND MIT OR Apache-2.0", "MIT"), Compatibility::Ok);
+    }
+
+    #[test]
+    fn parentheses_keep_their_grouping() {
+        assert_eq!(check_compatibility("(MIT OR Apache-2.0) AND GPL-3.0", "MIT"), Compatibility::Violation);
+        assert_eq!(check_compatibility("GPL-3.0 AND (MIT OR Apache-2.0)", "MIT"), Compatibility::Violation);
+        assert_eq!(check_compatibility("(GPL-3.0 OR LGPL-3.0) AND MIT", "MIT"), Compatibility::Review);
+        assert_eq!(check_compatibility("MIT AND (Apache-2.0 OR (GPL-3.0 AND BSD-3-Clause))", "MIT"), Compatibility::Ok);
+        assert_eq!(check_compatibility("((GPL-3.0 OR AGPL-3.0) AND (MIT OR ISC))", "MIT"), Compatibility::Violation);
+    }
+
+    #[test]
+    fn copyleft_is_accepted_by_every_project_license() {
+        assert_eq!(check_compatibility("(MIT OR Apache-2.0) AND GPL-3.0", "GPL-3.0"), Compatibility::Ok);
+        assert_eq!(check_compatibility("GPL-3.0", "GPL-3.0 OR MIT"), Compatibility::Violation);
+    }
+
+    #[test]
+    fn gpl2_only_is_not_gpl3_compatible() {
+        assert_eq!(resolve_license("GPL-2.0").unwrap().id, "GPL-2.0-only");
+        assert_eq!(resolve_license("GPL-2.0+").unwrap().id, "GPL-2.0-or-later");
+        assert_eq!(check_compatibility("GPL-2.0-only", "GPL-3.0"), Compatibility::Violation);
+        assert_eq!(check_compatibility("GPL-2.0-only", "AGPL-3.0"), Compatibility::Violation);
+        assert_eq!(check_compatibility("GPL-2.0-or-later", "GPL-3.0"), Compatibility::Ok);
+        assert_eq!(check_compatibility("GPL-2.0-only", "GPL-2.0-or-later"), Compatibility::Ok);
+        assert_eq!(check_compatibility("GPL-2.0", "GPL-2.0"), Compatibility::Ok);
+    }
+
+    #[test]
+    fn exceptions_and_unknown_licenses() {
+        assert_eq!(check_compatibility("Apache-2.0 WITH LLVM-exception", "MIT"), Compatibility::Ok);
+        assert_eq!(check_compatibility("(MIT OR Apache-2.0) AND Nonexistent-1.0", "MIT"), Compatibility::Unknown);
+    }
+}
@@ -0,0 +1,463 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+use crate::license::{check_compatibility, parse_license_expression, Compatibility};
+use crate::util::{home_dir, site_packages_dirs};
+
+#[derive(Serialize)]
+pub struct DependencyLicense {
+    pub name: String,
+    pub version: String,
+    pub ecosystem: String,
+    pub license: Option<String>,
+    pub status: Compatibility,
+}
+
+#[derive(Serialize)]
+struct AuditReport<'a> {
+    project_license: &'a str,
+    dependencies: &'a [DependencyLicense],
+    violations: usize,
+}
+
+/// Collects dependency licenses from metadata already on disk and checks them against
+/// `project_license`. Returns true when no dependency violates the project license.
+pub fn audit_licenses(project_path: &Path, project_license: &str, format: &str) -> bool {
+    let mut dependencies = dependency_licenses(project_path);
+    let violations = check_dependencies(&mut dependencies, project_license);
+    if format == "json" {
+        println!("{}", json_report(&dependencies, project_license, violations));
+    } else {
+        print_table(&dependencies, project_license, violations);
+    }
+
+    violations == 0
+}
+
+// Sets each dependency's status, worst first, and returns the number of violations.
+fn check_dependencies(dependencies: &mut [DependencyLicense], project_license: &str) -> usize {
+    for dependency in dependencies.iter_mut() {
+        dependency.status = match &dependency.license {
+            Some(license) => check_compatibility(license, project_license),
+            None => Compatibility::Unknown,
+        };
+    }
+    dependencies.sort_by(|a, b| b.status.cmp(&a.status).then_with(|| a.name.cmp(&b.name)));
+    dependencies.iter().filter(|dependency| dependency.status == Compatibility::Violation).count()
+}
+
+fn json_report(dependencies: &[DependencyLicense], project_license: &str, violations: usize) -> String {
+    let report = AuditReport { project_license, dependencies, violations };
+    serde_json::to_string_pretty(&report).expect("Failed to serialize license report")
+}
+
+/// The licenses of the project's Cargo, npm, Python and Go dependencies, read from the registry sources,
+/// node_modules, the venv's dist-info and the module cache.
+pub fn dependency_licenses(project_path: &Path) -> Vec<DependencyLicense> {
+    let mut dependencies = Vec::new();
+    dependencies.extend(cargo_licenses(project_path));
+    dependencies.extend(node_licenses(project_path));
+    dependencies.extend(python_licenses(project_path));
+    dependencies.extend(go_licenses(project_path));
+    dependencies
+}
+
+fn print_table(dependencies: &[DependencyLicense], project_license: &str, violations: usize) {
+    if dependencies.is_empty() {
+        println!("No dependency metadata found. Build or install the project's dependencies first.");
+        return;
+    }
+
+    let name_width = dependencies.iter().map(|d| d.name.len()).max().unwrap_or(0).max("PACKAGE".len());
+    let version_width = dependencies.iter().map(|d| d.version.len()).max().unwrap_or(0).max("VERSION".len());
+    println!("{:<name_width$}  {:<version_width$}  {:<9}  {:<9}  LICENSE", "PACKAGE", "VERSION", "ECOSYSTEM", "STATUS");
+    for dependency in dependencies {
+        let status = match dependency.status {
+            Compatibility::Ok => "ok",
+            Compatibility::Review => "review",
+            Compatibility::Unknown => "unknown",
+            Compatibility::Violation => "VIOLATION",
+        };
+        println!(
+            "{:<name_width$}  {:<version_width$}  {:<9}  {:<9}  {}",
+            dependency.name,
+            dependency.version,
+            dependency.ecosystem,
+            status,
+            dependency.license.as_deref().unwrap_or("-")
+        );
+    }
+
+    println!(" ");
+    println!("{}", summary_line(dependencies, project_license, violations));
+}
+
+fn summary_line(dependencies: &[DependencyLicense], project_license: &str, violations: usize) -> String {
+    let review = dependencies.iter().filter(|d| matches!(d.status, Compatibility::Review | Compatibility::Unknown)).count();
+    format!("{} dependencies checked against {}: {} violation(s), {} to review.", dependencies.len(), project_license, violations, review)
+}
+
+fn dependency(name: &str, version: &str, ecosystem: &str, license: Option<String>) -> DependencyLicense {
+    DependencyLicense {
+        name: name.to_string(),
+        version: version.to_string(),
+        ecosystem: ecosystem.to_string(),
+        license,
+        status: Compatibility::Unknown,
+    }
+}
+
+// Canonicalizes a declared license, keeping the raw text if it is not a recognized expression.
+fn normalize_declared(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if raw.is_empty() || raw.eq_ignore_ascii_case("UNKNOWN") {
+        return None;
+    }
+    // Older crates and packages use "MIT/Apache-2.0" to mean a choice of licenses.
+    let expression = raw.replace('/', " OR ");
+    Some(parse_license_expression(&expression).unwrap_or_else(|_| raw.to_string()))
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    env::var_os("CARGO_HOME").map(PathBuf::from).or_else(|| home_dir().map(|home| home.join(".cargo")))
+}
+
+fn cargo_licenses(project_path: &Path) -> Vec<DependencyLicense> {
+    let Ok(contents) = fs::read_to_string(project_path.join("Cargo.lock")) else {
+        return Vec::new();
+    };
+    let Ok(lock) = contents.parse::<toml::Table>() else {
+        eprintln!("Failed to parse Cargo.lock.");
+        return Vec::new();
+    };
+    let registry_dirs: Vec<PathBuf> = cargo_home()
+        .and_then(|home| fs::read_dir(home.join("registry").join("src")).ok())
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+
+    let packages = lock.get("package").and_then(|packages| packages.as_array()).cloned().unwrap_or_default();
+    packages
+        .iter()
+        .filter(|package| package.get("source").and_then(|source| source.as_str()).is_some_and(|source| source.starts_with("registry+")))
+        .filter_map(|package| {
+            let name = package.get("name")?.as_str()?;
+            let version = package.get("version")?.as_str()?;
+            let license = registry_dirs
+                .iter()
+                .map(|dir| dir.join(format!("{}-{}", name, version)).join("Cargo.toml"))
+                .find(|manifest| manifest.exists())
+                .and_then(|manifest| fs::read_to_string(manifest).ok())
+                .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+                .and_then(|manifest| {
+                    let package = manifest.get("package")?;
+                    match package.get("license").and_then(|license| license.as_str()) {
+                        Some(license) => normalize_declared(license),
+                        None => package.get("license-file").map(|_| "LicenseRef-file".to_string()),
+                    }
+                });
+            Some(dependency(name, version, "cargo", license))
+        })
+        .collect()
+}
+
+fn node_licenses(project_path: &Path) -> Vec<DependencyLicense> {
+    let node_modules = project_path.join("node_modules");
+    let Ok(entries) = fs::read_dir(&node_modules) else {
+        return Vec::new();
+    };
+
+    let mut package_dirs = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.starts_with('@') {
+            if let Ok(scoped) = fs::read_dir(entry.path()) {
+                package_dirs.extend(scoped.flatten().map(|scoped| scoped.path()));
+            }
+        } else {
+            package_dirs.push(entry.path());
+        }
+    }
+
+    package_dirs
+        .iter()
+        .filter_map(|dir| {
+            let contents = fs::read_to_string(dir.join("package.json")).ok()?;
+            let json: serde_json::Value = serde_json::from_str(&contents).ok()?;
+            let name = json.get("name")?.as_str()?;
+            let version = json.get("version").and_then(|version| version.as_str()).unwrap_or("");
+            let license = match json.get("license") {
+                Some(serde_json::Value::String(license)) => normalize_declared(license),
+                Some(license) => license.get("type").and_then(|kind| kind.as_str()).and_then(normalize_declared),
+                // The deprecated "licenses" array lists alternatives.
+                None => json.get("licenses").and_then(|licenses| licenses.as_array()).and_then(|licenses| {
+                    let types: Vec<&str> = licenses.iter().filter_map(|license| license.get("type")?.as_str()).collect();
+                    normalize_declared(&types.join(" OR "))
+                }),
+            };
+            Some(dependency(name, version, "npm", license))
+        })
+        .collect()
+}
+
+fn python_licenses(project_path: &Path) -> Vec<DependencyLicense> {
+    let mut dependencies = Vec::new();
+    for site_packages in site_packages_dirs(project_path) {
+        let Ok(entries) = fs::read_dir(site_packages) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().ends_with(".dist-info") {
+                continue;
+            }
+            if let Ok(metadata) = fs::read_to_string(entry.path().join("METADATA")) {
+                if let Some(dependency) = parse_python_metadata(&metadata) {
+                    dependencies.push(dependency);
+                }
+            }
+        }
+    }
+    dependencies
+}
+
+fn parse_python_metadata(metadata: &str) -> Option<DependencyLicense> {
+    let mut name = None;
+    let mut version = None;
+    let mut expression = None;
+    let mut license_field = None;
+    let mut classifiers = Vec::new();
+
+    // Only the RFC 822 style header block carries metadata; the body is the long description.
+    for line in metadata.lines().take_while(|line| !line.is_empty()) {
+        let Some((key, value)) = line.split_once(": ") else {
+            continue;
+        };
+        match key {
+            "Name" => name = Some(value.trim()),
+            "Version" => version = Some(value.trim()),
+            "License-Expression" => expression = Some(value.trim()),
+            "License" => license_field = Some(value.trim()),
+            "Classifier" if value.starts_with("License ::") => {
+                if let Some(last) = value.rsplit(" :: ").next() {
+                    classifiers.push(last.trim());
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let license = expression
+        .and_then(normalize_declared)
+        .or_else(|| {
+            // Some packages paste the whole license text into "License"; only trust short values.
+            license_field
+                .filter(|license| license.len() <= 64)
+                .and_then(|license| parse_license_expression(license).ok())
+        })
+        .or_else(|| {
+            let resolved: Vec<String> = classifiers.iter().filter_map(|classifier| parse_license_expression(classifier).ok()).collect();
+            (!resolved.is_empty()).then(|| resolved.join(" OR "))
+        })
+        .or_else(|| license_field.filter(|license| license.len() <= 64).and_then(normalize_declared));
+
+    Some(dependency(name?, version.unwrap_or(""), "pypi", license))
+}
+
+fn go_module_cache() -> Option<PathBuf> {
+    if let Some(cache) = env::var_os("GOMODCACHE") {
+        return Some(PathBuf::from(cache));
+    }
+    let gopath = env::var_os("GOPATH").map(PathBuf::from).or_else(|| home_dir().map(|home| home.join("go")))?;
+    Some(gopath.join("pkg").join("mod"))
+}
+
+// The module cache stores upper-case letters as '!' followed by the lower-case letter.
+fn escape_module_path(module: &str) -> String {
+    let mut escaped = String::new();
+    for c in module.chars() {
+        if c.is_ascii_uppercase() {
+            escaped.push('!');
+            escaped.push(c.to_ascii_lowercase());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+fn go_licenses(project_path: &Path) -> Vec<DependencyLicense> {
+    let Ok(go_mod) = fs::read_to_string(project_path.join("go.mod")) else {
+        return Vec::new();
+    };
+    let cache = go_module_cache();
+
+    let mut requirements = Vec::new();
+    let mut in_block = false;
+    for line in go_mod.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if line.starts_with("require (") {
+            in_block = true;
+            continue;
+        }
+        if in_block && line == ")" {
+            in_block = false;
+            continue;
+        }
+        let requirement = if in_block { Some(line) } else { line.strip_prefix("require ") };
+        if let Some((module, version)) = requirement.and_then(|requirement| requirement.split_once(char::is_whitespace)) {
+            requirements.push((module.to_string(), version.trim().to_string()));
+        }
+    }
+
+    requirements
+        .iter()
+        .map(|(module, version)| {
+            let license = cache
+                .as_ref()
+                .map(|cache| cache.join(format!("{}@{}", escape_module_path(module), version)))
+                .and_then(|dir| fs::read_dir(dir).ok())
+                .and_then(|entries| {
+                    entries
+                        .flatten()
+                        .filter(|entry| {
+                            let name = entry.file_name().to_string_lossy().to_uppercase();
+                            name.starts_with("LICENSE") || name.starts_with("LICENCE") || name.starts_with("COPYING")
+                        })
+                        .find_map(|entry| fs::read_to_string(entry.path()).ok().and_then(|text| identify_license_text(&text)))
+                });
+            dependency(module, version, "go", license)
+        })
+        .collect()
+}
+
+/// Recognizes the common license texts by their distinctive phrases.
+pub fn identify_license_text(text: &str) -> Option<String> {
+    let upper = text.to_uppercase();
+    let id = if upper.contains("GNU AFFERO GENERAL PUBLIC LICENSE") {
+        "AGPL-3.0"
+    } else if upper.contains("GNU LESSER GENERAL PUBLIC LICENSE") {
+        if upper.contains("VERSION 3") { "LGPL-3.0" } else { "LGPL-2.1" }
+    } else if upper.contains("GNU GENERAL PUBLIC LICENSE") {
+        // The text alone doesn't say whether "or later" applies, so assume the stricter reading.
+        if upper.contains("VERSION 3") { "GPL-3.0" } else { "GPL-2.0-only" }
+    } else if upper.contains("MOZILLA PUBLIC LICENSE") && upper.contains("2.0") {
+        "MPL-2.0"
+    } else if upper.contains("APACHE LICENSE") && upper.contains("VERSION 2.0") {
+        "Apache-2.0"
+    } else if upper.contains("ECLIPSE PUBLIC LICENSE") {
+        "EPL-2.0"
+    } else if upper.contains("PERMISSION IS HEREBY GRANTED, FREE OF CHARGE") {
+        "MIT"
+    } else if upper.contains("PERMISSION TO USE, COPY, MODIFY, AND/OR DISTRIBUTE") {
+        "ISC"
+    } else if upper.contains("REDISTRIBUTION AND USE IN SOURCE AND BINARY FORMS") {
+        if upper.contains("NEITHER THE NAME") || upper.contains("3. ") { "BSD-3-Clause" } else { "BSD-2-Clause" }
+    } else if upper.contains("FREE AND UNENCUMBERED SOFTWARE RELEASED INTO THE PUBLIC DOMAIN") {
+        "Unlicense"
+    } else {
+        return None;
+    };
+    Some(id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> &'static Path {
+        Path::new("tests/fixtures/license_audit/project")
+    }
+
+    fn metadata_license(metadata: &str) -> Option<String> {
+        parse_python_metadata(metadata).unwrap().license
+    }
+
+    #[test]
+    fn python_metadata_precedence() {
+        // License-Expression wins over License and the classifiers; the description body is ignored.
+        let attrs = include_str!("../tests/fixtures/license_audit/project/.venv/lib/python3.12/site-packages/attrs-23.2.0.dist-info/METADATA");
+        let attrs = parse_python_metadata(attrs).unwrap();
+        assert_eq!((attrs.name.as_str(), attrs.version.as_str(), attrs.ecosystem.as_str()), ("attrs", "23.2.0", "pypi"));
+        assert_eq!(attrs.license.as_deref(), Some("MIT"));
+
+        let requests = include_str!("../tests/fixtures/license_audit/project/.venv/lib/python3.12/site-packages/requests-2.32.3.dist-info/METADATA");
+        assert_eq!(metadata_license(requests).as_deref(), Some("Apache-2.0"));
+        // "UNKNOWN" falls through to the classifier.
+        let pyyaml = include_str!("../tests/fixtures/license_audit/project/.venv/lib/python3.12/site-packages/PyYAML-6.0.1.dist-info/METADATA");
+        assert_eq!(metadata_license(pyyaml).as_deref(), Some("MIT"));
+        // A pasted license text is not mistaken for a license name.
+        let pasted = include_str!("../tests/fixtures/license_audit/project/.venv/lib/python3.12/site-packages/pasted-1.0.dist-info/METADATA");
+        assert_eq!(metadata_license(pasted), None);
+
+        assert_eq!(metadata_license("Name: dual\nClassifier: License :: OSI Approved :: MIT License\nClassifier: License :: OSI Approved :: ISC License (ISCL)\n").as_deref(), Some("MIT OR ISC"));
+        // A short License value that isn't an SPDX expression is kept as written.
+        assert_eq!(metadata_license("Name: internal\nLicense: Proprietary\n").as_deref(), Some("Proprietary"));
+        assert!(parse_python_metadata("Version: 1.0\n").is_none());
+    }
+
+    #[test]
+    fn go_module_paths_escape_upper_case() {
+        assert_eq!(escape_module_path("github.com/BurntSushi/toml"), "github.com/!burnt!sushi/toml");
+        assert_eq!(escape_module_path("golang.org/x/text"), "golang.org/x/text");
+    }
+
+    #[test]
+    fn license_texts() {
+        let mit = "Permission is hereby granted, free of charge, to any person obtaining a copy of this software";
+        assert_eq!(identify_license_text(mit).as_deref(), Some("MIT"));
+        assert_eq!(identify_license_text("Apache License\nVersion 2.0, January 2004").as_deref(), Some("Apache-2.0"));
+        assert_eq!(identify_license_text("GNU GENERAL PUBLIC LICENSE\nVersion 3, 29 June 2007").as_deref(), Some("GPL-3.0"));
+        assert_eq!(identify_license_text("GNU GENERAL PUBLIC LICENSE\nVersion 2, June 1991").as_deref(), Some("GPL-2.0-only"));
+        assert_eq!(identify_license_text("GNU LESSER GENERAL PUBLIC LICENSE\nVersion 2.1").as_deref(), Some("LGPL-2.1"));
+        let bsd3 = "Redistribution and use in source and binary forms, with or without modification... Neither the name of the copyright holder";
+        assert_eq!(identify_license_text(bsd3).as_deref(), Some("BSD-3-Clause"));
+        assert_eq!(identify_license_text("Redistribution and use in source and binary forms are permitted").as_deref(), Some("BSD-2-Clause"));
+        assert_eq!(identify_license_text("All rights reserved."), None);
+    }
+
+    #[test]
+    fn declared_licenses() {
+        assert_eq!(normalize_declared("MIT/Apache-2.0").as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(normalize_declared(" mit ").as_deref(), Some("MIT"));
+        assert_eq!(normalize_declared("SEE LICENSE IN LICENSE.txt").as_deref(), Some("SEE LICENSE IN LICENSE.txt"));
+        assert_eq!(normalize_declared("UNKNOWN"), None);
+        assert_eq!(normalize_declared("  "), None);
+    }
+
+    #[test]
+    fn audit_counts_violations() {
+        let mut dependencies = dependency_licenses(fixture());
+        let violations = check_dependencies(&mut dependencies, "MIT");
+        let statuses: Vec<(&str, Option<&str>, Compatibility)> =
+            dependencies.iter().map(|dependency| (dependency.name.as_str(), dependency.license.as_deref(), dependency.status)).collect();
+        assert_eq!(
+            statuses,
+            [
+                ("@acme/gpl-widget", Some("GPL-3.0"), Compatibility::Violation),
+                ("left-pad", Some("SEE LICENSE IN LICENSE.txt"), Compatibility::Unknown),
+                ("pasted", None, Compatibility::Unknown),
+                ("PyYAML", Some("MIT"), Compatibility::Ok),
+                ("attrs", Some("MIT"), Compatibility::Ok),
+                ("legacy", Some("MIT OR Apache-2.0"), Compatibility::Ok),
+                ("old-style", Some("MIT OR Apache-2.0"), Compatibility::Ok),
+                ("requests", Some("Apache-2.0"), Compatibility::Ok),
+            ]
+        );
+        assert_eq!(violations, 1);
+        assert_eq!(summary_line(&dependencies, "MIT", violations), "8 dependencies checked against MIT: 1 violation(s), 2 to review.");
+
+        let report: serde_json::Value = serde_json::from_str(&json_report(&dependencies, "MIT", violations)).unwrap();
+        assert_eq!(report["violations"], 1);
+        assert_eq!(report["project_license"], "MIT");
+        assert_eq!(report["dependencies"][0]["name"], "@acme/gpl-widget");
+        assert_eq!(report["dependencies"][0]["status"], "violation");
+        assert_eq!(report["dependencies"].as_array().unwrap().len(), 8);
+
+        // Under a GPL project the same dependencies pass.
+        assert_eq!(check_dependencies(&mut dependencies, "GPL-3.0"), 0);
+    }
+}
@@ -3,6 +3,7 @@ use crate::project_database::load_projects_db;
 use std::fs;
 use std::path::Path;
 use crate::util::{get_install_path};
+use crate::license::print_license_registry;
 
 pub fn list_manager(argument: &str) {
     match argument {
@@ -42,44 +43,13 @@ pub fn list_manager(argument: &str) {
                 }
             };
 
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let template_name = entry.file_name();
-                    println!("{}", template_name.to_string_lossy());
-                }
+            for entry in entries.flatten() {
+                let template_name = entry.file_name();
+                println!("{}", template_name.to_string_lossy());
             }
         },
         "licenses" => {
-            let install_path = match get_install_path() {
-                Ok(path) => path,
-                Err(err) => {
-                    eprintln!("Error getting install path: {}", err);
-                    return;
-                }
-            };
-            let license_dir = Path::new(&install_path).join("licenses");
-
-            if !license_dir.exists() {
-                if let Err(err) = fs::create_dir_all(&license_dir) {
-                    eprintln!("Failed to create licenses directory: {}", err);
-                    return;
-                }
-                println!("Created licenses directory: {}", license_dir.display());
-            }
-
-            let entries = match fs::read_dir(license_dir) {
-                Ok(entries) => entries,
-                Err(err) => {
-                    eprintln!("Failed to read licenses directory: {}", err);
-                    return;
-                }
-            };
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let license_name = entry.file_name();
-                    println!("{}", license_name.to_string_lossy());
-                }
-            }
+            print_license_registry();
         },
         "projects" => {
             let db = load_projects_db();
@@ -101,6 +71,7 @@ pub fn list_manager(argument: &str) {
             println!(" ");
             println!("Editor: {}", config.preferences.editor);
             println!("License: {}", config.preferences.license);
+            println!("Author: {}", config.preferences.author.as_deref().unwrap_or("(git user.name)"));
             println!(" ");
             println!("Use 'upm config defaults <flag_to_toggle>' or 'upm config editor/license <editor/license_name>' to modify preferences.");
             println!(" ");
@@ -1,25 +1,57 @@
+use std::collections::BTreeMap;
+use std::path::Path;
 use clap::{Arg, Command as ClapCommand, ArgAction};
 pub mod project_init;
 pub mod project_database;
 pub mod project_management;
 pub mod secrets;
+pub mod dotenv;
+pub mod env_encryption;
+pub mod team_secrets;
 pub mod util;
 pub mod config;
 pub mod scripts;
 pub mod packages;
 pub mod templates;
 pub mod list;
+pub mod license;
+pub mod license_audit;
+pub mod test_scaffold;
+pub mod testing;
+pub mod build;
+pub mod child;
+pub mod tasks;
+pub mod manifest;
+pub mod watch;
+pub mod dev;
+pub mod dependencies;
+pub mod dependency_edit;
+pub mod package_request;
+pub mod outdated;
+pub mod dependency_inventory;
+pub mod sbom;
+pub mod vulnerability_audit;
 
 use crate::project_init::{create_project, init_project};
 use crate::util::{clean_path};
-use crate::project_database::{load_projects_db};
-use crate::config::{read_config_from, set_license, set_defaults, set_editor, set_warnings};
-use crate::secrets::{secrets_manager};
+use crate::project_database::{load_projects_db, TaskDef};
+use crate::config::{read_config_from, set_author, set_license, set_defaults, set_editor, set_secrets_cache, set_warnings};
+use crate::secrets::{recipients_command, secrets_manager};
 use crate::scripts::{add_script, delete_script, save_script};
-use crate::packages::{add_package};
-use crate::project_management::{open_project, delete_project, run_project, set_main_path};
+use crate::packages::{add_package, install_packages, remove_package, set_package_manager};
+use crate::dependencies::{deps_command};
+use crate::outdated::{outdated_command, update_command};
+use crate::dependency_inventory::inventory_command;
+use crate::sbom::sbom_command;
+use crate::vulnerability_audit::audit_command;
+use crate::project_management::{open_project, delete_project, run_project, set_main_path, set_native_build, set_working_dir};
 use crate::templates::{template_manager};
 use crate::list::{list_manager};
+use crate::license::{license_manager};
+use crate::testing::{run_tests};
+use crate::build::{build_command, generate_build_files};
+use crate::dev::{dev_command};
+use crate::tasks::{add_task, list_tasks, remove_task, run_task};
 
 fn main() {
     let config = read_config_from();
@@ -55,8 +87,9 @@ fn main() {
                 .arg(Arg::new("license")
                     .long("license")
                     .short('l')
-                    .action(ArgAction::SetTrue)
-                    .help("Initializes a license. Uses default license if no argument is provided."))
+                    .num_args(0..=1)
+                    .default_missing_value("")
+                    .help("Initializes a license, e.g. --license \"MIT OR Apache-2.0\". Uses default license if no argument is provided."))
                 .arg(Arg::new("readme")
                     .long("readme")
                     .short('r')
@@ -77,18 +110,238 @@ fn main() {
                     .short('o')
                     .action(ArgAction::SetTrue)
                     .help("Initializes the project with docker"))
+                .arg(Arg::new("build-system")
+                    .long("build-system")
+                    .value_name("SYSTEM")
+                    .value_parser(["cmake", "make", "maven", "gradle"])
+                    .help("Generates a CMakeLists.txt or Makefile for C and C++ projects, or a Maven or Gradle build for Java"))
         )
         .subcommand(
             ClapCommand::new("add")
                 .about("Adds a package to the project")
                 .arg(Arg::new("PACKAGE_NAME")
-                    .help("The name of the package to add")
+                    .help("The name of the package to add, optionally with a version, e.g. serde@^1.0")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::new("dev")
+                    .long("dev")
+                    .short('D')
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("optional")
+                    .help("Adds it as a development dependency"))
+                .arg(Arg::new("optional")
+                    .long("optional")
+                    .action(ArgAction::SetTrue)
+                    .help("Adds it as an optional dependency"))
+                .arg(Arg::new("features")
+                    .long("features")
+                    .value_delimiter(',')
+                    .num_args(1..)
+                    .help("Features (Cargo) or extras (Python) to enable, comma separated")),
+        )
+        .subcommand(
+            ClapCommand::new("remove")
+                .about("Removes a package from the project")
+                .arg(Arg::new("PACKAGE_NAME")
+                    .help("The name of the package to remove")
                     .required(true)
                     .index(1)),
         )
+        .subcommand(
+            ClapCommand::new("install")
+                .about("Installs the project's dependencies with its package manager")
+        )
+        .subcommand(
+            ClapCommand::new("deps")
+                .about("Lists the project's dependencies from its manifests and lockfiles")
+                .arg(Arg::new("tree")
+                    .long("tree")
+                    .action(ArgAction::SetTrue)
+                    .help("Shows transitive dependencies from the lockfile"))
+                .arg(Arg::new("format")
+                    .long("format")
+                    .value_parser(["table", "json"])
+                    .default_value("table")
+                    .help("The output format of the dependency list"))
+                .subcommand(
+                    ClapCommand::new("inventory")
+                        .about("Lists the packages used across all registered projects and the versions each one uses")
+                        .arg(Arg::new("package")
+                            .long("package")
+                            .help("Only reports this package"))
+                        .arg(Arg::new("format")
+                            .long("format")
+                            .value_parser(["table", "json"])
+                            .default_value("table")
+                            .help("The output format of the inventory")),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("outdated")
+                .about("Lists dependencies with newer versions available")
+                .arg(Arg::new("format")
+                    .long("format")
+                    .value_parser(["table", "json"])
+                    .default_value("table")
+                    .help("The output format of the list")),
+        )
+        .subcommand(
+            ClapCommand::new("sbom")
+                .about("Prints a software bill of materials for the project")
+                .arg(Arg::new("format")
+                    .long("format")
+                    .value_parser(["cyclonedx", "spdx"])
+                    .default_value("cyclonedx")
+                    .help("CycloneDX 1.5 or SPDX 2.3 JSON")),
+        )
+        .subcommand(
+            ClapCommand::new("audit")
+                .about("Checks the project's locked dependencies against a local OSV or RustSec advisory database")
+                .arg(Arg::new("db")
+                    .long("db")
+                    .value_name("DIR")
+                    .required(true)
+                    .help("Directory of OSV JSON advisories or a RustSec advisory-db checkout"))
+                .arg(Arg::new("fail-on")
+                    .long("fail-on")
+                    .value_parser(["low", "medium", "high", "critical"])
+                    .default_value("low")
+                    .help("Exit with an error when a finding has at least this severity"))
+                .arg(Arg::new("format")
+                    .long("format")
+                    .value_parser(["table", "json"])
+                    .default_value("table")
+                    .help("Output format")),
+        )
+        .subcommand(
+            ClapCommand::new("update")
+                .about("Updates dependencies to the newest versions their constraints allow")
+                .arg(Arg::new("PACKAGE_NAME")
+                    .help("The package to update; all dependencies when left out")
+                    .required(false)
+                    .index(1))
+                .arg(Arg::new("major")
+                    .long("major")
+                    .action(ArgAction::SetTrue)
+                    .help("Updates to the latest release, changing the version constraint")),
+        )
         .subcommand(
             ClapCommand::new("run")
-                .about("Runs the main entrypoint of the project")
+                .about("Runs the main entrypoint of the project, or a named task")
+                .arg(Arg::new("watch")
+                    .long("watch")
+                    .short('w')
+                    .action(ArgAction::SetTrue)
+                    .help("Restarts the program whenever project files change, rebuilding first if needed"))
+                .arg(Arg::new("TASK")
+                    .help("Runs this task instead of the main entrypoint")
+                    .required(false)
+                    .index(1))
+                .arg(Arg::new("ARGS")
+                    .help("Arguments passed to the program, after --")
+                    .num_args(0..)
+                    .last(true)
+                    .allow_hyphen_values(true)
+                    .index(2))
+        )
+        .subcommand(
+            ClapCommand::new("task")
+                .about("Runs or manages the project's named tasks")
+                .args_conflicts_with_subcommands(true)
+                .arg(Arg::new("watch")
+                    .long("watch")
+                    .short('w')
+                    .action(ArgAction::SetTrue)
+                    .help("Restarts the task whenever project files change"))
+                .arg(Arg::new("NAME")
+                    .help("The task to run, after the tasks it depends on")
+                    .index(1))
+                .arg(Arg::new("ARGS")
+                    .help("Arguments passed to the task's command, after --")
+                    .num_args(0..)
+                    .last(true)
+                    .allow_hyphen_values(true)
+                    .index(2))
+                .subcommand(
+                    ClapCommand::new("add")
+                        .about("Defines or replaces a task")
+                        .arg(Arg::new("NAME")
+                            .help("The name of the task")
+                            .required(true)
+                            .index(1))
+                        .arg(Arg::new("COMMAND")
+                            .help("The shell command the task runs")
+                            .required(true)
+                            .allow_hyphen_values(true)
+                            .index(2))
+                        .arg(Arg::new("env")
+                            .long("env")
+                            .value_name("KEY=VALUE")
+                            .action(ArgAction::Append)
+                            .help("Sets an environment variable for the task"))
+                        .arg(Arg::new("cwd")
+                            .long("cwd")
+                            .value_name("DIR")
+                            .help("Runs the task in DIR, relative to the project root"))
+                        .arg(Arg::new("depends-on")
+                            .long("depends-on")
+                            .value_name("TASK")
+                            .action(ArgAction::Append)
+                            .help("Runs TASK first"))
+                        .arg(Arg::new("inputs")
+                            .long("inputs")
+                            .value_name("GLOB")
+                            .action(ArgAction::Append)
+                            .help("Skips the task while the matching files are unchanged since its last success"))
+                )
+                .subcommand(
+                    ClapCommand::new("remove")
+                        .about("Removes a task")
+                        .arg(Arg::new("NAME")
+                            .help("The name of the task")
+                            .required(true)
+                            .index(1))
+                )
+                .subcommand(
+                    ClapCommand::new("list")
+                        .about("Lists the project's tasks")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("dev")
+                .about("Starts every process from the Procfile or the [processes] table in upm.toml")
+                .arg(Arg::new("restart")
+                    .long("restart")
+                    .action(ArgAction::SetTrue)
+                    .help("Restarts processes that crash instead of stopping the others"))
+        )
+        .subcommand(
+            ClapCommand::new("build")
+                .about("Builds the project and records its artifacts")
+                .arg(Arg::new("release")
+                    .long("release")
+                    .action(ArgAction::SetTrue)
+                    .help("Builds with optimizations instead of debug info"))
+                .arg(Arg::new("out")
+                    .long("out")
+                    .value_name("DIR")
+                    .help("Places the artifacts in DIR instead of build/<profile>"))
+        )
+        .subcommand(
+            ClapCommand::new("test")
+                .about("Runs the project's tests with the runner for its language")
+                .arg(Arg::new("FILTER")
+                    .help("Only runs tests whose name matches the filter")
+                    .required(false)
+                    .index(1))
+                .arg(Arg::new("coverage")
+                    .long("coverage")
+                    .action(ArgAction::SetTrue)
+                    .help("Collects code coverage where the runner supports it"))
+                .arg(Arg::new("junit")
+                    .long("junit")
+                    .value_name("PATH")
+                    .help("Writes a combined JUnit XML report to PATH"))
         )
         .subcommand(
             ClapCommand::new("script")
@@ -128,6 +381,7 @@ fn main() {
                 .arg(Arg::new("ARGUMENT")
                     .help("pass the desired arguments")
                     .required(true)
+                    .allow_hyphen_values(true)
                     .index(2))
         )
         .subcommand(
@@ -165,18 +419,66 @@ fn main() {
         .subcommand(
             ClapCommand::new("secrets")
                 .about("Manages secrets for the project")
+                .args_conflicts_with_subcommands(true)
+                .subcommand_negates_reqs(true)
                 .arg(Arg::new("ACTION")
-                    .help("The action to perform on the secrets")
+                    .help("The action to perform on the secrets: add, remove, show, encrypt, decrypt, unlock, lock or keygen")
                     .required(true)
                     .index(1))
                 .arg(Arg::new("SECRET")
                     .help("The secret to act on")
-                    .required(true)
+                    .required(false)
                     .index(2))
                 .arg(Arg::new("SECRET_VALUE")
                     .help("The secret value")
                     .required(false)
                     .index(3))
+                .arg(Arg::new("team")
+                    .long("team")
+                    .action(ArgAction::SetTrue)
+                    .help("Acts on the team's secrets.enc, encrypted to every recipient, instead of .env"))
+                .subcommand(
+                    ClapCommand::new("recipients")
+                        .about("Manages who can decrypt the team's secrets.enc")
+                        .arg(Arg::new("ACTION")
+                            .help("add, remove or list")
+                            .required(true)
+                            .value_parser(["add", "remove", "list"])
+                            .index(1))
+                        .arg(Arg::new("NAME")
+                            .help("The recipient's name")
+                            .required(false)
+                            .index(2))
+                        .arg(Arg::new("PUBLIC_KEY")
+                            .help("The recipient's public key, from 'upm secrets keygen'")
+                            .required(false)
+                            .index(3)),
+                )
+        )
+        .subcommand(
+            ClapCommand::new("license")
+                .about("Manages the project's license")
+                .arg(Arg::new("ACTION")
+                    .help("The action to perform. Try 'headers', 'audit' or 'add'.")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::new("LICENSE_ID")
+                    .help("The SPDX identifier to register a custom license under")
+                    .required(false)
+                    .index(2))
+                .arg(Arg::new("LICENSE_FILE")
+                    .help("The path to the custom license text")
+                    .required(false)
+                    .index(3))
+                .arg(Arg::new("check")
+                    .long("check")
+                    .action(ArgAction::SetTrue)
+                    .help("Only verifies SPDX headers, exiting non-zero if any are missing"))
+                .arg(Arg::new("format")
+                    .long("format")
+                    .value_parser(["table", "json"])
+                    .default_value("table")
+                    .help("The output format of the license audit"))
         )
         .subcommand(
             ClapCommand::new("open")
@@ -203,7 +505,12 @@ fn main() {
             let git = *sub_m.get_one::<bool>("git").unwrap() || config.default_flags.git;
             let ignore = *sub_m.get_one::<bool>("ignore").unwrap() || (config.default_flags.ignore && git);
             let venv = *sub_m.get_one::<bool>("venv").unwrap() || config.default_flags.venv;
-            let license = *sub_m.get_one::<bool>("license").unwrap() || config.default_flags.license;
+            let license = match sub_m.get_one::<String>("license") {
+                Some(expression) if !expression.is_empty() => Some(expression.clone()),
+                Some(_) => Some(config.preferences.license.clone()),
+                None if config.default_flags.license => Some(config.preferences.license.clone()),
+                None => None,
+            };
             let readme = *sub_m.get_one::<bool>("readme").unwrap() || config.default_flags.readme;
             let tests = *sub_m.get_one::<bool>("tests").unwrap() || config.default_flags.tests;
             let docs = *sub_m.get_one::<bool>("docs").unwrap() || config.default_flags.docs;
@@ -212,7 +519,10 @@ fn main() {
                 println!("Project creation requires the necessary dependencies to be installed. Errors may occur if you do not have the language installed.");
                 println!("To disable this warning run 'upman config warnings creation' to toggle the warning, or manually set it to false in the upmconfig.toml file.");
             }
-            create_project(project_name, project_language, git, ignore, venv, license, readme, tests, docs, docker);
+            create_project(project_name, project_language, git, ignore, venv, license.as_deref(), readme, tests, docs, docker);
+            if let Some(system) = sub_m.get_one::<String>("build-system") {
+                generate_build_files(Path::new(project_name), project_language, system);
+            }
         },
         Some(("add", sub_m)) => {
             let package_name = sub_m.get_one::<String>("PACKAGE_NAME").unwrap();
@@ -220,14 +530,106 @@ fn main() {
                 println!("Adding a package requires the necessary package manager for that language to be installed. Some languages may not have a package manager.");
                 println!("To disable this warning run 'upman config warnings add' to toggle the warning, or manually set it to false in the upmconfig.toml file.");
             }
-            add_package(package_name);
+            let features = sub_m.get_many::<String>("features").map(|features| features.cloned().collect()).unwrap_or_default();
+            add_package(package_name, sub_m.get_flag("dev"), sub_m.get_flag("optional"), features);
+        },
+        Some(("remove", sub_m)) => {
+            let package_name = sub_m.get_one::<String>("PACKAGE_NAME").unwrap();
+            remove_package(package_name);
+        },
+        Some(("install", _)) => {
+            install_packages();
+        },
+        Some(("deps", sub_m)) => {
+            if let Some(("inventory", inventory_m)) = sub_m.subcommand() {
+                let package = inventory_m.get_one::<String>("package");
+                let format = inventory_m.get_one::<String>("format").unwrap();
+                inventory_command(package.map(String::as_str), format);
+            } else {
+                let tree = *sub_m.get_one::<bool>("tree").unwrap();
+                let format = sub_m.get_one::<String>("format").unwrap();
+                deps_command(tree, format);
+            }
+        },
+        Some(("outdated", sub_m)) => {
+            let format = sub_m.get_one::<String>("format").unwrap();
+            outdated_command(format);
+        },
+        Some(("sbom", sub_m)) => {
+            let format = sub_m.get_one::<String>("format").unwrap();
+            sbom_command(format);
+        },
+        Some(("audit", sub_m)) => {
+            let db = sub_m.get_one::<String>("db").unwrap();
+            let fail_on = sub_m.get_one::<String>("fail-on").unwrap();
+            let format = sub_m.get_one::<String>("format").unwrap();
+            audit_command(db, fail_on, format);
+        },
+        Some(("update", sub_m)) => {
+            let package_name = sub_m.get_one::<String>("PACKAGE_NAME");
+            update_command(package_name.map(String::as_str), sub_m.get_flag("major"));
         },
-        Some(("run", _)) => {
+        Some(("run", sub_m)) => {
             if config.warnings.run {
                 println!("Running a project requires the necessary dependencies to be installed. Errors may occur if you do not have the language or other necessary compilers/interpreters installed.");
                 println!("To disable this warning run 'upman config warnings run' to toggle the warning, or manually set it to false in the upmconfig.toml file.");
             }
-            run_project();
+            let args: Vec<String> = sub_m.get_many::<String>("ARGS").unwrap_or_default().cloned().collect();
+            let watch = *sub_m.get_one::<bool>("watch").unwrap();
+            match sub_m.get_one::<String>("TASK") {
+                Some(task) => run_task(task, &args, watch),
+                None => run_project(&args, watch),
+            }
+        },
+        Some(("task", sub_m)) => {
+            match sub_m.subcommand() {
+                Some(("add", add_m)) => {
+                    let name = add_m.get_one::<String>("NAME").unwrap();
+                    let mut env = BTreeMap::new();
+                    for pair in add_m.get_many::<String>("env").unwrap_or_default() {
+                        match pair.split_once('=') {
+                            Some((key, value)) => {
+                                env.insert(key.to_string(), value.to_string());
+                            },
+                            None => {
+                                eprintln!("Invalid --env '{}'; expected KEY=VALUE.", pair);
+                                return;
+                            },
+                        }
+                    }
+                    let task = TaskDef {
+                        command: add_m.get_one::<String>("COMMAND").unwrap().clone(),
+                        env,
+                        cwd: add_m.get_one::<String>("cwd").cloned(),
+                        depends_on: add_m.get_many::<String>("depends-on").unwrap_or_default().cloned().collect(),
+                        inputs: add_m.get_many::<String>("inputs").unwrap_or_default().cloned().collect(),
+                    };
+                    add_task(name, task);
+                },
+                Some(("remove", remove_m)) => remove_task(remove_m.get_one::<String>("NAME").unwrap()),
+                Some(("list", _)) => list_tasks(),
+                _ => match sub_m.get_one::<String>("NAME") {
+                    Some(name) => {
+                        let args: Vec<String> = sub_m.get_many::<String>("ARGS").unwrap_or_default().cloned().collect();
+                        run_task(name, &args, *sub_m.get_one::<bool>("watch").unwrap());
+                    },
+                    None => list_tasks(),
+                },
+            }
+        },
+        Some(("dev", sub_m)) => {
+            dev_command(*sub_m.get_one::<bool>("restart").unwrap());
+        },
+        Some(("build", sub_m)) => {
+            let release = *sub_m.get_one::<bool>("release").unwrap();
+            let out = sub_m.get_one::<String>("out");
+            build_command(release, out.map(String::as_str));
+        },
+        Some(("test", sub_m)) => {
+            let filter = sub_m.get_one::<String>("FILTER");
+            let coverage = *sub_m.get_one::<bool>("coverage").unwrap();
+            let junit = sub_m.get_one::<String>("junit");
+            run_tests(filter.map(String::as_str), coverage, junit.map(String::as_str));
         },
         Some(("script", sub_m)) => {
             let action = sub_m.get_one::<String>("ACTION").unwrap();
@@ -260,6 +662,15 @@ fn main() {
             if modifier == "main" {
                 set_main_path(argument);
             }
+            if modifier == "workdir" {
+                set_working_dir(argument);
+            }
+            if modifier == "package-manager" {
+                set_package_manager(argument);
+            }
+            if modifier == "includes" || modifier == "cflags" || modifier == "libs" {
+                set_native_build(modifier, argument);
+            }
             if modifier == "defaults" {
                 set_defaults(argument);
             }
@@ -269,9 +680,15 @@ fn main() {
             if modifier == "license" {
                 set_license(argument);
             }
+            if modifier == "author" {
+                set_author(argument);
+            }
             if modifier == "warnings" {
                 set_warnings(argument);
             }
+            if modifier == "secrets-cache" {
+                set_secrets_cache(argument);
+            }
         },
         Some(("template", sub_m)) => {
             let action = sub_m.get_one::<String>("ACTION").unwrap();
@@ -289,11 +706,26 @@ fn main() {
             list_manager(argument);
         },
         Some(("secrets", sub_m)) => {
-            let action = sub_m.get_one::<String>("ACTION").unwrap();
-            let secret = sub_m.get_one::<String>("SECRET").unwrap();
-            let secret_value = sub_m.get_one::<String>("SECRET_VALUE").map(String::to_string).unwrap_or_default();
+            if let Some(("recipients", recipients_m)) = sub_m.subcommand() {
+                let action = recipients_m.get_one::<String>("ACTION").unwrap();
+                let name = recipients_m.get_one::<String>("NAME");
+                let public_key = recipients_m.get_one::<String>("PUBLIC_KEY");
+                recipients_command(action, name.map(String::as_str), public_key.map(String::as_str));
+            } else {
+                let action = sub_m.get_one::<String>("ACTION").unwrap();
+                let secret = sub_m.get_one::<String>("SECRET");
+                let secret_value = sub_m.get_one::<String>("SECRET_VALUE").map(String::to_string).unwrap_or_default();
 
-            secrets_manager(action, secret, &secret_value);
+                secrets_manager(action, secret.map(String::as_str), &secret_value, sub_m.get_flag("team"));
+            }
+        },
+        Some(("license", sub_m)) => {
+            let action = sub_m.get_one::<String>("ACTION").unwrap();
+            let license_id = sub_m.get_one::<String>("LICENSE_ID");
+            let license_file = sub_m.get_one::<String>("LICENSE_FILE");
+            let check = *sub_m.get_one::<bool>("check").unwrap();
+            let format = sub_m.get_one::<String>("format").unwrap();
+            license_manager(action, license_id.map(String::as_str), license_file.map(String::as_str), check, format);
         },
         Some(("open", sub_m)) => {
             let project = sub_m.get_one::<String>("PROJECT").unwrap();
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::project_database::{NativeBuild, TaskDef};
+
+pub const MANIFEST_FILE: &str = "upm.toml";
+
+/// The committed `upm.toml` in a project root, which teammates share instead of re-running `init`.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ProjectManifest {
+    pub project: ManifestProject,
+    #[serde(default, skip_serializing_if = "NativeBuild::is_empty")]
+    pub build: NativeBuild,
+    /// Non-secret variables set for `run` and tasks; the project's `.env` overrides them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, TaskDef>,
+    /// Long-running commands started together by `upman dev` when the project has no Procfile.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub processes: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ManifestProject {
+    pub name: String,
+    pub language: String,
+    pub main: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The upman template the project was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<String>,
+}
+
+pub fn has_manifest(project_path: &Path) -> bool {
+    project_path.join(MANIFEST_FILE).is_file()
+}
+
+pub fn load_manifest(project_path: &Path) -> Option<ProjectManifest> {
+    let manifest_path = project_path.join(MANIFEST_FILE);
+    let contents = fs::read_to_string(&manifest_path).ok()?;
+    match toml::from_str(&contents) {
+        Ok(manifest) => Some(manifest),
+        Err(err) => {
+            eprintln!("Failed to parse {}: {}", manifest_path.display(), err);
+            None
+        },
+    }
+}
+
+/// Writes the manifest, keeping the comments and layout of entries that did not change.
+pub fn save_manifest(project_path: &Path, manifest: &ProjectManifest) {
+    let manifest_path = project_path.join(MANIFEST_FILE);
+    let updated = toml::to_string(manifest)
+        .expect("Failed to serialize upm.toml")
+        .parse::<DocumentMut>()
+        .expect("Failed to serialize upm.toml");
+
+    let document = match fs::read_to_string(&manifest_path).ok().and_then(|contents| contents.parse::<DocumentMut>().ok()) {
+        Some(mut existing) => {
+            merge_table(existing.as_table_mut(), updated.as_table(), &[]);
+            existing
+        },
+        None => updated,
+    };
+
+    if let Err(err) = fs::write(&manifest_path, document.to_string()) {
+        eprintln!("Failed to write {}: {}", manifest_path.display(), err);
+    }
+}
+
+fn merge_table(existing: &mut Table, updated: &Table, path: &[&str]) {
+    let removed: Vec<String> = existing
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !updated.contains_key(key) && owns_key(path, key))
+        .collect();
+    for key in removed {
+        existing.remove(&key);
+    }
+
+    for (key, item) in updated.iter() {
+        match (existing.get_mut(key), item) {
+            (Some(Item::Table(current)), Item::Table(new)) => merge_table(current, new, &[path, &[key]].concat()),
+            (Some(Item::Value(current)), Item::Value(new)) if same_value(current, new) => {},
+            _ => {
+                existing.insert(key, item.clone());
+            },
+        }
+    }
+}
+
+// Whether upman writes `key` in the table at `path`. Keys it doesn't know were added by hand and are never removed.
+fn owns_key(path: &[&str], key: &str) -> bool {
+    match path {
+        [] => matches!(key, "project" | "build" | "env" | "tasks" | "processes"),
+        ["project"] => matches!(key, "name" | "language" | "main" | "license" | "template" | "working_dir" | "package_manager"),
+        ["build"] => matches!(key, "include_dirs" | "flags" | "libs"),
+        ["env"] | ["tasks"] | ["processes"] | ["tasks", _, "env"] => true,
+        ["tasks", _] => matches!(key, "command" | "env" | "cwd" | "depends_on" | "inputs"),
+        _ => false,
+    }
+}
+
+// Compares values by meaning, so `["a", "b"]` and a reformatted multi-line array are equal.
+fn same_value(current: &toml_edit::Value, new: &toml_edit::Value) -> bool {
+    let parse = |value: &toml_edit::Value| format!("value = {}", value).parse::<toml::Table>().ok();
+    parse(current) == parse(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(existing: &str, updated: &str) -> String {
+        let mut document = existing.parse::<DocumentMut>().unwrap();
+        merge_table(document.as_table_mut(), updated.parse::<DocumentMut>().unwrap().as_table(), &[]);
+        document.to_string()
+    }
+
+    #[test]
+    fn merge_keeps_unknown_keys_and_comments() {
+        let existing = "\
+# Shared project settings
+[project]
+name = \"demo\"
+language = \"python\"
+main = \"main.py\"
+license = \"MIT\"
+owner = \"platform-team\"
+
+[env]
+LOG_LEVEL = \"debug\" # noisy locally
+STALE = \"1\"
+
+[tasks.test]
+command = \"pytest\"
+timeout = 600
+
+[tasks.old]
+command = \"make old\"
+
+[tool.ci]
+image = \"python:3.12\"
+";
+        let updated = "\
+[project]
+name = \"demo\"
+language = \"python\"
+main = \"app.py\"
+
+[env]
+LOG_LEVEL = \"debug\"
+
+[tasks.test]
+command = \"pytest -q\"
+";
+        let merged = merge(existing, updated);
+        let manifest: toml::Table = merged.parse().unwrap();
+        assert_eq!(manifest["project"]["main"].as_str(), Some("app.py"));
+        // Owned keys that are gone from the manifest are removed.
+        assert!(manifest["project"].get("license").is_none());
+        assert!(manifest["env"].get("STALE").is_none());
+        assert!(manifest["tasks"].get("old").is_none());
+        assert_eq!(manifest["tasks"]["test"]["command"].as_str(), Some("pytest -q"));
+        // Keys upman doesn't write are left alone.
+        assert_eq!(manifest["project"]["owner"].as_str(), Some("platform-team"));
+        assert_eq!(manifest["tasks"]["test"]["timeout"].as_integer(), Some(600));
+        assert_eq!(manifest["tool"]["ci"]["image"].as_str(), Some("python:3.12"));
+        assert!(merged.starts_with("# Shared project settings\n"));
+        assert!(merged.contains("LOG_LEVEL = \"debug\" # noisy locally"));
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::path::Path;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use crate::dependencies::{normalize_python_name, package_key, project_dependencies, DependencyKind};
+use crate::load_projects_db;
+use crate::package_request::{Constraint, PackageRequest};
+use crate::packages::{current_project_info, dart_tool, detect_package_manager, execute_package_command, run_package_command, tool_command, unsupported, PackageManager};
+use crate::util::{node_tool, venv_bin};
+
+/// A dependency with a newer release. `wanted` is the newest version the manifest's constraint allows,
+/// when the tool reports one; `latest` is the newest release regardless of the constraint.
+#[derive(Serialize)]
+pub struct OutdatedPackage {
+    pub name: String,
+    pub ecosystem: String,
+    pub current: Option<String>,
+    pub wanted: Option<String>,
+    pub latest: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<DependencyKind>,
+}
+
+pub fn outdated_command(format: &str) {
+    let db = load_projects_db();
+    let Some(info) = current_project_info(&db) else {
+        return;
+    };
+    let Some(package_manager) = detect_package_manager(info) else {
+        unsupported(info);
+        return;
+    };
+
+    let packages = outdated_packages(&package_manager, Path::new(&info.project_path), false).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    });
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&packages).expect("Failed to serialize outdated packages"));
+    } else if packages.is_empty() {
+        println!("All dependencies are up to date.");
+    } else {
+        print_table(&packages);
+    }
+}
+
+/// Updates the package (or every dependency) to the newest version its constraint allows, or with `major`
+/// to the latest release, rewriting the constraint in the manifest.
+pub fn update_command(package: Option<&str>, major: bool) {
+    let db = load_projects_db();
+    let Some(info) = current_project_info(&db) else {
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
+    let Some(package_manager) = detect_package_manager(info) else {
+        unsupported(info);
+        return;
+    };
+
+    let with_package = |program: &str, args: &[&str]| {
+        let mut command = tool_command(program, args);
+        command.args(package);
+        command
+    };
+    let mut command = match (&package_manager, major) {
+        // These tools can move past the manifest's constraints themselves.
+        (PackageManager::Pnpm, _) | (PackageManager::Yarn, _) | (PackageManager::Bun, _) => {
+            let (program, update) = match package_manager {
+                PackageManager::Pnpm => (node_tool("pnpm"), "update"),
+                PackageManager::Yarn => (node_tool("yarn"), "upgrade"),
+                _ => ("bun".to_string(), "update"),
+            };
+            let mut command = with_package(&program, &[update]);
+            if major {
+                command.arg("--latest");
+            }
+            command
+        },
+        (PackageManager::Dart, _) => {
+            let mut command = tool_command(dart_tool(project_path), &["pub", "upgrade"]);
+            if major {
+                command.arg("--major-versions");
+            }
+            command.args(package);
+            command
+        },
+        (PackageManager::Cargo, false) => {
+            let mut command = tool_command("cargo", &["update"]);
+            if let Some(package) = package {
+                command.args(["-p", package]);
+            }
+            command
+        },
+        (PackageManager::Npm, false) => with_package(&node_tool("npm"), &["update"]),
+        (PackageManager::Poetry, false) => with_package("poetry", &["update"]),
+        (PackageManager::Uv, false) => match package {
+            Some(package) => tool_command("uv", &["sync", "--upgrade-package", package]),
+            None => tool_command("uv", &["sync", "--upgrade"]),
+        },
+        (PackageManager::Pipenv, false) => with_package("pipenv", &["update"]),
+        (PackageManager::Bundler, false) => with_package("bundle", &["update"]),
+        (PackageManager::Pip, false) => {
+            let requirements = declared_requirements(project_path, package);
+            let mut command = tool_command(&venv_bin(project_path, "pip").to_string_lossy(), &["install", "--upgrade"]);
+            command.args(&requirements);
+            command
+        },
+        (PackageManager::Go, false) => tool_command("go", &["get", "-u", package.unwrap_or("./...")]),
+        (PackageManager::Go, true) => {
+            eprintln!("A new Go major version is a new module path; add it with 'upman add <module>/vN'.");
+            std::process::exit(1);
+        },
+        // Everything else is moved to the latest release by adding it again with a new constraint.
+        (PackageManager::Cargo, true) | (PackageManager::Npm, true) | (PackageManager::Poetry, true) | (PackageManager::Uv, true)
+        | (PackageManager::Pipenv, true) | (PackageManager::Pip, true) | (PackageManager::Dotnet, _) => {
+            update_to_latest(&package_manager, project_path, package, major);
+            return;
+        },
+        _ => {
+            eprintln!("upman update does not support {} projects.", package_manager.name());
+            std::process::exit(1);
+        },
+    };
+
+    if run_package_command(command.current_dir(project_path)) {
+        println!("Dependencies updated.");
+    } else {
+        eprintln!("Failed to update dependencies.");
+        std::process::exit(1);
+    }
+}
+
+fn update_to_latest(package_manager: &PackageManager, project_path: &Path, package: Option<&str>, major: bool) {
+    // `dotnet list package --highest-minor` already limits "latest" to the current major version.
+    let outdated = outdated_packages(package_manager, project_path, !major).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    });
+    let targets: Vec<&OutdatedPackage> = outdated
+        .iter()
+        .filter(|outdated| package.is_none_or(|package| package_key(&outdated.ecosystem, package) == package_key(&outdated.ecosystem, &outdated.name)))
+        .filter(|outdated| outdated.current.as_deref() != Some(outdated.latest.as_str()))
+        .collect();
+    if targets.is_empty() {
+        println!("{} already up to date.", package.map(|package| format!("'{}' is", package)).unwrap_or("Everything is".to_string()));
+        return;
+    }
+
+    for outdated in targets {
+        // Cargo and NuGet read a bare version as a minimum already; elsewhere the caret keeps later minor releases allowed.
+        let version = if matches!(package_manager, PackageManager::Cargo | PackageManager::Dotnet) {
+            Constraint::Exact(outdated.latest.clone())
+        } else {
+            Constraint::Caret(outdated.latest.clone())
+        };
+        let request = PackageRequest {
+            name: outdated.name.clone(),
+            version: Some(version),
+            dev: outdated.kind == Some(DependencyKind::Dev),
+            optional: false,
+            features: Vec::new(),
+        };
+        execute_package_command(package_manager.clone(), project_path, &format!("{}@{}", outdated.name, outdated.latest), &request);
+    }
+}
+
+// The project's Python requirements as declared, so `pip install --upgrade` stays within their constraints.
+fn declared_requirements(project_path: &Path, package: Option<&str>) -> Vec<String> {
+    let requirements: Vec<String> = project_dependencies(project_path, false)
+        .into_iter()
+        .filter(|dependency| dependency.ecosystem == "pypi")
+        .filter(|dependency| package.is_none_or(|package| normalize_python_name(package) == dependency.name))
+        .map(|dependency| format!("{}{}", dependency.name, dependency.requested.unwrap_or_default()))
+        .collect();
+    match package {
+        Some(package) if requirements.is_empty() => vec![package.to_string()],
+        _ => requirements,
+    }
+}
+
+/// Runs the ecosystem's own outdated check and keeps the packages the project declares directly,
+/// tagged with their dependency kind. Tools that list every installed package (pip, go) are narrowed this way too.
+pub fn outdated_packages(package_manager: &PackageManager, project_path: &Path, highest_minor: bool) -> Result<Vec<OutdatedPackage>, String> {
+    let (mut command, ecosystem) = match package_manager {
+        PackageManager::Cargo => (tool_command("cargo", &["update", "--dry-run", "--verbose"]), "cargo"),
+        PackageManager::Npm => (tool_command(&node_tool("npm"), &["outdated", "--json"]), "npm"),
+        PackageManager::Pnpm => (tool_command(&node_tool("pnpm"), &["outdated", "--format", "json"]), "npm"),
+        PackageManager::Pip | PackageManager::Poetry | PackageManager::Uv | PackageManager::Pipenv => {
+            (tool_command(&venv_bin(project_path, "pip").to_string_lossy(), &["list", "--outdated", "--format", "json"]), "pypi")
+        },
+        PackageManager::Go => (tool_command("go", &["list", "-m", "-u", "-json", "all"]), "go"),
+        PackageManager::Dotnet => {
+            let mut command = tool_command("dotnet", &["list", "package", "--outdated", "--format", "json"]);
+            if highest_minor {
+                command.arg("--highest-minor");
+            }
+            (command, "nuget")
+        },
+        _ => return Err(format!("upman outdated does not support {} projects.", package_manager.name())),
+    };
+
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command.current_dir(project_path).output().map_err(|err| format!("Failed to run {}: {}", program, err))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // npm and pnpm exit with 1 whenever something is outdated.
+    let reported = matches!(package_manager, PackageManager::Npm | PackageManager::Pnpm) && !stdout.trim().is_empty();
+    if !output.status.success() && !reported {
+        return Err(format!("{} failed:\n{}", program, stderr.trim_end()));
+    }
+    let packages = match package_manager {
+        PackageManager::Cargo => Some(parse_cargo_update(&stderr)),
+        PackageManager::Npm | PackageManager::Pnpm => parse_npm_outdated(&stdout),
+        PackageManager::Go => parse_go_list(&stdout),
+        PackageManager::Dotnet => parse_dotnet_outdated(&stdout),
+        _ => parse_pip_outdated(&stdout),
+    };
+    let mut packages = packages.ok_or(format!("Could not read the output of {}.", program))?;
+
+    let declared: HashMap<String, DependencyKind> = project_dependencies(project_path, false)
+        .into_iter()
+        .filter(|dependency| dependency.ecosystem == ecosystem)
+        .map(|dependency| (package_key(ecosystem, &dependency.name), dependency.kind))
+        .collect();
+    if !declared.is_empty() {
+        packages.retain(|package| declared.contains_key(&package_key(ecosystem, &package.name)));
+        for package in packages.iter_mut() {
+            package.kind = declared.get(&package_key(ecosystem, &package.name)).copied();
+        }
+    }
+    packages.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.cmp(&b.name)));
+    Ok(packages)
+}
+
+/// `cargo update --dry-run --verbose` (stderr): `Updating name vA -> vB` for compatible updates and
+/// `Unchanged name vA (available: vC)` for ones the constraint excludes. Newer cargo says `latest:`.
+pub fn parse_cargo_update(output: &str) -> Vec<OutdatedPackage> {
+    let updating = Regex::new(r"^\s*Updating (\S+) v(\S+) -> v(\S+)(?: \((?:available|latest): v([^)]+)\))?").unwrap();
+    let unchanged = Regex::new(r"^\s*Unchanged (\S+) v(\S+) \((?:available|latest): v([^)]+)\)").unwrap();
+    let mut packages = Vec::new();
+    for line in output.lines() {
+        let (name, current, wanted, latest) = if let Some(captures) = updating.captures(line) {
+            let latest = captures.get(4).unwrap_or(captures.get(3).unwrap()).as_str();
+            (captures[1].to_string(), captures[2].to_string(), captures[3].to_string(), latest.to_string())
+        } else if let Some(captures) = unchanged.captures(line) {
+            (captures[1].to_string(), captures[2].to_string(), captures[2].to_string(), captures[3].to_string())
+        } else {
+            continue;
+        };
+        packages.push(OutdatedPackage { name, ecosystem: "cargo".to_string(), current: Some(current), wanted: Some(wanted), latest, kind: None });
+    }
+    packages
+}
+
+/// `npm outdated --json` and `pnpm outdated --format json`: an object keyed by package name. npm gives
+/// an array per package when it is installed in several places; the first one is used.
+pub fn parse_npm_outdated(output: &str) -> Option<Vec<OutdatedPackage>> {
+    if output.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    let report: serde_json::Map<String, Value> = serde_json::from_str(output).ok()?;
+    let text = |entry: &Value, key: &str| entry.get(key).and_then(Value::as_str).map(String::from);
+    let packages = report
+        .iter()
+        .filter_map(|(name, entry)| {
+            let entry = entry.as_array().and_then(|entries| entries.first()).unwrap_or(entry);
+            let kind = match text(entry, "type").or_else(|| text(entry, "dependencyType")).as_deref() {
+                Some("devDependencies") => DependencyKind::Dev,
+                _ => DependencyKind::Runtime,
+            };
+            Some(OutdatedPackage {
+                name: name.clone(),
+                ecosystem: "npm".to_string(),
+                current: text(entry, "current"),
+                wanted: text(entry, "wanted"),
+                latest: text(entry, "latest")?,
+                kind: Some(kind),
+            })
+        })
+        .collect();
+    Some(packages)
+}
+
+/// `pip list --outdated --format json`: every installed distribution with a newer release.
+pub fn parse_pip_outdated(output: &str) -> Option<Vec<OutdatedPackage>> {
+    let report: Vec<Value> = serde_json::from_str(output).ok()?;
+    let packages = report
+        .iter()
+        .filter_map(|entry| {
+            Some(OutdatedPackage {
+                name: entry.get("name")?.as_str()?.to_string(),
+                ecosystem: "pypi".to_string(),
+                current: entry.get("version").and_then(Value::as_str).map(String::from),
+                wanted: None,
+                latest: entry.get("latest_version")?.as_str()?.to_string(),
+                kind: None,
+            })
+        })
+        .collect();
+    Some(packages)
+}
+
+/// `go list -m -u -json all`: a stream of module objects; those with an `Update` have a newer version
+/// within their major version. The main module is skipped.
+pub fn parse_go_list(output: &str) -> Option<Vec<OutdatedPackage>> {
+    let mut packages = Vec::new();
+    for module in serde_json::Deserializer::from_str(output).into_iter::<Value>() {
+        let module = module.ok()?;
+        if module.get("Main").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        let (Some(path), Some(update)) = (module.get("Path").and_then(Value::as_str), module.pointer("/Update/Version").and_then(Value::as_str)) else {
+            continue;
+        };
+        packages.push(OutdatedPackage {
+            name: path.to_string(),
+            ecosystem: "go".to_string(),
+            current: module.get("Version").and_then(Value::as_str).map(String::from),
+            wanted: Some(update.to_string()),
+            latest: update.to_string(),
+            kind: Some(DependencyKind::Runtime),
+        });
+    }
+    Some(packages)
+}
+
+/// `dotnet list package --outdated --format json`: top-level packages per project and target framework,
+/// reported once per package.
+pub fn parse_dotnet_outdated(output: &str) -> Option<Vec<OutdatedPackage>> {
+    let report: Value = serde_json::from_str(output).ok()?;
+    let mut packages: Vec<OutdatedPackage> = Vec::new();
+    let frameworks = report.get("projects")?.as_array()?.iter().filter_map(|project| project.get("frameworks")?.as_array()).flatten();
+    for package in frameworks.filter_map(|framework| framework.get("topLevelPackages")?.as_array()).flatten() {
+        let (Some(id), Some(latest)) = (package.get("id").and_then(Value::as_str), package.get("latestVersion").and_then(Value::as_str)) else {
+            continue;
+        };
+        if packages.iter().any(|existing| existing.name.eq_ignore_ascii_case(id)) {
+            continue;
+        }
+        packages.push(OutdatedPackage {
+            name: id.to_string(),
+            ecosystem: "nuget".to_string(),
+            current: package.get("resolvedVersion").and_then(Value::as_str).map(String::from),
+            wanted: None,
+            latest: latest.to_string(),
+            kind: None,
+        });
+    }
+    Some(packages)
+}
+
+fn print_table(packages: &[OutdatedPackage]) {
+    let width = |header: &str, value: &dyn Fn(&OutdatedPackage) -> String| packages.iter().map(|package| value(package).len()).max().unwrap_or(0).max(header.len());
+    let current = |package: &OutdatedPackage| package.current.clone().unwrap_or("-".to_string());
+    let wanted = |package: &OutdatedPackage| package.wanted.clone().unwrap_or("-".to_string());
+    let name_width = width("PACKAGE", &|package| package.name.clone());
+    let current_width = width("CURRENT", &current);
+    let wanted_width = width("WANTED", &wanted);
+    let latest_width = width("LATEST", &|package| package.latest.clone());
+    println!("{:<name_width$}  {:<current_width$}  {:<wanted_width$}  {:<latest_width$}  {:<7}  ECOSYSTEM", "PACKAGE", "CURRENT", "WANTED", "LATEST", "KIND");
+    for package in packages {
+        let kind = match package.kind {
+            Some(DependencyKind::Runtime) => "runtime",
+            Some(DependencyKind::Dev) => "dev",
+            None => "-",
+        };
+        println!(
+            "{:<name_width$}  {:<current_width$}  {:<wanted_width$}  {:<latest_width$}  {:<7}  {}",
+            package.name,
+            current(package),
+            wanted(package),
+            package.latest,
+            kind,
+            package.ecosystem
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(package: &OutdatedPackage) -> (&str, Option<&str>, Option<&str>, &str) {
+        (package.name.as_str(), package.current.as_deref(), package.wanted.as_deref(), package.latest.as_str())
+    }
+
+    #[test]
+    fn parses_cargo_update_dry_run() {
+        let packages = parse_cargo_update(include_str!("../tests/fixtures/outdated/cargo-update.txt"));
+        let find = |name: &str| packages.iter().find(|package| package.name == name).map(versions);
+
+        assert_eq!(find("clap"), Some(("clap", Some("4.5.4"), Some("4.6.7"), "4.6.7")));
+        assert_eq!(find("notify"), Some(("notify", Some("6.1.1"), Some("6.1.1"), "8.2.0")));
+        assert_eq!(find("toml"), Some(("toml", Some("0.8.12"), Some("0.8.23"), "1.1.8+spec-1.1.0")));
+        // Added and removed lockfile entries are not updates.
+        assert_eq!(find("zmij"), None);
+        assert_eq!(find("ryu"), None);
+    }
+
+    #[test]
+    fn parses_newer_cargo_latest_wording() {
+        let packages = parse_cargo_update("    Updating serde v1.0.100 -> v1.0.200 (latest: v2.0.0)\n   Unchanged rand v0.7.3 (latest: v0.8.5)\n");
+        assert_eq!(packages.iter().map(versions).collect::<Vec<_>>(), [
+            ("serde", Some("1.0.100"), Some("1.0.200"), "2.0.0"),
+            ("rand", Some("0.7.3"), Some("0.7.3"), "0.8.5"),
+        ]);
+    }
+
+    #[test]
+    fn parses_npm_outdated() {
+        let packages = parse_npm_outdated(include_str!("../tests/fixtures/outdated/npm-outdated.json")).unwrap();
+        assert_eq!(packages.len(), 3);
+        let typescript = packages.iter().find(|package| package.name == "typescript").unwrap();
+        assert_eq!(versions(typescript), ("typescript", Some("5.3.3"), Some("5.4.5"), "5.4.5"));
+        assert_eq!(typescript.kind, Some(DependencyKind::Dev));
+        // Not installed yet, so there is no current version.
+        let left_pad = packages.iter().find(|package| package.name == "left-pad").unwrap();
+        assert_eq!(left_pad.current, None);
+        assert_eq!(parse_npm_outdated("").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn parses_pnpm_outdated() {
+        let packages = parse_npm_outdated(include_str!("../tests/fixtures/outdated/pnpm-outdated.json")).unwrap();
+        let express = packages.iter().find(|package| package.name == "express").unwrap();
+        assert_eq!(versions(express), ("express", Some("4.18.2"), Some("4.19.2"), "5.0.0"));
+        let vitest = packages.iter().find(|package| package.name == "vitest").unwrap();
+        assert_eq!(vitest.kind, Some(DependencyKind::Dev));
+    }
+
+    #[test]
+    fn parses_pip_list_outdated() {
+        let packages = parse_pip_outdated(include_str!("../tests/fixtures/outdated/pip-outdated.json")).unwrap();
+        assert_eq!(packages.iter().map(versions).collect::<Vec<_>>(), [
+            ("requests", Some("2.31.0"), None, "2.32.3"),
+            ("Flask_Login", Some("0.6.2"), None, "0.6.3"),
+            ("pip", Some("23.0.1"), None, "24.0"),
+        ]);
+        assert!(parse_pip_outdated("ERROR: not json").is_none());
+    }
+
+    #[test]
+    fn parses_go_list_updates() {
+        let packages = parse_go_list(include_str!("../tests/fixtures/outdated/go-list.json")).unwrap();
+        assert_eq!(packages.iter().map(versions).collect::<Vec<_>>(), [
+            ("github.com/spf13/cobra", Some("v1.7.0"), Some("v1.8.0"), "v1.8.0"),
+            ("golang.org/x/sys", Some("v0.15.0"), Some("v0.20.0"), "v0.20.0"),
+        ]);
+    }
+
+    #[test]
+    fn parses_dotnet_outdated_once_per_package() {
+        let packages = parse_dotnet_outdated(include_str!("../tests/fixtures/outdated/dotnet-outdated.json")).unwrap();
+        assert_eq!(packages.iter().map(versions).collect::<Vec<_>>(), [
+            ("Newtonsoft.Json", Some("13.0.1"), None, "13.0.3"),
+            ("Serilog", Some("2.12.0"), None, "4.0.0"),
+        ]);
+    }
+
+    #[test]
+    fn matches_python_and_nuget_names_loosely() {
+        assert_eq!(package_key("pypi", "Flask_Login"), package_key("pypi", "flask-login"));
+        assert_eq!(package_key("nuget", "Newtonsoft.Json"), "newtonsoft.json");
+        assert_ne!(package_key("cargo", "Serde"), package_key("cargo", "serde"));
+    }
+}
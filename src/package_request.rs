@@ -0,0 +1,237 @@
+/// What `upman add` was asked for: `name@version` plus the dependency kind and features.
+pub struct PackageRequest {
+    pub name: String,
+    pub version: Option<Constraint>,
+    pub dev: bool,
+    pub optional: bool,
+    pub features: Vec<String>,
+}
+
+/// A version constraint as typed after `@`. Caret and tilde ranges use npm/Cargo semantics and are
+/// rewritten for ecosystems that don't understand them; anything else is passed through unchanged.
+pub enum Constraint {
+    Caret(String),
+    Tilde(String),
+    Exact(String),
+    Raw(String),
+}
+
+impl PackageRequest {
+    /// Splits `name@version` on the last `@`, so npm scopes (`@types/node@^20`) keep their leading `@`.
+    pub fn parse(spec: &str, dev: bool, optional: bool, features: Vec<String>) -> PackageRequest {
+        let (name, version) = match spec.rfind('@') {
+            Some(index) if index > 0 => (&spec[..index], Some(Constraint::parse(&spec[index + 1..]))),
+            _ => (spec, None),
+        };
+        PackageRequest { name: name.to_string(), version: version.flatten(), dev, optional, features }
+    }
+}
+
+impl Constraint {
+    pub fn parse(version: &str) -> Option<Constraint> {
+        let version = version.trim();
+        if version.is_empty() || version == "*" || version == "latest" {
+            return None;
+        }
+        let constraint = if let Some(rest) = version.strip_prefix('^') {
+            Constraint::Caret(rest.trim().to_string())
+        } else if let Some(rest) = version.strip_prefix('~').filter(|rest| !rest.starts_with('=') && !rest.starts_with('>')) {
+            Constraint::Tilde(rest.trim().to_string())
+        } else if numeric_parts(version).is_some() {
+            Constraint::Exact(version.to_string())
+        } else {
+            Constraint::Raw(version.to_string())
+        };
+        Some(constraint)
+    }
+
+    /// The constraint as written, for tools that take npm/Cargo syntax (cargo, npm, pnpm, yarn, bun, poetry).
+    pub fn as_written(&self) -> String {
+        match self {
+            Constraint::Caret(version) => format!("^{}", version),
+            Constraint::Tilde(version) => format!("~{}", version),
+            Constraint::Exact(version) | Constraint::Raw(version) => version.clone(),
+        }
+    }
+
+    /// The lower and upper bound of a caret or tilde range, e.g. `^1.2` is 1.2 up to 2.
+    pub fn range(&self) -> Option<(String, String)> {
+        let (version, upper) = match self {
+            Constraint::Caret(version) => (version, caret_upper(&numeric_parts(version)?)),
+            Constraint::Tilde(version) => (version, tilde_upper(&numeric_parts(version)?)),
+            _ => return None,
+        };
+        if upper.is_empty() {
+            return None;
+        }
+        let upper = upper.iter().map(|part| part.to_string()).collect::<Vec<_>>().join(".");
+        Some((version.trim_start_matches('v').to_string(), upper))
+    }
+
+    /// PEP 440, for pip, uv and pipenv: `^1.2` becomes `>=1.2,<2` and a bare version `==1.2`.
+    pub fn pep440(&self) -> String {
+        match (self, self.range()) {
+            (_, Some((lower, upper))) => format!(">={},<{}", lower, upper),
+            (Constraint::Exact(version), _) => format!("=={}", version),
+            _ => self.as_written(),
+        }
+    }
+
+    /// RubyGems requirements, comma separated as `bundle add --version` and `gem install -v` expect.
+    pub fn rubygems(&self) -> String {
+        match self.range() {
+            Some((lower, upper)) => format!(">= {}, < {}", lower, upper),
+            None => self.as_written(),
+        }
+    }
+
+    /// NuGet and Maven both write ranges as `[1.2,2)`; Gradle accepts the same notation.
+    pub fn interval(&self) -> String {
+        match self.range() {
+            Some((lower, upper)) => format!("[{},{})", lower, upper),
+            None => self.as_written(),
+        }
+    }
+
+    /// Dart understands carets but not tildes.
+    pub fn dart(&self) -> String {
+        match (self, self.range()) {
+            (Constraint::Tilde(_), Some((lower, upper))) => format!(">={} <{}", lower, upper),
+            _ => self.as_written(),
+        }
+    }
+
+    /// A `go get` version query. The major version is part of a Go module path, so a caret only needs
+    /// a lower bound, except for v0 modules where the minor version is the breaking one.
+    pub fn go(&self) -> String {
+        let with_v = |version: &str| if version.starts_with('v') { version.to_string() } else { format!("v{}", version) };
+        match self {
+            Constraint::Exact(version) => with_v(version),
+            Constraint::Caret(version) | Constraint::Tilde(version) => {
+                let parts = numeric_parts(version).unwrap_or_default();
+                let breaking_minor = matches!(self, Constraint::Tilde(_)) || parts.first() == Some(&0);
+                match parts.as_slice() {
+                    [major, minor, ..] if breaking_minor => format!("v{}.{}", major, minor),
+                    _ => format!(">={}", with_v(version)),
+                }
+            },
+            Constraint::Raw(version) => version.clone(),
+        }
+    }
+}
+
+// "1.2.3", "v1.2" or "1.2.3-beta" as numbers; pre-release and build metadata don't affect the bounds.
+fn numeric_parts(version: &str) -> Option<Vec<u64>> {
+    let release = version.trim_start_matches('v').split(['-', '+']).next()?;
+    release.split('.').map(|part| part.parse().ok()).collect()
+}
+
+// Bumps the first non-zero part: ^1.2.3 < 2, ^0.3.1 < 0.4, ^0.0.3 < 0.0.4.
+fn caret_upper(parts: &[u64]) -> Vec<u64> {
+    if parts.is_empty() {
+        return Vec::new();
+    }
+    let index = parts.iter().position(|part| *part != 0).unwrap_or(parts.len() - 1);
+    let mut upper = parts[..index].to_vec();
+    upper.push(parts[index] + 1);
+    upper
+}
+
+// ~1.2.3 and ~1.2 stay below 1.3; ~1 stays below 2.
+fn tilde_upper(parts: &[u64]) -> Vec<u64> {
+    match parts {
+        [major] => vec![major + 1],
+        [major, minor, ..] => vec![*major, minor + 1],
+        [] => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constraint(version: &str) -> Constraint {
+        Constraint::parse(version).unwrap()
+    }
+
+    #[test]
+    fn parses_names_and_versions() {
+        let request = PackageRequest::parse("@types/node@^20", true, false, Vec::new());
+        assert_eq!(request.name, "@types/node");
+        assert!(matches!(request.version, Some(Constraint::Caret(ref version)) if version == "20"));
+        assert!(request.dev);
+
+        let scoped = PackageRequest::parse("@types/node", false, false, Vec::new());
+        assert_eq!(scoped.name, "@types/node");
+        assert!(scoped.version.is_none());
+        for spec in ["react@latest", "react@*", "react@", "react"] {
+            let request = PackageRequest::parse(spec, false, false, Vec::new());
+            assert_eq!(request.name, "react");
+            assert!(request.version.is_none(), "{}", spec);
+        }
+
+        assert!(matches!(constraint("~1.2"), Constraint::Tilde(_)));
+        assert!(matches!(constraint("2.31.0"), Constraint::Exact(_)));
+        assert!(matches!(constraint("v1.2.3"), Constraint::Exact(_)));
+        // PEP 440's ~= and Ruby's ~> are passed through as written.
+        assert!(matches!(constraint("~=1.4"), Constraint::Raw(_)));
+        assert!(matches!(constraint("~>1.4"), Constraint::Raw(_)));
+        assert!(matches!(constraint(">=4,<5"), Constraint::Raw(_)));
+    }
+
+    #[test]
+    fn caret_and_tilde_bounds() {
+        assert_eq!(caret_upper(&[1, 2, 3]), [2]);
+        assert_eq!(caret_upper(&[0, 3, 1]), [0, 4]);
+        assert_eq!(caret_upper(&[0, 0, 3]), [0, 0, 4]);
+        assert_eq!(caret_upper(&[0, 0]), [0, 1]);
+        assert_eq!(caret_upper(&[]), Vec::<u64>::new());
+        assert_eq!(tilde_upper(&[1]), [2]);
+        assert_eq!(tilde_upper(&[1, 2, 3]), [1, 3]);
+        assert_eq!(tilde_upper(&[]), Vec::<u64>::new());
+
+        assert_eq!(constraint("^v1.2").range(), Some(("1.2".to_string(), "2".to_string())));
+        assert_eq!(constraint("^1.2.3-beta.1").range(), Some(("1.2.3-beta.1".to_string(), "2".to_string())));
+        assert_eq!(constraint("~1").range(), Some(("1".to_string(), "2".to_string())));
+        assert_eq!(constraint("^next").range(), None);
+        assert_eq!(constraint("1.2.3").range(), None);
+    }
+
+    #[test]
+    fn pep440() {
+        assert_eq!(constraint("^1.2").pep440(), ">=1.2,<2");
+        assert_eq!(constraint("^0.0.3").pep440(), ">=0.0.3,<0.0.4");
+        assert_eq!(constraint("~1.2.3").pep440(), ">=1.2.3,<1.3");
+        assert_eq!(constraint("2.31.0").pep440(), "==2.31.0");
+        assert_eq!(constraint(">=4,<5").pep440(), ">=4,<5");
+    }
+
+    #[test]
+    fn rubygems_and_intervals() {
+        assert_eq!(constraint("~1").rubygems(), ">= 1, < 2");
+        assert_eq!(constraint("^0.3.1").rubygems(), ">= 0.3.1, < 0.4");
+        assert_eq!(constraint("7.1.3").rubygems(), "7.1.3");
+        assert_eq!(constraint("^0.3.1").interval(), "[0.3.1,0.4)");
+        assert_eq!(constraint("~v2.1").interval(), "[2.1,2.2)");
+        assert_eq!(constraint("13.0.1").interval(), "13.0.1");
+    }
+
+    #[test]
+    fn dart() {
+        assert_eq!(constraint("^1.2").dart(), "^1.2");
+        assert_eq!(constraint("~1.2.3").dart(), ">=1.2.3 <1.3");
+        assert_eq!(constraint("3.0.0").dart(), "3.0.0");
+    }
+
+    #[test]
+    fn go() {
+        assert_eq!(constraint("1.2.3").go(), "v1.2.3");
+        assert_eq!(constraint("v1.2.3").go(), "v1.2.3");
+        assert_eq!(constraint("^1.2").go(), ">=v1.2");
+        assert_eq!(constraint("^v1.2.0-rc.1").go(), ">=v1.2.0-rc.1");
+        assert_eq!(constraint("^0.3.1").go(), "v0.3");
+        assert_eq!(constraint("~1.4.2").go(), "v1.4");
+        assert_eq!(constraint("~1").go(), ">=v1");
+        assert_eq!(constraint("master").go(), "master");
+    }
+}
@@ -1,130 +1,685 @@
 use std::process::Command;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::build::{gradle_command, maven_command};
+use crate::dependency_edit::{
+    add_gradle_dependency, add_maven_dependency, add_swift_dependency, parse_coordinates, parse_swift_package, remove_gradle_dependency,
+    remove_maven_dependency, remove_swift_dependency, swift_package_name,
+};
 use crate::load_projects_db;
 use crate::clean_path;
+use crate::dependencies::{normalize_python_name, parse_requirement};
+use crate::project_database::{find_project_for_dir, save_project, ProjectInfo, ProjectsDb};
+use crate::package_request::{Constraint, PackageRequest};
+use crate::util::{node_tool, venv_bin};
 use std::env;
-use std::io::Write;
+use regex::Regex;
+use toml_edit::{Array, DocumentMut, Item, Table, Value};
 
-enum PackageManager {
+#[derive(Clone, PartialEq, Eq)]
+pub enum PackageManager {
     Pip,
+    Poetry,
+    Uv,
+    Pipenv,
     Cargo,
     Npm,
+    Pnpm,
+    Yarn,
+    Bun,
     Gem,
-    Other(String), // For package managers that are just a single command
+    Bundler,
+    Maven,
+    Gradle,
+    Dotnet,
+    Go,
+    Dart,
+    SwiftPm,
 }
 
-pub fn add_package(package_name: &str) {
+impl PackageManager {
+    /// Names accepted by `upman config package-manager`.
+    pub const NAMES: [&'static str; 17] =
+        ["pip", "poetry", "uv", "pipenv", "cargo", "npm", "pnpm", "yarn", "bun", "gem", "bundler", "maven", "gradle", "dotnet", "go", "dart", "swift"];
+
+    pub fn from_name(name: &str) -> Option<PackageManager> {
+        match name.to_lowercase().as_str() {
+            "pip" => Some(PackageManager::Pip),
+            "poetry" => Some(PackageManager::Poetry),
+            "uv" => Some(PackageManager::Uv),
+            "pipenv" => Some(PackageManager::Pipenv),
+            "cargo" => Some(PackageManager::Cargo),
+            "npm" => Some(PackageManager::Npm),
+            "pnpm" => Some(PackageManager::Pnpm),
+            "yarn" => Some(PackageManager::Yarn),
+            "bun" => Some(PackageManager::Bun),
+            "gem" => Some(PackageManager::Gem),
+            "bundler" | "bundle" => Some(PackageManager::Bundler),
+            "maven" | "mvn" => Some(PackageManager::Maven),
+            "gradle" => Some(PackageManager::Gradle),
+            "dotnet" | "nuget" => Some(PackageManager::Dotnet),
+            "go" => Some(PackageManager::Go),
+            "dart" => Some(PackageManager::Dart),
+            "swift" | "swiftpm" => Some(PackageManager::SwiftPm),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        PackageManager::NAMES[self.clone() as usize]
+    }
+}
+
+/// The package manager a project uses: the configured override, else whatever its lockfiles and manifests point to.
+pub fn detect_package_manager(info: &ProjectInfo) -> Option<PackageManager> {
+    if let Some(manager) = info.package_manager.as_deref().and_then(PackageManager::from_name) {
+        return Some(manager);
+    }
+    let project_path = Path::new(&info.project_path);
+    let exists = |file: &str| project_path.join(file).exists();
+
+    match info.project_language.to_lowercase().as_str() {
+        "python" => {
+            let pyproject = fs::read_to_string(project_path.join("pyproject.toml")).unwrap_or_default();
+            if exists("poetry.lock") || pyproject.contains("[tool.poetry]") {
+                Some(PackageManager::Poetry)
+            } else if exists("uv.lock") || pyproject.contains("[tool.uv]") {
+                Some(PackageManager::Uv)
+            } else if exists("Pipfile.lock") || exists("Pipfile") {
+                Some(PackageManager::Pipenv)
+            } else {
+                Some(PackageManager::Pip)
+            }
+        },
+        "javascript" | "js" | "typescript" | "ts" | "react" => {
+            // Corepack's "packageManager": "pnpm@9.1.0" field names the tool even before a lockfile exists.
+            let declared = fs::read_to_string(project_path.join("package.json"))
+                .ok()
+                .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+                .and_then(|manifest| manifest.get("packageManager")?.as_str().map(|field| field.split('@').next().unwrap_or_default().to_string()))
+                .and_then(|name| PackageManager::from_name(&name));
+            if exists("pnpm-lock.yaml") {
+                Some(PackageManager::Pnpm)
+            } else if exists("yarn.lock") {
+                Some(PackageManager::Yarn)
+            } else if exists("bun.lockb") || exists("bun.lock") {
+                Some(PackageManager::Bun)
+            } else if exists("package-lock.json") {
+                Some(PackageManager::Npm)
+            } else {
+                Some(declared.unwrap_or(PackageManager::Npm))
+            }
+        },
+        "rust" | "rs" => Some(PackageManager::Cargo),
+        "ruby" if exists("Gemfile") => Some(PackageManager::Bundler),
+        "ruby" => Some(PackageManager::Gem),
+        // Plain javac projects keep their jars in lib/ and have no dependency manifest to edit.
+        "java" if exists("pom.xml") => Some(PackageManager::Maven),
+        "java" if exists("build.gradle") || exists("build.gradle.kts") => Some(PackageManager::Gradle),
+        "cs" | "c#" => Some(PackageManager::Dotnet),
+        "go" => Some(PackageManager::Go),
+        "dart" => Some(PackageManager::Dart),
+        "swift" => Some(PackageManager::SwiftPm),
+        _ => None,
+    }
+}
+
+pub fn set_package_manager(name: &str) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let mut db = load_projects_db();
+
+    let Some((project_name, project_info)) = db.projects.iter_mut().find(|(_key, value)| current_dir_str.starts_with(&value.project_path)) else {
+        println!("No project found in the current directory.");
+        return;
+    };
+
+    // "auto" goes back to detecting the package manager from lockfiles.
+    if name == "auto" {
+        project_info.package_manager = None;
+    } else if PackageManager::from_name(name).is_some() {
+        project_info.package_manager = Some(name.to_lowercase());
+    } else {
+        eprintln!("Unknown package manager '{}'. Use one of {} or 'auto'.", name, PackageManager::NAMES.join(", "));
+        return;
+    }
+    let project_name = project_name.clone();
+    save_project(&db, &project_name);
+    println!("Project package manager updated to '{}'", name);
+}
+
+pub fn unsupported(info: &ProjectInfo) {
+    if info.project_language.eq_ignore_ascii_case("java") {
+        println!("Java projects without a pom.xml or build.gradle keep their jars in lib/. Create one with 'upman new --build-system maven|gradle'.");
+    } else {
+        println!("Package management not supported for {}", info.project_language);
+    }
+}
+
+pub fn current_project_info(db: &ProjectsDb) -> Option<&ProjectInfo> {
     let current_dir = env::current_dir().expect("Failed to get current directory");
     let current_dir_str = clean_path(&current_dir);
+    match find_project_for_dir(db, &current_dir_str) {
+        Some((_project_name, info)) => Some(info),
+        None => {
+            println!("Current directory is not a recognized UPM project.");
+            None
+        },
+    }
+}
 
+pub fn add_package(spec: &str, dev: bool, optional: bool, features: Vec<String>) {
     let db = load_projects_db();
-    
-    let project_info = db.projects.iter().find(|(_key, value)| {
-        current_dir_str.starts_with(&value.project_path)
-    });
-
-    if let Some((_project_name, info)) = project_info {
-        let package_manager = match info.project_language.as_str() {
-            "python" => PackageManager::Pip,
-            "rust" => PackageManager::Cargo,
-            "javascript" => PackageManager::Npm,
-            "ruby" => PackageManager::Gem,
-            "java" => PackageManager::Other("gradle".to_string()),
-            "c#" => PackageManager::Other("nuget".to_string()),
-            _ => {
-                println!("Package management not supported for {}", info.project_language);
-                return;
-            },
-        };
+    let Some(info) = current_project_info(&db) else {
+        return;
+    };
+    let Some(package_manager) = detect_package_manager(info) else {
+        unsupported(info);
+        return;
+    };
+    let request = PackageRequest::parse(spec, dev, optional, features);
+    if let Err(message) = check_request(&package_manager, Path::new(&info.project_path), &request) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+
+    execute_package_command(package_manager, Path::new(&info.project_path), spec, &request);
+}
+
+// Rejects flags the package manager has no equivalent for before anything is installed.
+fn check_request(package_manager: &PackageManager, project_path: &Path, request: &PackageRequest) -> Result<(), String> {
+    use PackageManager::*;
+    let supports_dev = !matches!(package_manager, Gem | Go | Dotnet | SwiftPm);
+    let supports_optional = matches!(package_manager, Pip | Poetry | Uv | Cargo | Npm | Pnpm | Yarn | Bun);
+    // Python extras are the closest thing to Cargo features.
+    let supports_features = matches!(package_manager, Pip | Poetry | Uv | Pipenv | Cargo);
+    let unsupported = |flag: &str| Err(format!("{} is not supported for {} dependencies.", flag, package_manager.name()));
 
-        execute_package_command(package_manager, &PathBuf::from(&info.project_path), package_name);
+    if request.dev && !supports_dev {
+        unsupported("--dev")
+    } else if request.optional && !supports_optional {
+        unsupported("--optional")
+    } else if request.optional && *package_manager == Pip && pep621_pyproject(project_path).is_none() {
+        Err("Optional dependencies are declared in pyproject.toml, which needs a [project] table.".to_string())
+    } else if !request.features.is_empty() && !supports_features {
+        unsupported("--features")
     } else {
-        println!("Current directory is not a recognized UPM project.");
+        Ok(())
     }
 }
 
-fn execute_package_command(package_manager: PackageManager, current_dir: &PathBuf, package_name: &str) {
-    match package_manager {
-        PackageManager::Pip => {
-            let requirements_path = current_dir.join("requirements.txt");
-            let mut file = fs::OpenOptions::new()
-                .write(true)
-                .append(true)
-                .open(&requirements_path)
-                .expect("Failed to open requirements.txt");
-            writeln!(file, "{}", package_name).expect("Failed to write to requirements.txt");
-
-            let pip_path = if cfg!(target_os = "windows") {
-                current_dir.join("venv").join("Scripts").join("pip.exe")
-            } else {
-                current_dir.join("venv").join("bin").join("pip")
-            };
+pub fn execute_package_command(package_manager: PackageManager, current_dir: &Path, spec: &str, request: &PackageRequest) {
+    let name = request.name.as_str();
+    let written = request.version.as_ref().map(Constraint::as_written);
+    let at_version = |separator: &str, version: Option<String>| match version {
+        Some(version) => format!("{}{}{}", name, separator, version),
+        None => name.to_string(),
+    };
 
-            let status = Command::new(pip_path)
-                .args(&["install", package_name])
-                .status()
-                .expect("Failed to install package");
-            if !status.success() {
-                eprintln!("Failed to add package '{}'.", package_name);
+    let mut command = match package_manager {
+        PackageManager::Pip => tool_command(&venv_bin(current_dir, "pip").to_string_lossy(), &["install", &python_requirement(request)]),
+        PackageManager::Poetry => {
+            let mut command = tool_command("poetry", &["add", &at_version("@", written)]);
+            if request.dev {
+                command.args(["--group", "dev"]);
+            } else if request.optional {
+                command.arg(format!("--optional={}", name));
+            }
+            for feature in &request.features {
+                command.args(["--extras", feature]);
+            }
+            command
+        },
+        PackageManager::Uv => {
+            let mut command = tool_command("uv", &["add", &python_requirement(request)]);
+            if request.dev {
+                command.arg("--dev");
+            } else if request.optional {
+                command.args(["--optional", name]);
+            }
+            command
+        },
+        PackageManager::Pipenv => {
+            let mut command = tool_command("pipenv", &["install", &python_requirement(request)]);
+            if request.dev {
+                command.arg("--dev");
             }
+            command
         },
         PackageManager::Cargo => {
-            let status = Command::new("cargo")
-                .args(&["add", package_name])
-                .current_dir(current_dir)
-                .status()
-                .expect("Failed to run cargo command");
-            if !status.success() {
-                eprintln!("Failed to add package '{}'.", package_name);
+            let mut command = tool_command("cargo", &["add", &at_version("@", written)]);
+            if request.dev {
+                command.arg("--dev");
+            }
+            if request.optional {
+                command.arg("--optional");
+            }
+            if !request.features.is_empty() {
+                command.args(["--features", &request.features.join(",")]);
             }
+            command
         },
-        PackageManager::Npm => {
-            let status = Command::new("npm")
-                .args(&["install", "--save", package_name])
-                .current_dir(current_dir)
-                .status()
-                .expect("Failed to run npm command");
-            if !status.success() {
-                eprintln!("Failed to add package '{}'.", package_name);
+        PackageManager::Npm | PackageManager::Pnpm | PackageManager::Yarn | PackageManager::Bun => {
+            let (program, add) = match package_manager {
+                PackageManager::Npm => (node_tool("npm"), "install"),
+                PackageManager::Pnpm => (node_tool("pnpm"), "add"),
+                PackageManager::Yarn => (node_tool("yarn"), "add"),
+                _ => ("bun".to_string(), "add"),
+            };
+            let mut command = tool_command(&program, &[add, &at_version("@", written)]);
+            // npm and pnpm spell the flags --save-dev/--save-optional; yarn and bun use --dev/--optional.
+            let prefix = if matches!(package_manager, PackageManager::Npm | PackageManager::Pnpm) { "--save-" } else { "--" };
+            if request.dev {
+                command.arg(format!("{}dev", prefix));
+            } else if request.optional {
+                command.arg(format!("{}optional", prefix));
+            } else if package_manager == PackageManager::Npm {
+                command.arg("--save");
             }
+            command
         },
         PackageManager::Gem => {
-            let status = Command::new("gem")
-                .args(&["install", package_name])
-                .current_dir(current_dir)
-                .status()
-                .expect("Failed to run gem command");
-            if !status.success() {
-                eprintln!("Failed to add package '{}'.", package_name);
-            }
-        },
-        PackageManager::Other(cmd) => match cmd.as_str() {
-            "gradle" => {
-                let status = Command::new(cmd)
-                    .args(&["install", package_name])
-                    .current_dir(current_dir)
-                    .status()
-                    .expect("Failed to run package manager command");
-                if !status.success() {
-                    eprintln!("Failed to add package '{}'.", package_name);
+            let mut command = tool_command("gem", &["install", name]);
+            if let Some(version) = &request.version {
+                command.args(["-v", &version.rubygems()]);
+            }
+            command
+        },
+        PackageManager::Bundler => {
+            let mut command = tool_command("bundle", &["add", name]);
+            if let Some(version) = &request.version {
+                command.args(["--version", &version.rubygems()]);
+            }
+            if request.dev {
+                command.args(["--group", "development"]);
+            }
+            command
+        },
+        PackageManager::Dotnet => {
+            let mut command = tool_command("dotnet", &["add", "package", name]);
+            if let Some(version) = &request.version {
+                command.args(["--version", &version.interval()]);
+            }
+            command
+        },
+        PackageManager::Go => tool_command("go", &["get", &at_version("@", request.version.as_ref().map(Constraint::go))]),
+        PackageManager::Dart => {
+            let package = at_version(":", request.version.as_ref().map(Constraint::dart));
+            let package = if request.dev { format!("dev:{}", package) } else { package };
+            tool_command(dart_tool(current_dir), &["pub", "add", &package])
+        },
+        PackageManager::Maven | PackageManager::Gradle | PackageManager::SwiftPm => {
+            edit_build_file(&package_manager, current_dir, spec, request);
+            return;
+        },
+    };
+
+    // `bundle add` refuses gems the Gemfile already lists, so the old entry is dropped first and put back if the add fails.
+    let gemfile_backup = if package_manager == PackageManager::Bundler && gemfile_lists(current_dir, name) {
+        let backup = ["Gemfile", "Gemfile.lock"].map(|file| fs::read_to_string(current_dir.join(file)).ok());
+        run_package_command(tool_command("bundle", &["remove", name]).current_dir(current_dir));
+        Some(backup)
+    } else {
+        None
+    };
+
+    if !run_package_command(command.current_dir(current_dir)) {
+        if let Some(backup) = gemfile_backup {
+            for (file, contents) in ["Gemfile", "Gemfile.lock"].iter().zip(backup) {
+                if let Some(contents) = contents {
+                    let _ = fs::write(current_dir.join(file), contents);
                 }
+            }
+        }
+        eprintln!("Failed to add package '{}'.", spec);
+        std::process::exit(1);
+    }
+
+    // pip has no manifest of its own, so the requirement is recorded once the install has succeeded.
+    if package_manager == PackageManager::Pip {
+        match record_python_requirement(current_dir, request) {
+            Ok(file) => println!("Package '{}' added to {}.", spec, file),
+            Err(err) => {
+                eprintln!("Package '{}' was installed, but the manifest could not be updated: {}", spec, err);
+                std::process::exit(1);
             },
-            "nuget" => {
-                let status = Command::new(cmd)
-                    .args(&["install", package_name])
-                    .current_dir(current_dir)
-                    .status()
-                    .expect("Failed to run package manager command");
-                if !status.success() {
-                    eprintln!("Failed to add package '{}'.", package_name);
+        }
+    } else {
+        println!("Package '{}' added successfully.", spec);
+    }
+}
+
+// Java and Swift builds are plain files; the dependency is written there and resolved on the next build.
+fn edit_build_file(package_manager: &PackageManager, project_path: &Path, spec: &str, request: &PackageRequest) {
+    let mut hint = None;
+    let (file, added) = match package_manager {
+        PackageManager::SwiftPm => {
+            let package = parse_swift_package(spec);
+            hint = Some(format!("Add .product(name: \"...\", package: \"{}\") to the target that uses it.", swift_package_name(&package.url)));
+            ("Package.swift".to_string(), add_swift_dependency(&project_path.join("Package.swift"), &package))
+        },
+        _ => {
+            let coordinates = parse_coordinates(&request.name).map(|mut coordinates| {
+                // `group:artifact@^1.2` is accepted too, as a Maven/Gradle range.
+                if let Some(version) = &request.version {
+                    coordinates.version = Some(version.interval());
                 }
-            },
-            _ => {
-                eprintln!("Package management not supported for '{}'.", cmd);
+                coordinates
+            });
+            let Some(coordinates) = coordinates.filter(|coordinates| coordinates.version.is_some()) else {
+                eprintln!("Java dependencies are written as group:artifact:version, e.g. com.google.guava:guava:33.0.0-jre.");
+                std::process::exit(1);
+            };
+            if *package_manager == PackageManager::Maven {
+                let scope = if request.dev { Some("test") } else { None };
+                ("pom.xml".to_string(), add_maven_dependency(&project_path.join("pom.xml"), &coordinates, scope))
+            } else {
+                let build_file = gradle_build_file(project_path);
+                let configuration = if request.dev { "testImplementation" } else { "implementation" };
+                let added = add_gradle_dependency(&build_file, &coordinates, configuration);
+                (build_file.file_name().unwrap().to_string_lossy().to_string(), added)
             }
         },
+    };
+    if added {
+        println!("Package '{}' added to {}.", spec, file);
+        if let Some(hint) = hint {
+            println!("{}", hint);
+        }
+    } else {
+        eprintln!("Failed to add package '{}'.", spec);
+        std::process::exit(1);
+    }
+}
+
+fn gradle_build_file(project_path: &Path) -> PathBuf {
+    let kotlin = project_path.join("build.gradle.kts");
+    if kotlin.exists() { kotlin } else { project_path.join("build.gradle") }
+}
+
+// Flutter packages are managed with `flutter pub`, which also updates the Flutter SDK dependencies.
+pub fn dart_tool(project_path: &Path) -> &'static str {
+    let pubspec = fs::read_to_string(project_path.join("pubspec.yaml")).unwrap_or_default();
+    if pubspec.contains("sdk: flutter") { "flutter" } else { "dart" }
+}
+
+pub fn tool_command(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new(program);
+    command.args(args);
+    command
+}
+
+pub fn run_package_command(command: &mut Command) -> bool {
+    match command.status() {
+        Ok(status) => status.success(),
+        Err(err) => {
+            eprintln!("Failed to run {}: {}", command.get_program().to_string_lossy(), err);
+            false
+        },
+    }
+}
+
+pub fn remove_package(package_name: &str) {
+    let db = load_projects_db();
+    let Some(info) = current_project_info(&db) else {
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
+    let Some(package_manager) = detect_package_manager(info) else {
+        unsupported(info);
+        return;
+    };
+
+    let mut command = match &package_manager {
+        PackageManager::Pip => tool_command(&venv_bin(project_path, "pip").to_string_lossy(), &["uninstall", "-y", package_name]),
+        PackageManager::Poetry => tool_command("poetry", &["remove", package_name]),
+        PackageManager::Uv => tool_command("uv", &["remove", package_name]),
+        PackageManager::Pipenv => tool_command("pipenv", &["uninstall", package_name]),
+        PackageManager::Cargo => tool_command("cargo", &["remove", package_name]),
+        PackageManager::Npm => tool_command(&node_tool("npm"), &["uninstall", package_name]),
+        PackageManager::Pnpm => tool_command(&node_tool("pnpm"), &["remove", package_name]),
+        PackageManager::Yarn => tool_command(&node_tool("yarn"), &["remove", package_name]),
+        PackageManager::Bun => tool_command("bun", &["remove", package_name]),
+        PackageManager::Gem => tool_command("gem", &["uninstall", package_name]),
+        PackageManager::Bundler => tool_command("bundle", &["remove", package_name]),
+        PackageManager::Dotnet => tool_command("dotnet", &["remove", "package", package_name]),
+        PackageManager::Go => tool_command("go", &["get", &format!("{}@none", package_name)]),
+        PackageManager::Dart => tool_command(dart_tool(project_path), &["pub", "remove", package_name]),
+        PackageManager::Maven | PackageManager::Gradle => {
+            let Some(coordinates) = parse_coordinates(package_name) else {
+                eprintln!("Java dependencies are removed by group:artifact, e.g. com.google.guava:guava.");
+                std::process::exit(1);
+            };
+            let removed = if package_manager == PackageManager::Maven {
+                remove_maven_dependency(&project_path.join("pom.xml"), &coordinates)
+            } else {
+                remove_gradle_dependency(&gradle_build_file(project_path), &coordinates)
+            };
+            report_removed(package_name, removed);
+            return;
+        },
+        PackageManager::SwiftPm => {
+            report_removed(package_name, remove_swift_dependency(&project_path.join("Package.swift"), package_name));
+            return;
+        },
+    };
+
+    let removed = run_package_command(command.current_dir(project_path));
+    // pip has no manifest of its own, so the requirements files are edited here.
+    let dropped = package_manager == PackageManager::Pip && remove_requirement(project_path, package_name);
+    report_removed(package_name, removed || dropped);
+}
+
+fn report_removed(package_name: &str, removed: bool) {
+    if removed {
+        println!("Package '{}' removed successfully.", package_name);
+    } else {
+        eprintln!("Failed to remove package '{}'.", package_name);
+        std::process::exit(1);
+    }
+}
+
+/// Installs everything the project's manifests and lockfiles ask for.
+pub fn install_packages() {
+    let db = load_projects_db();
+    let Some(info) = current_project_info(&db) else {
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
+    let Some(package_manager) = detect_package_manager(info) else {
+        unsupported(info);
+        return;
+    };
+
+    let mut commands = match &package_manager {
+        PackageManager::Pip => {
+            let pip = venv_bin(project_path, "pip").to_string_lossy().to_string();
+            ["requirements.txt", "requirements-dev.txt"]
+                .iter()
+                .filter(|file| project_path.join(file).exists())
+                .map(|file| tool_command(&pip, &["install", "-r", file]))
+                .collect()
+        },
+        PackageManager::Poetry => vec![tool_command("poetry", &["install"])],
+        PackageManager::Uv => vec![tool_command("uv", &["sync"])],
+        PackageManager::Pipenv => vec![tool_command("pipenv", &["install", "--dev"])],
+        PackageManager::Cargo => vec![tool_command("cargo", &["fetch"])],
+        PackageManager::Npm => vec![tool_command(&node_tool("npm"), &["install"])],
+        PackageManager::Pnpm => vec![tool_command(&node_tool("pnpm"), &["install"])],
+        PackageManager::Yarn => vec![tool_command(&node_tool("yarn"), &["install"])],
+        PackageManager::Bun => vec![tool_command("bun", &["install"])],
+        PackageManager::Bundler => vec![tool_command("bundle", &["install"])],
+        PackageManager::Gem => Vec::new(),
+        PackageManager::Maven => maven_command(project_path).map(|mut command| {
+            command.args(["-q", "dependency:resolve"]);
+            command
+        }).into_iter().collect(),
+        PackageManager::Gradle => gradle_command(project_path).map(|mut command| {
+            command.args(["-q", "dependencies"]);
+            command
+        }).into_iter().collect(),
+        PackageManager::Dotnet => vec![tool_command("dotnet", &["restore"])],
+        PackageManager::Go => vec![tool_command("go", &["mod", "download"])],
+        PackageManager::Dart => vec![tool_command(dart_tool(project_path), &["pub", "get"])],
+        PackageManager::SwiftPm => vec![tool_command("swift", &["package", "resolve"])],
+    };
+    if commands.is_empty() {
+        println!("Nothing to install: the project has no dependency manifest.");
+        return;
+    }
+
+    for command in commands.iter_mut() {
+        if !run_package_command(command.current_dir(project_path)) {
+            eprintln!("Failed to install dependencies.");
+            std::process::exit(1);
+        }
     }
+    println!("Dependencies installed.");
+}
+
+// Drops the package from requirements.txt and requirements-dev.txt, keeping every other line as written.
+fn remove_requirement(project_path: &Path, package_name: &str) -> bool {
+    let mut dropped = false;
+    for file in ["requirements.txt", "requirements-dev.txt"] {
+        let path = project_path.join(file);
+        if !path.exists() {
+            continue;
+        }
+        match update_requirements_file(&path, package_name, None) {
+            Ok(changed) => dropped |= changed,
+            Err(err) => eprintln!("Failed to update {}: {}", file, err),
+        }
+    }
+    dropped
+}
+
+/// `name[extra,...]<PEP 440 specifier>`, as pip, uv and pipenv take it and requirements files record it.
+fn python_requirement(request: &PackageRequest) -> String {
+    let extras = if request.features.is_empty() { String::new() } else { format!("[{}]", request.features.join(",")) };
+    let specifier = request.version.as_ref().map(Constraint::pep440).unwrap_or_default();
+    format!("{}{}{}", request.name, extras, specifier)
+}
+
+fn pep621_pyproject(project_path: &Path) -> Option<DocumentMut> {
+    let document = fs::read_to_string(project_path.join("pyproject.toml")).ok()?.parse::<DocumentMut>().ok()?;
+    document.get("project").is_some_and(Item::is_table).then_some(document)
+}
+
+// pip projects keep using their requirements files; a PEP 621 pyproject.toml is used when they have none,
+// and is the only place optional dependencies (extras) can be declared.
+fn record_python_requirement(project_path: &Path, request: &PackageRequest) -> Result<String, String> {
+    let requirement = python_requirement(request);
+    let (file, other) = if request.dev { ("requirements-dev.txt", "requirements.txt") } else { ("requirements.txt", "requirements-dev.txt") };
+    let pyproject = pep621_pyproject(project_path);
 
-    println!("Package '{}' added successfully.", package_name);
-}
\ No newline at end of file
+    if request.optional || (!project_path.join(file).exists() && pyproject.is_some()) {
+        let mut document = pyproject.ok_or("pyproject.toml has no [project] table")?;
+        let (target, other): (&[&str], &[&str]) = if request.optional {
+            (&["project", "optional-dependencies", &request.name], &[])
+        } else if request.dev {
+            (&["dependency-groups", "dev"], &["project", "dependencies"])
+        } else {
+            (&["project", "dependencies"], &["dependency-groups", "dev"])
+        };
+        let array = requirement_array(document.as_table_mut(), target).ok_or(format!("{} is not an array", target.join(".")))?;
+        set_requirement(array, &request.name, &requirement);
+        // Moving a package between runtime and dev leaves a single entry.
+        if let Some(array) = existing_requirement_array(document.as_table_mut(), other) {
+            array.retain(|value| value.as_str().and_then(parse_requirement).is_none_or(|(name, _)| !same_package(&name, &request.name)));
+        }
+        fs::write(project_path.join("pyproject.toml"), document.to_string()).map_err(|err| err.to_string())?;
+        return Ok("pyproject.toml".to_string());
+    }
+
+    update_requirements_file(&project_path.join(file), &request.name, Some(&requirement)).map_err(|err| err.to_string())?;
+    update_requirements_file(&project_path.join(other), &request.name, None).map_err(|err| err.to_string())?;
+    Ok(file.to_string())
+}
+
+fn same_package(name: &str, other: &str) -> bool {
+    normalize_python_name(name) == normalize_python_name(other)
+}
+
+// Replaces the first line for the package (dropping any duplicates) or appends one; `None` removes it.
+// Comments, options and the order of the other lines are left alone.
+fn update_requirements_file(path: &Path, package_name: &str, requirement: Option<&str>) -> std::io::Result<bool> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let mut lines = Vec::new();
+    let mut replaced = false;
+    let mut changed = false;
+    for line in contents.lines() {
+        if parse_requirement(line).is_some_and(|(name, _)| same_package(&name, package_name)) {
+            if let Some(requirement) = requirement.filter(|_| !replaced) {
+                let comment = line.find(" #").map(|index| &line[line[..index].trim_end().len()..]).unwrap_or_default();
+                let updated = format!("{}{}", requirement, comment);
+                changed |= line != updated;
+                lines.push(updated);
+                replaced = true;
+            } else {
+                changed = true;
+            }
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if let Some(requirement) = requirement.filter(|_| !replaced) {
+        lines.push(requirement.to_string());
+        changed = true;
+    }
+    if !changed {
+        return Ok(false);
+    }
+
+    let mut updated = lines.join("\n");
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    fs::write(path, updated)?;
+    Ok(true)
+}
+
+// The array at `keys`, creating the tables on the way and the array itself as needed.
+fn requirement_array<'a>(table: &'a mut Table, keys: &[&str]) -> Option<&'a mut Array> {
+    let (last, tables) = keys.split_last()?;
+    let mut table = table;
+    for key in tables {
+        table = table.entry(key).or_insert(Item::Table(Table::new())).as_table_mut()?;
+    }
+    table.entry(last).or_insert(toml_edit::value(Array::new())).as_array_mut()
+}
+
+fn existing_requirement_array<'a>(table: &'a mut Table, keys: &[&str]) -> Option<&'a mut Array> {
+    let (last, tables) = keys.split_last()?;
+    let mut table = table;
+    for key in tables {
+        table = table.get_mut(key)?.as_table_mut()?;
+    }
+    table.get_mut(last)?.as_array_mut()
+}
+
+fn set_requirement(array: &mut Array, package_name: &str, requirement: &str) {
+    let matches = |value: &Value| value.as_str().and_then(parse_requirement).is_some_and(|(name, _)| same_package(&name, package_name));
+    let Some(index) = array.iter().position(matches) else {
+        // New entries follow the layout of the last one, so multi-line arrays stay multi-line.
+        let mut value = Value::from(requirement);
+        if let Some(last) = array.iter().last() {
+            *value.decor_mut() = last.decor().clone();
+        }
+        array.push_formatted(value);
+        return;
+    };
+    array.replace(index, requirement);
+    let mut index = index + 1;
+    while index < array.len() {
+        if array.get(index).is_some_and(matches) {
+            array.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+}
+
+fn gemfile_lists(project_path: &Path, gem: &str) -> bool {
+    let declaration = Regex::new(&format!(r#"^\s*gem\s+["']{}["']"#, regex::escape(gem))).unwrap();
+    fs::read_to_string(project_path.join("Gemfile")).is_ok_and(|contents| contents.lines().any(|line| declaration.is_match(line)))
+}
@@ -1,8 +1,9 @@
 use std::path::{Path};
 use std::fs::{self, OpenOptions};
 use std::io::{Read, Write};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use serde::{Serialize, Deserialize};
+use crate::manifest::{has_manifest, load_manifest, save_manifest, ManifestProject, ProjectManifest};
 use crate::util::get_install_path;
 
 #[derive(Serialize, Deserialize)]
@@ -10,11 +11,98 @@ pub struct ProjectsDb {
     pub projects: HashMap<String, ProjectInfo>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// A registered project. For projects with an `upm.toml`, everything but the path is read from the manifest.
+#[derive(Serialize, Deserialize, Default)]
 pub struct ProjectInfo {
     pub project_path: String,
+    #[serde(default)]
     pub project_language: String,
+    #[serde(default)]
     pub project_main: String,
+    #[serde(default, skip_serializing_if = "NativeBuild::is_empty")]
+    pub native_build: NativeBuild,
+    /// Directory `upman run` starts in, relative to the project root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    /// Package manager chosen with `upman config package-manager`, overriding lockfile detection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, TaskDef>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub processes: BTreeMap<String, String>,
+}
+
+impl ProjectInfo {
+    fn apply_manifest(&mut self, manifest: ProjectManifest) {
+        self.project_language = manifest.project.language;
+        self.project_main = manifest.project.main;
+        self.working_dir = manifest.project.working_dir;
+        self.package_manager = manifest.project.package_manager;
+        self.license = manifest.project.license;
+        self.template = manifest.project.template;
+        self.native_build = manifest.build;
+        self.env = manifest.env;
+        self.tasks = manifest.tasks;
+        self.processes = manifest.processes;
+    }
+
+    fn to_manifest(&self, project_name: &str) -> ProjectManifest {
+        ProjectManifest {
+            project: ManifestProject {
+                name: project_name.to_string(),
+                language: self.project_language.clone(),
+                main: self.project_main.clone(),
+                license: self.license.clone(),
+                template: self.template.clone(),
+                working_dir: self.working_dir.clone(),
+                package_manager: self.package_manager.clone(),
+            },
+            build: self.native_build.clone(),
+            env: self.env.clone(),
+            tasks: self.tasks.clone(),
+            processes: self.processes.clone(),
+        }
+    }
+}
+
+/// A named command run with `upman task <name>`, after the tasks it depends on.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct TaskDef {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Directory the command runs in, relative to the project root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Glob patterns for the files the task reads; the task is skipped while they are unchanged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+}
+
+/// Compiler settings for C and C++ projects, set with `upman config includes|cflags|libs`.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct NativeBuild {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_dirs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub libs: Vec<String>,
+}
+
+impl NativeBuild {
+    pub fn is_empty(&self) -> bool {
+        self.include_dirs.is_empty() && self.flags.is_empty() && self.libs.is_empty()
+    }
 }
 
 fn db_path() -> String {
@@ -32,12 +120,30 @@ pub fn load_projects_db() -> ProjectsDb {
     let mut file = fs::File::open(db_path).expect("Failed to open projects database");
     let mut contents = String::new();
     file.read_to_string(&mut contents).expect("Failed to read projects database");
-    serde_json::from_str(&contents).unwrap_or_else(|_| ProjectsDb { projects: HashMap::new() })
+    let mut db: ProjectsDb = serde_json::from_str(&contents).unwrap_or_else(|_| ProjectsDb { projects: HashMap::new() });
+
+    for info in db.projects.values_mut() {
+        if let Some(manifest) = load_manifest(Path::new(&info.project_path)) {
+            info.apply_manifest(manifest);
+        }
+    }
+    db
 }
 pub fn save_projects_db(db: &ProjectsDb) {
     let path_str = db_path();
     let db_path = Path::new(&path_str);
-    let contents = serde_json::to_string(db).expect("Failed to serialize projects database");
+
+    // Projects with a manifest are indexed by path only; older entries keep their settings here.
+    let mut index = serde_json::to_value(db).expect("Failed to serialize projects database");
+    if let Some(projects) = index.get_mut("projects").and_then(|projects| projects.as_object_mut()) {
+        for entry in projects.values_mut() {
+            let project_path = entry["project_path"].as_str().unwrap_or_default().to_string();
+            if has_manifest(Path::new(&project_path)) {
+                *entry = serde_json::json!({ "project_path": project_path });
+            }
+        }
+    }
+    let contents = serde_json::to_string(&index).expect("Failed to serialize projects database");
 
     let mut file = OpenOptions::new()
         .write(true)
@@ -49,14 +155,37 @@ pub fn save_projects_db(db: &ProjectsDb) {
     file.write_all(contents.as_bytes()).expect("Failed to write projects database");
 }
 
-pub fn add_project_to_db(project_name: &str, project_path: &str, project_language: &str, project_main: &str) {
+pub fn add_project_to_db(project_name: &str, project_path: &str, project_language: &str, project_main: &str, license: Option<&str>) {
     let mut db = load_projects_db();
 
     db.projects.insert(project_name.to_string(), ProjectInfo {
         project_path: project_path.to_string(),
         project_language: project_language.to_string(),
         project_main: project_main.to_string(),
+        license: license.map(String::from),
+        ..ProjectInfo::default()
     });
 
+    save_project(&db, project_name);
+}
+
+/// Indexes a project whose settings come from the `upm.toml` already in its root.
+pub fn register_manifest_project(project_name: &str, project_path: &str) {
+    let mut db = load_projects_db();
+    db.projects.insert(project_name.to_string(), ProjectInfo {
+        project_path: project_path.to_string(),
+        ..ProjectInfo::default()
+    });
     save_projects_db(&db);
-}
\ No newline at end of file
+}
+
+/// Writes the project's settings to its `upm.toml` and updates the index.
+pub fn save_project(db: &ProjectsDb, project_name: &str) {
+    if let Some(info) = db.projects.get(project_name) {
+        save_manifest(Path::new(&info.project_path), &info.to_manifest(project_name));
+    }
+    save_projects_db(db);
+}
+pub fn find_project_for_dir<'a>(db: &'a ProjectsDb, dir: &str) -> Option<(&'a String, &'a ProjectInfo)> {
+    db.projects.iter().find(|(_key, value)| dir.starts_with(&value.project_path))
+}
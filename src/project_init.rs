@@ -4,17 +4,20 @@ use std::fs::{ self, File };
 use std::io::{ self, Write };
 use std::env;
 
-use crate::project_database::{ add_project_to_db, load_projects_db };
-use crate::util::{ clean_path, get_install_path };
-use crate::config::{ read_config_from };
+use crate::manifest::{load_manifest, MANIFEST_FILE};
+use crate::project_database::{ add_project_to_db, load_projects_db, register_manifest_project };
+use crate::util::{ clean_path };
+use crate::license::{initialize_project_license, parse_license_expression};
+use crate::test_scaffold::initialize_tests;
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_project(
     project_name: &str,
     project_language: &str,
     git: bool,
     ignore: bool,
     venv: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -137,7 +140,8 @@ pub fn create_project(
     let project_path = clean_path(
         &Path::new(project_name).canonicalize().expect("Failed to get absolute path")
     );
-    add_project_to_db(project_name, &project_path, project_language, project_main);
+    let license = license.and_then(|expression| parse_license_expression(expression).ok());
+    add_project_to_db(project_name, &project_path, project_language, project_main, license.as_deref());
 }
 
 fn initialize_docs(project_path: &Path) {
@@ -152,54 +156,8 @@ fn initialize_docs(project_path: &Path) {
     println!("Initialized docs directory.");
 }
 
-fn initialize_tests(project_path: &Path) {
-    let tests_path = project_path.join("tests");
-    fs::create_dir_all(&tests_path).expect("Failed to create tests directory");
-    println!("Initialized tests directory.");
-}
-
-fn initialize_license(project_path: &Path) {
-    let install_path = get_install_path().unwrap();
-    let license_dir_str = format!("{}/licenses", install_path);
-    let license_dir = Path::new(&license_dir_str);
-
-    if !license_dir.exists() {
-        eprintln!("No licenses directory found.");
-        return;
-    }
-
-    let entries = match fs::read_dir(license_dir) {
-        Ok(entries) => entries,
-        Err(err) => {
-            eprintln!("Failed to read licenses directory: {}", err);
-            return;
-        }
-    };
-
-    let config = read_config_from();
-    let preferred_license_name = config.preferences.license;
-
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let license_name = entry.file_name();
-
-            if *license_name == *preferred_license_name {
-                let license_content = fs::read_to_string(entry.path())
-                    .expect("Failed to read license file");
-
-                let license_path = project_path.join("LICENSE");
-                fs::write(&license_path, &license_content)
-                    .expect("Failed to create LICENSE file");
-
-                println!("Initialized LICENSE file with the preferred license.");
-                return;
-            }
-
-            println!("{}", license_name.to_string_lossy());
-        }
-    }
-
-    eprintln!("Preferred license not found in the directory.");
+fn initialize_license(project_path: &Path, expression: &str) {
+    initialize_project_license(project_path, expression);
 }
 
 fn initialize_readme(project_path: &Path) {
@@ -219,14 +177,14 @@ fn initialize_docker(project_path: &Path) {
 fn initialize_git(project_path: &Path, git: bool, ignore: bool) {
     if git {
         Command::new("git")
-            .args(&["init", project_path.to_str().unwrap()])
+            .args(["init", project_path.to_str().unwrap()])
             .status()
             .expect("Failed to initialize git repository");
         println!("Initialized empty Git repository in {}/.git/", project_path.display());
 
         if ignore {
             let gitignore_path = project_path.join(".gitignore");
-            let gitignore_content = "venv/\n__pycache__/\n*.pyc";
+            let gitignore_content = "venv/\n__pycache__/\n*.pyc\n.upm/";
             fs::write(gitignore_path, gitignore_content).expect("Failed to create .gitignore");
             println!("Created .gitignore");
         }
@@ -235,20 +193,21 @@ fn initialize_git(project_path: &Path, git: bool, ignore: bool) {
 
 fn initialize_documents(
     project_path: &Path,
-    license: bool,
+    language: &str,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
     docker: bool
 ) {
-    if license {
-        initialize_license(project_path);
+    if let Some(expression) = license {
+        initialize_license(project_path, expression);
     }
     if readme {
         initialize_readme(project_path);
     }
     if tests {
-        initialize_tests(project_path);
+        initialize_tests(project_path, language);
     }
     if docs {
         initialize_docs(project_path);
@@ -268,6 +227,19 @@ pub fn init_project(project_language: Option<&str>, project_main: Option<&str>)
         return;
     }
 
+    // A committed upm.toml already says everything init would ask for.
+    if let Some(manifest) = load_manifest(&current_dir) {
+        register_manifest_project(&manifest.project.name, &current_dir_str);
+        println!(
+            "Registered '{}' from {} with language '{}' and main file '{}'.",
+            manifest.project.name,
+            MANIFEST_FILE,
+            manifest.project.language,
+            manifest.project.main
+        );
+        return;
+    }
+
     let project_language = match project_language {
         Some(lang) => lang.to_string(),
         None => {
@@ -303,7 +275,8 @@ pub fn init_project(project_language: Option<&str>, project_main: Option<&str>)
         current_dir.file_name().unwrap().to_str().unwrap(),
         &current_dir_str,
         &project_language,
-        &project_main
+        &project_main,
+        None
     );
     println!(
         "Initialized '{}' as a UPM project with language '{}' and main file '{}'.",
@@ -312,11 +285,12 @@ pub fn init_project(project_language: Option<&str>, project_main: Option<&str>)
         project_main
     );
 }
+#[allow(clippy::too_many_arguments)]
 fn create_shell_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -352,15 +326,16 @@ fn create_shell_project(
     }
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "shell", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_cpp_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -383,15 +358,16 @@ fn create_cpp_project(
     ).expect("Failed to write to main.cpp");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "cpp", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_c_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -414,15 +390,16 @@ fn create_c_project(
     ).expect("Failed to write to main.c");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "c", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_rust_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -431,25 +408,26 @@ fn create_rust_project(
     println!("Initializing Rust project...");
     let root_path = Path::new(project_name);
     Command::new("cargo")
-        .args(&["new", project_name, "--bin"])
+        .args(["new", project_name, "--bin"])
         .status()
         .expect("Failed to create Rust project with Cargo");
 
     if git && ignore {
         let gitignore_path = Path::new(project_name).join(".gitignore");
-        let gitignore_content = "target/\n**/*.log\n.DS_Store";
+        let gitignore_content = "target/\n**/*.log\n.DS_Store\n.upm/";
         fs::write(gitignore_path, gitignore_content).expect("Failed to create .gitignore");
         println!("Created .gitignore");
     }
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "rust", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_scala_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -486,7 +464,7 @@ fn create_scala_project(
             .expect("Failed to create MainTest.scala");
         writeln!(
             test_scala,
-            "import org.scalatest.funsuite.AnyFunSuite\n\nclass MainTest extends AnyFunSuite {{\n  test(\'Hello World Test\') {{\n    assert(true)\n  }}\n}}"
+            "import org.scalatest.funsuite.AnyFunSuite\n\nclass MainTest extends AnyFunSuite {{\n  test(\"Hello World Test\") {{\n    assert(true)\n  }}\n}}"
         ).expect("Failed to write to MainTest.scala");
     }
 
@@ -498,16 +476,17 @@ fn create_scala_project(
     ).expect("Failed to write to build.sbt");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "scala", license, readme, tests, docs, docker);
 
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_r_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -529,11 +508,12 @@ fn create_r_project(
     );
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "r", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
-fn create_perl_project(project_name: &str, git: bool, ignore: bool, license: bool, readme: bool, tests: bool, docs: bool, docker: bool) {
+#[allow(clippy::too_many_arguments)]
+fn create_perl_project(project_name: &str, git: bool, ignore: bool, license: Option<&str>, readme: bool, tests: bool, docs: bool, docker: bool) {
     println!("Initializing Perl project...");
     let root_path = Path::new(project_name);
     if root_path.exists() {
@@ -548,15 +528,16 @@ fn create_perl_project(project_name: &str, git: bool, ignore: bool, license: boo
     writeln!(main_pl, "#!/usr/bin/perl\n\nprint 'Hello, World!';").expect("Failed to write to main.pl");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "perl", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_go_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -580,8 +561,8 @@ fn create_go_project(
     ).expect("Failed to write to main.go");
 
     let output = Command::new("go")
-        .args(&["mod", "init", project_name])
-        .current_dir(&root_path)
+        .args(["mod", "init", project_name])
+        .current_dir(root_path)
         .output()
         .expect("Failed to initialize Go module");
 
@@ -594,15 +575,16 @@ fn create_go_project(
     }
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "go", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_swift_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -612,7 +594,7 @@ fn create_swift_project(
     let root_path = Path::new(project_name);
 
     // Create the project directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(&root_path) {
+    if let Err(e) = fs::create_dir_all(root_path) {
         eprintln!("Failed to create project directory: {:?}", e);
         return;
     }
@@ -620,8 +602,8 @@ fn create_swift_project(
     // Initialize the Swift package
     if
         let Err(e) = Command::new("swift")
-            .args(&["package", "init", "--type", "executable"])
-            .current_dir(&root_path)
+            .args(["package", "init", "--type", "executable"])
+            .current_dir(root_path)
             .status()
     {
         eprintln!("Failed to create Swift project with Swift Package Manager: {:?}", e);
@@ -648,7 +630,7 @@ fn create_swift_project(
     if git && ignore {
         let gitignore_path = root_path.join(".gitignore");
         let gitignore_content =
-            ".build/\n*.xcodeproj\n*.xcworkspace\n*.xcuserstate\n*.swiftpm/xcode\n";
+            ".build/\n*.xcodeproj\n*.xcworkspace\n*.xcuserstate\n*.swiftpm/xcode\n.upm/\n";
         if let Err(e) = fs::write(&gitignore_path, gitignore_content) {
             eprintln!("Failed to create .gitignore: {:?}", e);
             return;
@@ -656,16 +638,17 @@ fn create_swift_project(
         println!("Created .gitignore");
     }
 
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "swift", license, readme, tests, docs, docker);
 
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_html_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -691,15 +674,17 @@ fn create_html_project(
     ).expect("Failed to write to index.html");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "html", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+// create-react-app initializes git and writes its own .gitignore, so `git` and `ignore` go unused.
+#[allow(clippy::too_many_arguments, unused_variables)]
 fn create_react_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -708,19 +693,20 @@ fn create_react_project(
     println!("Initializing React project...");
     let root_path = Path::new(project_name);
     Command::new("C:\\Program Files\\nodejs\\npx.cmd")
-        .args(&["create-react-app", project_name])
+        .args(["create-react-app", project_name])
         .status()
         .expect("Failed to create React project");
 
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "react", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_java_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -745,15 +731,16 @@ fn create_java_project(
     ).expect("Failed to write to Main.java");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "java", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_javascript_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -774,8 +761,8 @@ fn create_javascript_project(
     writeln!(main_js, "console.log('Hello, World!');").expect("Failed to write to main.js");
 
     let output = Command::new("C:\\Program Files\\nodejs\\npm.cmd")
-        .args(&["init", "-y"])
-        .current_dir(&root_path)
+        .args(["init", "-y"])
+        .current_dir(root_path)
         .output()
         .expect("Failed to initialize npm project");
 
@@ -788,15 +775,16 @@ fn create_javascript_project(
     }
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "javascript", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_dart_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -819,8 +807,8 @@ fn create_dart_project(
     );
 
     let output = Command::new("dart")
-        .args(&["create", "."])
-        .current_dir(&root_path)
+        .args(["create", "."])
+        .current_dir(root_path)
         .output()
         .expect("Failed to initialize Dart project");
 
@@ -833,15 +821,16 @@ fn create_dart_project(
     }
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "dart", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_typescript_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -862,8 +851,8 @@ fn create_typescript_project(
     writeln!(main_ts, "console.log('Hello, TypeScript!');").expect("Failed to write to main.ts");
 
     let output = Command::new("C:\\Program Files\\nodejs\\npm.cmd")
-        .args(&["init", "-y"])
-        .current_dir(&root_path)
+        .args(["init", "-y"])
+        .current_dir(root_path)
         .output()
         .expect("Failed to initialize npm project");
 
@@ -877,8 +866,8 @@ fn create_typescript_project(
 
     // Install TypeScript and create tsconfig.json
     let output = Command::new("C:\\Program Files\\nodejs\\npm.cmd")
-        .args(&["install", "typescript", "--save-dev"])
-        .current_dir(&root_path)
+        .args(["install", "typescript", "--save-dev"])
+        .current_dir(root_path)
         .output()
         .expect("Failed to install TypeScript");
 
@@ -894,19 +883,20 @@ fn create_typescript_project(
     let mut tsconfig = fs::File::create(&tsconfig_path).expect("Failed to create tsconfig.json");
     writeln!(
         tsconfig,
-        r#"{{"compilerOptions": {{"target": "es6", "module": "commonjs"}}}}"#
+        r#"{{"compilerOptions": {{"target": "es6", "module": "commonjs"}}, "include": ["src"]}}"#
     ).expect("Failed to write to tsconfig.json");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "typescript", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_ruby_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -926,15 +916,16 @@ fn create_ruby_project(
     writeln!(main_rb, "puts 'Hello, World!'").expect("Failed to write to main.rb");
 
     initialize_git(root_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "ruby", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_cs_project(
     project_name: &str,
     git: bool,
     ignore: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -943,22 +934,23 @@ fn create_cs_project(
     println!("Initializing C# project...");
     let root_path = Path::new(project_name);
     Command::new("dotnet")
-        .args(&["new", "console", "-n", project_name])
+        .args(["new", "console", "-n", project_name])
         .status()
         .expect("Failed to create C# project with dotnet CLI");
 
     let project_path = Path::new(project_name);
     initialize_git(project_path, git, ignore);
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "cs", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_python_project(
     project_name: &str,
     git: bool,
     ignore: bool,
     venv: bool,
-    license: bool,
+    license: Option<&str>,
     readme: bool,
     tests: bool,
     docs: bool,
@@ -987,7 +979,7 @@ fn create_python_project(
 
     if git {
         Command::new("git")
-            .args(&["init", project_name])
+            .args(["init", project_name])
             .status()
             .expect("Failed to initialize git repository");
         println!("Initialized empty Git repository in {}/.git/", project_name);
@@ -995,7 +987,7 @@ fn create_python_project(
 
     if ignore {
         let gitignore_path = Path::new(project_name).join(".gitignore");
-        let gitignore_content = if ignore { "venv/\n__pycache__/\n*.pyc" } else { "" }; // Customize as needed
+        let gitignore_content = if ignore { "venv/\n__pycache__/\n*.pyc\n.upm/" } else { "" }; // Customize as needed
         fs::write(gitignore_path, gitignore_content).expect("Failed to create .gitignore");
         println!("Created .gitignore");
     }
@@ -1003,13 +995,13 @@ fn create_python_project(
         println!("CREATING PYTHON VENV");
         create_virtual_env(project_name);
     }
-    initialize_documents(root_path, license, readme, tests, docs, docker);
+    initialize_documents(root_path, "python", license, readme, tests, docs, docker);
     println!("Project {} created successfully.", project_name);
 }
 
 fn create_virtual_env(project_path: &str) {
     Command::new("python3")
-        .args(&["-m", "venv", "venv"])
+        .args(["-m", "venv", "venv"])
         .current_dir(project_path)
         .status()
         .expect("Failed to create virtual environment");
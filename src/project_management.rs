@@ -1,8 +1,12 @@
-use std::process::{Command, Stdio};
+use std::process::{self, Command, Stdio};
 use std::path::Path;
-use crate::project_database::{load_projects_db, save_projects_db};
-use crate::config::read_config_from;
-use crate::util::{clean_path, get_install_path};
+use regex::Regex;
+use crate::build::ensure_built;
+use crate::child::run_child;
+use crate::project_database::{load_projects_db, save_project, save_projects_db, ProjectInfo};
+use crate::watch::watch_and_restart;
+use crate::secrets::project_env;
+use crate::util::{clean_path, get_install_path, node_tool};
 use std::env;
 use std::fs::{self, Permissions};
 #[cfg(unix)]
@@ -33,7 +37,6 @@ pub fn delete_project(project: &str) {
 
 pub fn open_project(project: &str) {
     let db = load_projects_db();
-    let config = read_config_from();
 
     if let Some(project_info) = db.projects.get(project) {
         let project_path = &project_info.project_path;
@@ -56,7 +59,7 @@ pub fn open_project(project: &str) {
         cd $1"
             };
         
-            if let Err(err) = fs::write(&script_path, script_content) {
+            if let Err(err) = fs::write(script_path, script_content) {
                 eprintln!("Failed to create script {:?}: {}", script_path, err);
                 return;
             }
@@ -74,7 +77,7 @@ pub fn open_project(project: &str) {
         let status = if cfg!(windows) {
             Command::new("cmd")
                 .arg("/C")
-                .arg(&script_path)
+                .arg(script_path)
                 .arg(project_path)
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit())
@@ -103,7 +106,7 @@ pub fn open_project(project: &str) {
     }
 }
 
-pub fn run_project() {
+pub fn run_project(args: &[String], watch: bool) {
     let current_dir = env::current_dir().unwrap();
     let current_dir_str = clean_path(&current_dir);
     
@@ -114,176 +117,216 @@ pub fn run_project() {
     });
 
     if let Some((_project_name, info)) = project_info {
-        // Construct the path to the project's main file
-        let script_path = Path::new(&info.project_path).join(&info.project_main);
-        let script_path_str = script_path.to_str().unwrap();
+        if watch {
+            watch_and_restart(Path::new(&info.project_path), || project_command(info, args).ok());
+            return;
+        }
 
-        match info.project_language.as_str() {
-            "python" => {
-                if let Err(e) = Command::new("python3").arg(script_path_str).status() {
-                    eprintln!("Failed to execute project: {}", e);
-                }
-            },
-            "rust" => {
-                if let Err(e) = Command::new("cargo").arg("run").current_dir(&info.project_path).status() {
-                    eprintln!("Failed to execute project: {}", e);
-                }
+        let mut command = match project_command(info, args) {
+            Ok(command) => command,
+            Err(0) => return,
+            Err(code) => process::exit(code),
+        };
+        match run_child(&mut command) {
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                eprintln!("Failed to execute project: {}", e);
+                process::exit(1);
             },
-            "cpp" | "c++" => {
-                let compile_status = if cfg!(target_os = "windows") {
-                    Command::new("g++").args([&info.project_main, "-o", "a.exe"]).status()
-                } else {
-                    Command::new("g++").args([&info.project_main, "-o", "a.out"]).status()
-                };
+        }
+    } else {
+        println!("Current directory is not a recognized UPM project.");
+    }
+}
 
-                if let Ok(status) = compile_status {
-                    if status.success() {
-                        let run_status = if cfg!(target_os = "windows") {
-                            Command::new("./a.exe").status()
-                        } else {
-                            Command::new("./a.out").status()
-                        };
-                        
-                        if let Err(e) = run_status {
-                            eprintln!("Failed to run compiled program: {}", e);
-                        }
-                    } else {
-                        eprintln!("Compilation failed");
-                    }
-                } else {
-                    eprintln!("Failed to compile.");
-                }
-            },
-            "swift" => {
-                if let Err(e) = Command::new("swift").args(&["run"]).status() {
-                    eprintln!("Failed to execute Swift project: {}", e);
-                }
-            },
-            "c"=> {
-                let compile_status = if cfg!(target_os = "windows") {
-                    Command::new("gcc").args([&info.project_main, "-o", "a.exe"]).status()
-                } else {
-                    Command::new("gcc").args([&info.project_main, "-o", "a.out"]).status()
-                };
+/// The command that runs the project's main entry point, building compiled projects first.
+/// `Err` carries the exit code for projects that have nothing to run (0) or failed to build.
+pub fn project_command(info: &ProjectInfo, args: &[String]) -> Result<Command, i32> {
+    let project_path = Path::new(&info.project_path);
+    // Construct the path to the project's main file
+    let script_path = project_path.join(&info.project_main);
+    let script_path_str = script_path.to_str().unwrap();
 
-                if let Ok(status) = compile_status {
-                    if status.success() {
-                        let run_status = if cfg!(target_os = "windows") {
-                            Command::new("./a.exe").status()
-                        } else {
-                            Command::new("./a.out").status()
-                        };
-                        
-                        if let Err(e) = run_status {
-                            eprintln!("Failed to run compiled program: {}", e);
-                        }
-                    } else {
-                        eprintln!("Compilation failed");
-                    }
-                } else {
-                    eprintln!("Failed to compile.");
-                }
-            },
-            "java" => {
-                let compile_status = Command::new("javac").arg(&info.project_main).status();
-                if let Ok(status) = compile_status {
-                    if status.success() {
-                        let class_path = Path::new(&info.project_path).join("src");
-                        let class_path_str = class_path.to_str().unwrap();
-                        let class_name = "Main";
-                        if let Err(e) = Command::new("java").arg("-cp").arg(class_path_str).arg(class_name).status() {
-                            eprintln!("Failed to run Java program: {}", e);
-                        }
-                    } else {
-                        eprintln!("Compilation failed");
-                    }
-                } else {
-                    eprintln!("Failed to compile.");
-                }
-            },
-            "js" | "javascript" => {
-                if let Err(e) = Command::new("node").arg(script_path_str).status() {
-                    eprintln!("Failed to execute JavaScript project: {}", e);
-                }
-            },
-            "ts" | "typescript" => {
-                if let Err(e) = Command::new("C:\\Program Files\\nodejs\\npx.cmd").args(&["tsc"]).status() {
-                    eprintln!("Failed to compile TypeScript: {}", e);
-                    return;
-                }
-                if let Err(e) = Command::new("node").arg(script_path_str).status() {
-                    eprintln!("Failed to execute TypeScript project: {}", e);
-                }
+    let working_dir = match &info.working_dir {
+        Some(dir) => project_path.join(dir),
+        None => project_path.to_path_buf(),
+    };
+
+    // Tools that take program arguments inside their own command line (sbt) consume them here.
+    let mut args = args;
+    let mut command = match info.project_language.to_lowercase().as_str() {
+        "python" => {
+            let mut command = Command::new("python3");
+            command.arg(script_path_str);
+            command
+        },
+        "rust" | "rs" => {
+            let mut command = Command::new("cargo");
+            command.arg("run").arg("--");
+            command
+        },
+        "c" | "cpp" | "c++" | "java" | "ts" | "typescript" | "go" => {
+            // Compiled projects run the recorded debug artifact, rebuilding only when sources changed.
+            let Some(record) = ensure_built(info) else {
+                return Err(1);
+            };
+            let Some((program, program_args)) = record.run_command.split_first() else {
+                eprintln!("No run command recorded for the last build.");
+                return Err(1);
+            };
+            let mut command = Command::new(program);
+            command.args(program_args);
+            command
+        },
+        "swift" => {
+            // The package root holds Package.swift even when the program runs from a configured working directory.
+            let mut command = Command::new("swift");
+            command.arg("run").arg("--package-path").arg(project_path);
+            if let Some(product) = swift_product(project_path, &info.project_main)? {
+                command.arg(product);
             }
-            "go" => {
-                if let Err(e) = Command::new("go").args(&["run", script_path_str]).status() {
-                    eprintln!("Failed to execute Go project: {}", e);
-                }
+            command
+        },
+        "js" | "javascript" => {
+            let mut command = Command::new("node");
+            command.arg(script_path_str);
+            command
+        },
+        "cs" | "c#" => {
+            let mut command = Command::new("dotnet");
+            command.arg("run").arg("--");
+            command
+        },
+        "react" => {
+            let mut command = Command::new(node_tool("npm"));
+            command.arg("start").arg("--");
+            command
+        },
+        "ruby" => {
+            let mut command = Command::new("ruby");
+            command.arg(script_path_str);
+            command
+        },
+        "dart" => {
+            let mut command = Command::new("dart");
+            command.arg("run").arg(script_path_str);
+            command
+        },
+        "shell" => {
+            if cfg!(windows) {
+                println!("Shell scripts are not supported on Windows.");
+                return Err(0);
             }
-            "cs" | "c#" => {
-                if let Err(e) = Command::new("dotnet").arg("run").current_dir(&info.project_path).status() {
-                    eprintln!("Failed to execute C# project: {}", e);
-                }
-            },
-            "react" => {
-                if let Err(e) = Command::new("C:\\Program Files\\nodejs\\npm.cmd").arg("start").current_dir(&info.project_path).status() {
-                    eprintln!("Failed to start React app: {}", e);
-                }
-            },
-            "ruby" => {
-                if let Err(e) = Command::new("ruby").arg(script_path_str).status() {
-                    eprintln!("Failed to execute Ruby script: {}", e);
-                }
-            },
-            "dart" => {
-                if let Err(e) = Command::new("dart").arg("run").arg(script_path_str).status() {
-                    eprintln!("Failed to execute Ruby script: {}", e);
+            let mut command = Command::new("sh");
+            command.arg(script_path_str);
+            command
+        },
+        "r" => {
+            let mut command = Command::new("Rscript");
+            command.arg(script_path_str);
+            // R reads the project's .Rprofile, which activates renv, from the working directory. From a subdirectory
+            // point R at it explicitly, or at renv's activate script when there is no .Rprofile; activate.R finds the
+            // project through RENV_PROJECT.
+            if working_dir != project_path {
+                let profiles = [project_path.join(".Rprofile"), project_path.join("renv").join("activate.R")];
+                if let Some(profile) = profiles.iter().find(|profile| profile.exists()) {
+                    command.env("RENV_PROJECT", project_path).env("R_PROFILE_USER", profile);
                 }
-            },
-            "shell" => {
-                #[cfg(unix)]
-                if let Err(e) = Command::new("sh").arg(script_path_str).status() {
-                    eprintln!("Failed to execute shell script: {}", e);
+            }
+            command
+        },
+        "scala" => {
+            if project_path.join("build.sbt").exists() {
+                // sbt reads program arguments from the same command as `run`.
+                let mut run = String::from("run");
+                for arg in args {
+                    run.push(' ');
+                    run.push_str(&sbt_quote(arg));
                 }
+                args = &[];
+                let mut command = Command::new(if cfg!(windows) { "sbt.bat" } else { "sbt" });
+                command.arg("--batch").arg(run);
+                command
+            } else {
+                let mut command = Command::new("scala-cli");
+                command.arg("run").arg(project_path).arg("--");
+                command
+            }
+        },
+        "perl" => {
+            let mut command = Command::new("perl");
+            command.arg(script_path_str);
+            command
+        },
+        "html" => {
+            // Typically, HTML files are opened in a web browser. This example uses the `xdg-open` command on Unix-like systems or `start` on Windows.
+            if cfg!(target_os = "windows") {
+                let mut command = Command::new("cmd");
+                command.args(["/c", "start", script_path_str]);
+                command
+            } else if cfg!(target_os = "macos") {
+                let mut command = Command::new("open");
+                command.arg(script_path_str);
+                command
+            }
+            else {
+                let mut command = Command::new("xdg-open");
+                command.arg(script_path_str);
+                command
+            }
+        },
+        
+        
+        _ => {
+            eprintln!("Unsupported project language.");
+            return Err(1);
+        },
+    };
 
-                #[cfg(windows)]
-                println!("Shell scripts are not supported on Windows.");
-            },
-            "r" => {
-                println!("Open the R script in RStudio or R interpreter.");
-            },
+    command.args(args).current_dir(working_dir).envs(project_env(project_path));
+    Ok(command)
 
-            "scala" => {
-                println!("Open the Scala script in SBT.");
-            },
-            "perl" => {
-                if let Err(e) = Command::new("perl").arg(script_path_str).status() {
-                    eprintln!("Failed to execute Perl script: {}", e);
-                }
-            },
-            "html" => {
-                // Typically, HTML files are opened in a web browser. This example uses the `xdg-open` command on Unix-like systems or `start` on Windows.
-                if cfg!(target_os = "windows") {
-                    if let Err(e) = Command::new("cmd").args(&["/c", "start", script_path_str]).status() {
-                        eprintln!("Failed to open HTML file: {}", e);
-                    }
-                } else if cfg!(target_os = "macos") {
-                    if let Err(e) = Command::new("open").arg(script_path_str).status() {
-                        eprintln!("Failed to open HTML file: {}", e);
-                    }
-                }
-                else {
-                    if let Err(e) = Command::new("xdg-open").arg(script_path_str).status() {
-                        eprintln!("Failed to open HTML file: {}", e);
-                    }
-                }
-            },
-            
-            
-            _ => eprintln!("Unsupported project language."),
-        }
+}
+
+/// Picks the executable product to run: the one the main file lives in, the only one, or the one named like the project.
+/// `Ok(None)` lets `swift run` choose when Package.swift declares no executables it can see.
+fn swift_product(project_path: &Path, project_main: &str) -> Result<Option<String>, i32> {
+    let manifest = fs::read_to_string(project_path.join("Package.swift")).unwrap_or_default();
+    let pattern = Regex::new(r#"\.executable(?:Target)?\s*\(\s*name:\s*"([^"]+)""#).unwrap();
+    let mut products: Vec<String> = pattern.captures_iter(&manifest).map(|captures| captures[1].to_string()).collect();
+    products.sort();
+    products.dedup();
+
+    let main_target = Path::new(project_main)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .skip_while(|component| component != "Sources")
+        .nth(1)
+        .filter(|target| !target.ends_with(".swift"));
+    if let Some(target) = main_target.filter(|target| products.contains(target)) {
+        return Ok(Some(target));
+    }
+    let project_name = project_path.file_name().unwrap().to_string_lossy().to_string();
+    match products.as_slice() {
+        [] => Ok(None),
+        [only] => Ok(Some(only.clone())),
+        _ if products.contains(&project_name) => Ok(Some(project_name)),
+        _ => {
+            eprintln!(
+                "Package.swift defines several executables ({}). Point the main file at one with 'upman config main Sources/<product>/main.swift'.",
+                products.join(", ")
+            );
+            Err(1)
+        },
+    }
+}
+
+// sbt splits its command line on spaces, so arguments with spaces or quotes are quoted.
+fn sbt_quote(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
     } else {
-        println!("Current directory is not a recognized UPM project.");
+        arg.to_string()
     }
 }
 
@@ -297,11 +340,57 @@ pub fn set_main_path(main_path: &str) {
     });
 
     match project {
-        Some((_, project_info)) => {
+        Some((project_name, project_info)) => {
             project_info.project_main = main_path.to_string();
-            save_projects_db(&db);
+            let project_name = project_name.clone();
+            save_project(&db, &project_name);
             println!("Project main path updated to '{}'", main_path);
         },
         None => println!("No project found in the current directory."),
     }
-}
\ No newline at end of file
+}
+pub fn set_native_build(setting: &str, value: &str) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let mut db = load_projects_db();
+
+    let project = db.projects.iter_mut().find(|(_key, value)| {
+        current_dir_str.starts_with(&value.project_path)
+    });
+
+    let Some((project_name, project_info)) = project else {
+        println!("No project found in the current directory.");
+        return;
+    };
+    let project_name = project_name.clone();
+    let values: Vec<String> = value.split_whitespace().map(String::from).collect();
+    match setting {
+        "includes" => project_info.native_build.include_dirs = values,
+        "cflags" => project_info.native_build.flags = values,
+        "libs" => project_info.native_build.libs = values,
+        _ => return,
+    }
+    save_project(&db, &project_name);
+    println!("Project {} updated to '{}'", setting, value);
+}
+
+pub fn set_working_dir(working_dir: &str) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let mut db = load_projects_db();
+
+    let project = db.projects.iter_mut().find(|(_key, value)| {
+        current_dir_str.starts_with(&value.project_path)
+    });
+
+    match project {
+        Some((project_name, project_info)) => {
+            // "." resets to the project root, which is where runs start by default.
+            project_info.working_dir = if working_dir == "." { None } else { Some(working_dir.to_string()) };
+            let project_name = project_name.clone();
+            save_project(&db, &project_name);
+            println!("Project working directory updated to '{}'", working_dir);
+        },
+        None => println!("No project found in the current directory."),
+    }
+}
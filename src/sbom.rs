@@ -0,0 +1,616 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use regex::Regex;
+use serde_json::{json, Value};
+use crate::dependencies::{normalize_python_name, package_key};
+use crate::license::{parse_license_expression, project_license};
+use crate::license_audit::dependency_licenses;
+use crate::load_projects_db;
+use crate::project_database::find_project_for_dir;
+use crate::util::{clean_path, site_packages_dirs, utc_timestamp};
+
+/// A package the project ships with, as recorded by its lockfiles.
+pub struct Component {
+    pub name: String,
+    pub version: String,
+    /// One of the `Dependency` ecosystems: cargo, npm, pypi, go, rubygems or nuget.
+    pub ecosystem: &'static str,
+    /// (algorithm, hex digest), with CycloneDX algorithm names such as `SHA-256`.
+    pub hashes: Vec<(String, String)>,
+    pub license: Option<String>,
+}
+
+impl Component {
+    pub fn purl(&self) -> String {
+        let name = match self.ecosystem {
+            // The purl spec lowercases PyPI names and spells them with dashes.
+            "pypi" => normalize_python_name(&self.name),
+            _ => self.name.clone(),
+        };
+        let name = name.split('/').map(purl_encode).collect::<Vec<_>>().join("/");
+        let purl_type = match self.ecosystem {
+            "go" => "golang",
+            "rubygems" => "gem",
+            ecosystem => ecosystem,
+        };
+        format!("pkg:{}/{}@{}", purl_type, name, purl_encode(&self.version))
+    }
+}
+
+pub fn sbom_command(format: &str) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((project_name, info)) = find_project_for_dir(&db, &current_dir_str) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
+
+    let components = project_components(project_path);
+    if components.is_empty() {
+        eprintln!("No lockfile found. Install or lock the project's dependencies first.");
+    }
+    let version = project_version(project_path);
+    let license = project_license(project_path);
+    let document = if format == "spdx" {
+        spdx_document(project_name, version.as_deref(), &license, &components)
+    } else {
+        cyclonedx_document(project_name, version.as_deref(), &license, &components)
+    };
+    println!("{}", serde_json::to_string_pretty(&document).expect("Failed to serialize the SBOM"));
+}
+
+/// Every locked package of every ecosystem in the project, with licenses from the metadata on disk where
+/// the lockfile doesn't carry them.
+pub fn project_components(project_path: &Path) -> Vec<Component> {
+    let mut components = Vec::new();
+    components.extend(cargo_components(project_path));
+    components.extend(npm_components(project_path));
+    components.extend(python_components(project_path));
+    components.extend(go_components(project_path));
+    components.extend(gem_components(project_path));
+    components.extend(nuget_components(project_path));
+
+    let licenses: HashMap<(String, String, String), String> = dependency_licenses(project_path)
+        .into_iter()
+        .filter_map(|dependency| {
+            let key = package_key(&dependency.ecosystem, &dependency.name);
+            Some(((dependency.ecosystem, key, dependency.version), dependency.license?))
+        })
+        .collect();
+    for component in components.iter_mut().filter(|component| component.license.is_none()) {
+        let key = (component.ecosystem.to_string(), package_key(component.ecosystem, &component.name), component.version.clone());
+        component.license = licenses.get(&key).cloned();
+    }
+
+    let mut seen = HashSet::new();
+    components.retain(|component| seen.insert((component.ecosystem, package_key(component.ecosystem, &component.name), component.version.clone())));
+    components.sort_by(|a, b| a.ecosystem.cmp(b.ecosystem).then_with(|| a.name.cmp(&b.name)).then_with(|| a.version.cmp(&b.version)));
+    components
+}
+
+fn component(name: &str, version: &str, ecosystem: &'static str, hashes: Vec<(String, String)>, license: Option<String>) -> Component {
+    Component { name: name.to_string(), version: version.to_string(), ecosystem, hashes, license }
+}
+
+/// The project's own version from whichever manifest declares one.
+fn project_version(project_path: &Path) -> Option<String> {
+    let read = |file: &str| fs::read_to_string(project_path.join(file)).ok();
+    let toml_version = |file: &str, tables: &[&[&str]]| {
+        let manifest = read(file)?.parse::<toml::Table>().ok()?;
+        tables.iter().find_map(|keys| {
+            let mut value = manifest.get(keys[0])?;
+            for key in &keys[1..] {
+                value = value.get(key)?;
+            }
+            value.as_str().map(String::from)
+        })
+    };
+    let xml_version = |contents: String, element: &str| {
+        let pattern = Regex::new(&format!(r"<{}>\s*([^<]+?)\s*</{}>", element, element)).unwrap();
+        pattern.captures(&contents).map(|captures| captures[1].to_string())
+    };
+
+    toml_version("Cargo.toml", &[&["package", "version"]])
+        .or_else(|| read("package.json").and_then(|contents| serde_json::from_str::<Value>(&contents).ok()?.get("version")?.as_str().map(String::from)))
+        .or_else(|| toml_version("pyproject.toml", &[&["project", "version"], &["tool", "poetry", "version"]]))
+        .or_else(|| {
+            // The project's own <version> comes before any dependency's, after the parent block.
+            let pom = read("pom.xml")?;
+            let pom = Regex::new(r"(?s)<parent>.*?</parent>").unwrap().replace(&pom, "").to_string();
+            let pom = pom.split("<dependencies>").next().unwrap_or_default().to_string();
+            xml_version(pom, "version")
+        })
+        .or_else(|| {
+            let csproj = fs::read_dir(project_path).ok()?.flatten().find(|entry| entry.path().extension().is_some_and(|extension| extension == "csproj"))?;
+            xml_version(fs::read_to_string(csproj.path()).ok()?, "Version")
+        })
+        .or_else(|| read("pubspec.yaml")?.lines().find_map(|line| line.strip_prefix("version:").map(|version| version.trim().to_string())))
+}
+
+fn cargo_components(project_path: &Path) -> Vec<Component> {
+    let Some(lock) = fs::read_to_string(project_path.join("Cargo.lock")).ok().and_then(|contents| contents.parse::<toml::Table>().ok()) else {
+        return Vec::new();
+    };
+    let packages = lock.get("package").and_then(|packages| packages.as_array()).cloned().unwrap_or_default();
+    packages
+        .iter()
+        // Packages without a source are the project itself and its workspace members.
+        .filter(|package| package.get("source").is_some())
+        .filter_map(|package| {
+            let hashes = package.get("checksum").and_then(|checksum| checksum.as_str()).map(|checksum| ("SHA-256".to_string(), checksum.to_string()));
+            Some(component(package.get("name")?.as_str()?, package.get("version")?.as_str()?, "cargo", hashes.into_iter().collect(), None))
+        })
+        .collect()
+}
+
+fn npm_components(project_path: &Path) -> Vec<Component> {
+    let Some(lock) = fs::read_to_string(project_path.join("package-lock.json")).ok().and_then(|contents| serde_json::from_str::<Value>(&contents).ok()) else {
+        return Vec::new();
+    };
+    let mut components = Vec::new();
+    if let Some(packages) = lock.get("packages").and_then(Value::as_object) {
+        for (path, package) in packages {
+            // "" is the project itself; links point at workspace packages.
+            if path.is_empty() || package.get("link").and_then(Value::as_bool) == Some(true) {
+                continue;
+            }
+            let name = package.get("name").and_then(Value::as_str).unwrap_or_else(|| path.rsplit("node_modules/").next().unwrap_or(path));
+            let Some(version) = package.get("version").and_then(Value::as_str) else {
+                continue;
+            };
+            let license = package.get("license").and_then(Value::as_str).map(String::from);
+            components.push(component(name, version, "npm", integrity_hashes(package), license));
+        }
+    } else if let Some(dependencies) = lock.get("dependencies").and_then(Value::as_object) {
+        // Lockfile v1 nests dependencies that couldn't be hoisted.
+        let mut pending: Vec<(&String, &Value)> = dependencies.iter().collect();
+        while let Some((name, package)) = pending.pop() {
+            if let Some(version) = package.get("version").and_then(Value::as_str) {
+                components.push(component(name, version, "npm", integrity_hashes(package), None));
+            }
+            if let Some(nested) = package.get("dependencies").and_then(Value::as_object) {
+                pending.extend(nested.iter());
+            }
+        }
+    }
+    components
+}
+
+// Subresource integrity, "sha512-<base64>".
+fn integrity_hashes(package: &Value) -> Vec<(String, String)> {
+    let Some(integrity) = package.get("integrity").and_then(Value::as_str) else {
+        return Vec::new();
+    };
+    integrity
+        .split_whitespace()
+        .filter_map(|entry| {
+            let (algorithm, digest) = entry.split_once('-')?;
+            let algorithm = match algorithm {
+                "sha512" => "SHA-512",
+                "sha384" => "SHA-384",
+                "sha256" => "SHA-256",
+                "sha1" => "SHA-1",
+                _ => return None,
+            };
+            Some((algorithm.to_string(), base64_to_hex(digest)?))
+        })
+        .collect()
+}
+
+fn python_components(project_path: &Path) -> Vec<Component> {
+    for lockfile in ["poetry.lock", "uv.lock"] {
+        let Some(lock) = fs::read_to_string(project_path.join(lockfile)).ok().and_then(|contents| contents.parse::<toml::Table>().ok()) else {
+            continue;
+        };
+        let packages = lock.get("package").and_then(|packages| packages.as_array()).cloned().unwrap_or_default();
+        return packages
+            .iter()
+            .filter(|package| {
+                // uv lists the project itself as an editable or virtual package.
+                let source = package.get("source");
+                !source.is_some_and(|source| source.get("editable").is_some() || source.get("virtual").is_some())
+            })
+            .filter_map(|package| {
+                let name = package.get("name")?.as_str()?;
+                let version = package.get("version")?.as_str()?;
+                // poetry records every file's hash; uv records the sdist and each wheel. The sdist comes first when there is one.
+                let hash = package
+                    .get("sdist")
+                    .and_then(|sdist| sdist.get("hash"))
+                    .or_else(|| package.get("files")?.as_array()?.iter().find(|file| file.get("file").and_then(|file| file.as_str()).is_some_and(|file| file.ends_with(".tar.gz")))?.get("hash"))
+                    .or_else(|| package.get("files")?.as_array()?.first()?.get("hash"))
+                    .or_else(|| package.get("wheels")?.as_array()?.first()?.get("hash"))
+                    .and_then(|hash| hash.as_str())
+                    .and_then(python_hash);
+                Some(component(name, version, "pypi", hash.into_iter().collect(), None))
+            })
+            .collect();
+    }
+
+    if let Some(lock) = fs::read_to_string(project_path.join("Pipfile.lock")).ok().and_then(|contents| serde_json::from_str::<Value>(&contents).ok()) {
+        let mut components = Vec::new();
+        for section in ["default", "develop"] {
+            for (name, package) in lock.get(section).and_then(Value::as_object).into_iter().flatten() {
+                let Some(version) = package.get("version").and_then(Value::as_str) else {
+                    continue;
+                };
+                let hash = package.get("hashes").and_then(Value::as_array).and_then(|hashes| hashes.first()).and_then(Value::as_str).and_then(python_hash);
+                components.push(component(name, version.trim_start_matches("=="), "pypi", hash.into_iter().collect(), None));
+            }
+        }
+        return components;
+    }
+
+    // Plain pip projects have no lockfile; what's installed in the venv is what ships.
+    let metadata_field = |metadata: &str, key: &str| metadata.lines().take_while(|line| !line.is_empty()).find_map(|line| line.strip_prefix(key).map(|value| value.trim().to_string()));
+    site_packages_dirs(project_path)
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".dist-info"))
+        .filter_map(|entry| {
+            let metadata = fs::read_to_string(entry.path().join("METADATA")).ok()?;
+            let name = metadata_field(&metadata, "Name:")?;
+            let version = metadata_field(&metadata, "Version:")?;
+            Some(component(&name, &version, "pypi", Vec::new(), None))
+        })
+        .collect()
+}
+
+// "sha256:<hex>"
+fn python_hash(hash: &str) -> Option<(String, String)> {
+    let (algorithm, digest) = hash.split_once(':')?;
+    let algorithm = match algorithm {
+        "sha256" => "SHA-256",
+        "sha384" => "SHA-384",
+        "sha512" => "SHA-512",
+        _ => return None,
+    };
+    Some((algorithm.to_string(), digest.to_string()))
+}
+
+// go.sum's h1: hashes cover a module's file tree rather than an archive, so no hash is recorded.
+fn go_components(project_path: &Path) -> Vec<Component> {
+    let Ok(go_mod) = fs::read_to_string(project_path.join("go.mod")) else {
+        return Vec::new();
+    };
+    let mut components = Vec::new();
+    let mut in_block = false;
+    for line in go_mod.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if line.starts_with("require (") {
+            in_block = true;
+            continue;
+        }
+        if in_block && line == ")" {
+            in_block = false;
+            continue;
+        }
+        let requirement = if in_block { Some(line) } else { line.strip_prefix("require ") };
+        if let Some((module, version)) = requirement.and_then(|requirement| requirement.split_once(char::is_whitespace)) {
+            components.push(component(module, version.trim(), "go", Vec::new(), None));
+        }
+    }
+    components
+}
+
+fn gem_components(project_path: &Path) -> Vec<Component> {
+    let Ok(lock) = fs::read_to_string(project_path.join("Gemfile.lock")) else {
+        return Vec::new();
+    };
+    // Bundler 2.5+ writes a CHECKSUMS section: "  name (version) sha256=<hex>".
+    let checksum = Regex::new(r"^  (\S+) \(([^)]+)\) sha256=([0-9a-f]+)").unwrap();
+    let checksums: HashMap<(String, String), String> = lock
+        .lines()
+        .filter_map(|line| checksum.captures(line))
+        .map(|captures| ((captures[1].to_string(), captures[2].to_string()), captures[3].to_string()))
+        .collect();
+
+    let spec = Regex::new(r"^    (\S+) \(([^)]+)\)$").unwrap();
+    let mut components = Vec::new();
+    let mut in_gem_section = false;
+    for line in lock.lines() {
+        if !line.starts_with(' ') {
+            in_gem_section = line == "GEM";
+            continue;
+        }
+        let Some(captures) = spec.captures(line).filter(|_| in_gem_section) else {
+            continue;
+        };
+        let key = (captures[1].to_string(), captures[2].to_string());
+        let hashes = checksums.get(&key).map(|hash| ("SHA-256".to_string(), hash.clone())).into_iter().collect();
+        components.push(component(&key.0, &key.1, "rubygems", hashes, None));
+    }
+    components
+}
+
+fn nuget_components(project_path: &Path) -> Vec<Component> {
+    let Some(assets) = fs::read_to_string(project_path.join("obj").join("project.assets.json")).ok().and_then(|contents| serde_json::from_str::<Value>(&contents).ok()) else {
+        return Vec::new();
+    };
+    assets
+        .get("libraries")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(_, library)| library.get("type").and_then(Value::as_str) == Some("package"))
+        .filter_map(|(key, library)| {
+            let (name, version) = key.split_once('/')?;
+            let hash = library.get("sha512").and_then(Value::as_str).and_then(base64_to_hex).map(|hash| ("SHA-512".to_string(), hash));
+            Some(component(name, version, "nuget", hash.into_iter().collect(), None))
+        })
+        .collect()
+}
+
+fn base64_to_hex(encoded: &str) -> Option<String> {
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    let mut hex = String::new();
+    for c in encoded.trim_end_matches('=').chars() {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return None,
+        };
+        bits = (bits << 6) | value;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            hex.push_str(&format!("{:02x}", (bits >> bit_count) & 0xff));
+        }
+    }
+    Some(hex)
+}
+
+// Percent-encodes everything but unreserved characters, as purl requires for names and versions.
+fn purl_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+// A valid SPDX expression in canonical form, or None for free-text licenses.
+fn spdx_expression(license: &str) -> Option<String> {
+    parse_license_expression(license).ok()
+}
+
+fn cyclonedx_licenses(license: Option<&str>) -> Option<Value> {
+    let license = license?;
+    let entry = match spdx_expression(license) {
+        Some(expression) if expression.contains(' ') => json!({ "expression": expression }),
+        Some(id) => json!({ "license": { "id": id } }),
+        None => json!({ "license": { "name": license } }),
+    };
+    Some(json!([entry]))
+}
+
+/// A CycloneDX 1.5 JSON BOM with the project as the metadata component.
+fn cyclonedx_document(project_name: &str, version: Option<&str>, license: &str, components: &[Component]) -> Value {
+    let mut root = json!({ "type": "application", "bom-ref": project_name, "name": project_name });
+    if let Some(version) = version {
+        root["version"] = json!(version);
+    }
+    if let Some(licenses) = cyclonedx_licenses(Some(license)) {
+        root["licenses"] = licenses;
+    }
+
+    let components: Vec<Value> = components
+        .iter()
+        .map(|component| {
+            let purl = component.purl();
+            let mut entry = json!({ "type": "library", "bom-ref": purl, "name": component.name, "version": component.version, "purl": purl });
+            if !component.hashes.is_empty() {
+                entry["hashes"] = component.hashes.iter().map(|(algorithm, content)| json!({ "alg": algorithm, "content": content })).collect();
+            }
+            if let Some(licenses) = cyclonedx_licenses(component.license.as_deref()) {
+                entry["licenses"] = licenses;
+            }
+            entry
+        })
+        .collect();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": utc_timestamp(),
+            "tools": { "components": [{ "type": "application", "name": "upman", "version": env!("CARGO_PKG_VERSION") }] },
+            "component": root,
+        },
+        "components": components,
+    })
+}
+
+/// An SPDX 2.3 JSON document describing the project package, which depends on every component.
+fn spdx_document(project_name: &str, version: Option<&str>, license: &str, components: &[Component]) -> Value {
+    let spdx_id = |index: usize, name: &str| {
+        let sanitized: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' }).collect();
+        format!("SPDXRef-Package-{}-{}", index, sanitized)
+    };
+    let root_id = "SPDXRef-Package-root".to_string();
+    let mut root = json!({
+        "name": project_name,
+        "SPDXID": root_id,
+        "downloadLocation": "NOASSERTION",
+        "filesAnalyzed": false,
+        "licenseConcluded": "NOASSERTION",
+        "licenseDeclared": spdx_expression(license).unwrap_or("NOASSERTION".to_string()),
+        "copyrightText": "NOASSERTION",
+    });
+    if let Some(version) = version {
+        root["versionInfo"] = json!(version);
+    }
+
+    let mut packages = vec![root];
+    let mut relationships = vec![json!({ "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": root_id })];
+    for (index, component) in components.iter().enumerate() {
+        let id = spdx_id(index + 1, &component.name);
+        let checksums: Vec<Value> = component
+            .hashes
+            .iter()
+            .map(|(algorithm, content)| json!({ "algorithm": algorithm.replace('-', ""), "checksumValue": content }))
+            .collect();
+        let mut package = json!({
+            "name": component.name,
+            "SPDXID": id,
+            "versionInfo": component.version,
+            "downloadLocation": "NOASSERTION",
+            "filesAnalyzed": false,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": component.license.as_deref().and_then(spdx_expression).unwrap_or("NOASSERTION".to_string()),
+            "copyrightText": "NOASSERTION",
+            "externalRefs": [{ "referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": component.purl() }],
+        });
+        if !checksums.is_empty() {
+            package["checksums"] = json!(checksums);
+        }
+        packages.push(package);
+        relationships.push(json!({ "spdxElementId": root_id, "relationshipType": "DEPENDS_ON", "relatedSpdxElement": id }));
+    }
+
+    // The namespace has to be unique per document, so it includes the creation time.
+    let created = utc_timestamp();
+    let mut hasher = DefaultHasher::new();
+    (project_name, version, &created, components.len()).hash(&mut hasher);
+    let namespace = format!("https://spdx.org/spdxdocs/{}-{}-{:016x}", purl_encode(project_name), version.unwrap_or("unversioned"), hasher.finish());
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": project_name,
+        "documentNamespace": namespace,
+        "creationInfo": { "created": created, "creators": [format!("Tool: upman-{}", env!("CARGO_PKG_VERSION"))] },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> &'static Path {
+        Path::new("tests/fixtures/sbom/project")
+    }
+
+    #[test]
+    fn purls() {
+        let purl = |name: &str, version: &str, ecosystem: &'static str| component(name, version, ecosystem, Vec::new(), None).purl();
+        assert_eq!(purl("Typing_Extensions", "4.12.2", "pypi"), "pkg:pypi/typing-extensions@4.12.2");
+        assert_eq!(purl("@types/node", "20.11.5", "npm"), "pkg:npm/%40types/node@20.11.5");
+        assert_eq!(purl("upman-fixture-git", "0.1.0+build.7", "cargo"), "pkg:cargo/upman-fixture-git@0.1.0%2Bbuild.7");
+        assert_eq!(purl("github.com/spf13/cobra", "v1.8.0", "go"), "pkg:golang/github.com/spf13/cobra@v1.8.0");
+        assert_eq!(purl("rails", "7.1.3", "rubygems"), "pkg:gem/rails@7.1.3");
+        assert_eq!(purl("Newtonsoft.Json", "13.0.3", "nuget"), "pkg:nuget/Newtonsoft.Json@13.0.3");
+    }
+
+    #[test]
+    fn hash_decoding() {
+        assert_eq!(python_hash("sha256:e20d1c91"), Some(("SHA-256".to_string(), "e20d1c91".to_string())));
+        assert_eq!(python_hash("sha512:00ff"), Some(("SHA-512".to_string(), "00ff".to_string())));
+        assert_eq!(python_hash("md5:0123"), None);
+        assert_eq!(python_hash("e20d1c91"), None);
+
+        assert_eq!(base64_to_hex("aGVsbG8=").as_deref(), Some("68656c6c6f"));
+        assert_eq!(base64_to_hex("AAEC/w==").as_deref(), Some("000102ff"));
+        // The URL-safe alphabet decodes the same way.
+        assert_eq!(base64_to_hex("AAEC_w").as_deref(), Some("000102ff"));
+        assert_eq!(base64_to_hex("not base64!"), None);
+    }
+
+    #[test]
+    fn lockfile_components() {
+        let components = project_components(fixture());
+        let summary: Vec<(&str, &str, String)> = components.iter().map(|component| (component.ecosystem, component.version.as_str(), component.purl())).collect();
+        assert_eq!(
+            summary,
+            [
+                ("cargo", "1.0.203", "pkg:cargo/serde@1.0.203".to_string()),
+                ("cargo", "0.1.0+build.7", "pkg:cargo/upman-fixture-git@0.1.0%2Bbuild.7".to_string()),
+                ("npm", "20.11.5", "pkg:npm/%40types/node@20.11.5".to_string()),
+                ("npm", "1.3.0", "pkg:npm/left-pad@1.3.0".to_string()),
+                ("pypi", "2.32.3", "pkg:pypi/requests@2.32.3".to_string()),
+                ("pypi", "4.12.2", "pkg:pypi/typing-extensions@4.12.2".to_string()),
+            ]
+        );
+        let hashes = |name: &str| components.iter().find(|component| component.name == name).unwrap().hashes.clone();
+        let hash = |algorithm: &str, digest: &str| vec![(algorithm.to_string(), digest.to_string())];
+        assert_eq!(hashes("serde"), hash("SHA-256", "8b917c4b6163bc82ef4aff025c6f5f4d54205232c4595f39b7b43008256a6cb7"));
+        assert_eq!(hashes("upman-fixture-git"), []);
+        assert_eq!(hashes("left-pad"), hash("SHA-1", "16c385a6cbd7c6ad06cd6a7195aafae4932fcf3d"));
+        assert_eq!(
+            hashes("@types/node"),
+            hash("SHA-512", "7125bd7eae2f0c567e03926887d2c880f10f0cc0ec6805a0bc989ebef4339bf849ac6951a4976620371c0e9d9acd7d3c1ef5d3eb54755deba2769d3c57818de2")
+        );
+        // The sdist's hash is preferred over a wheel's; unsupported algorithms are dropped.
+        assert_eq!(hashes("requests"), hash("SHA-256", "e20d1c91a492b9cf7fcde098571085f752a3cdd02ac9d7e5e135d640ef9fd095"));
+        assert_eq!(hashes("typing-extensions"), []);
+        assert_eq!(project_version(fixture()).as_deref(), Some("0.3.1"));
+    }
+
+    #[test]
+    fn cyclonedx_shape() {
+        let components = project_components(fixture());
+        let document = cyclonedx_document("demo", Some("0.3.1"), "MIT OR Apache-2.0", &components);
+        assert_eq!(document["bomFormat"], "CycloneDX");
+        assert_eq!(document["metadata"]["component"]["version"], "0.3.1");
+        assert_eq!(document["metadata"]["component"]["licenses"], json!([{ "expression": "MIT OR Apache-2.0" }]));
+
+        let entries = document["components"].as_array().unwrap();
+        assert_eq!(entries.len(), components.len());
+        let node = entries.iter().find(|entry| entry["name"] == "@types/node").unwrap();
+        assert_eq!(node["purl"], "pkg:npm/%40types/node@20.11.5");
+        assert_eq!(node["bom-ref"], node["purl"]);
+        assert_eq!(node["hashes"][0]["alg"], "SHA-512");
+        assert_eq!(node["licenses"], json!([{ "license": { "id": "MIT" } }]));
+        let left_pad = entries.iter().find(|entry| entry["name"] == "left-pad").unwrap();
+        assert_eq!(left_pad["licenses"], json!([{ "license": { "name": "SEE LICENSE IN LICENSE.txt" } }]));
+        let git = entries.iter().find(|entry| entry["name"] == "upman-fixture-git").unwrap();
+        assert!(git.get("hashes").is_none());
+    }
+
+    #[test]
+    fn spdx_shape() {
+        let components = project_components(fixture());
+        let document = spdx_document("demo", Some("0.3.1"), "MIT", &components);
+        let packages = document["packages"].as_array().unwrap();
+        assert_eq!(packages.len(), components.len() + 1);
+        assert_eq!(packages[0]["SPDXID"], "SPDXRef-Package-root");
+        assert_eq!(packages[0]["licenseDeclared"], "MIT");
+
+        let relationships = document["relationships"].as_array().unwrap();
+        assert_eq!(relationships[0]["relationshipType"], "DESCRIBES");
+        let depends_on: Vec<&Value> = relationships.iter().filter(|relationship| relationship["relationshipType"] == "DEPENDS_ON").collect();
+        assert_eq!(depends_on.len(), components.len());
+        for (relationship, package) in depends_on.iter().zip(&packages[1..]) {
+            assert_eq!(relationship["spdxElementId"], "SPDXRef-Package-root");
+            assert_eq!(relationship["relatedSpdxElement"], package["SPDXID"]);
+        }
+
+        let requests = packages.iter().find(|package| package["name"] == "requests").unwrap();
+        assert_eq!(requests["SPDXID"], "SPDXRef-Package-5-requests");
+        assert_eq!(requests["externalRefs"][0]["referenceLocator"], "pkg:pypi/requests@2.32.3");
+        assert_eq!(requests["checksums"], json!([{ "algorithm": "SHA256", "checksumValue": "e20d1c91a492b9cf7fcde098571085f752a3cdd02ac9d7e5e135d640ef9fd095" }]));
+        let node = packages.iter().find(|package| package["name"] == "@types/node").unwrap();
+        assert_eq!(node["SPDXID"], "SPDXRef-Package-3--types-node");
+        assert_eq!(node["licenseDeclared"], "MIT");
+        // Free-text licenses aren't SPDX expressions.
+        let left_pad = packages.iter().find(|package| package["name"] == "left-pad").unwrap();
+        assert_eq!(left_pad["licenseDeclared"], "NOASSERTION");
+    }
+}
@@ -1,6 +1,5 @@
 use std::fs;
 use std::path::Path;
-use std::env;
 use crate::util::get_install_path;
 
 pub fn delete_script(script_name: &str) {
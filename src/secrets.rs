@@ -1,93 +1,138 @@
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::fs::{self, OpenOptions};
+use std::collections::HashMap;
 use std::env;
+use std::path::Path;
 
+use crate::dotenv::{is_variable_name, EnvFile};
+use crate::env_encryption::{decrypt_env, encrypt_env, is_encrypted, lock_env, save_encrypted, unlock, unlock_env};
+use crate::manifest::load_manifest;
+use crate::team_secrets::{keygen, recipients_manager, remove_team_secret, set_team_secret, show_team_secret, team_env, team_secrets_path};
 use crate::util::clean_path;
-use crate::project_database::load_projects_db;
+use crate::project_database::{find_project_for_dir, load_projects_db};
 
-fn load_secrets(file_path: &std::path::Path) -> std::collections::HashMap<String, String> {
-    let mut secrets_map = std::collections::HashMap::new();
-
-    if let Ok(file) = fs::File::open(file_path) {
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if let Some((key, value)) = parse_env_line(&line) {
-                    secrets_map.insert(key.to_string(), value.to_string());
-                }
-            }
+/// Variables injected into commands upman runs for the project: the `[env]` table of upm.toml, overridden by the team's `secrets.enc`
+/// and then by the `.env` in the project root. `.env` values can reference the others and each other with `${VAR}`. An encrypted
+/// `.env.enc` is unlocked first, asking for the passphrase when no key is cached.
+pub fn project_env(project_path: &Path) -> HashMap<String, String> {
+    let mut variables: HashMap<String, String> = load_manifest(project_path).map(|manifest| manifest.env.into_iter().collect()).unwrap_or_default();
+    if team_secrets_path(project_path).exists() {
+        match team_env(project_path) {
+            Ok(team_variables) => variables.extend(team_variables),
+            Err(error) => eprintln!("Skipping secrets.enc: {}", error),
         }
     }
-
-    secrets_map
-}
-
-fn save_secrets(file_path: &std::path::Path, secrets_map: &std::collections::HashMap<String, String>) {
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(file_path)
-        .unwrap();
-
-    let mut writer = BufWriter::new(file);
-
-    for (key, value) in secrets_map {
-        writeln!(writer, "{}={}", key, value).expect("Failed to write to .env file");
-    }
+    let dotenv = if is_encrypted(project_path) {
+        match unlock(project_path, true) {
+            Ok((dotenv, _key)) => dotenv,
+            Err(error) => {
+                eprintln!("Skipping the project's secrets: {}", error);
+                return variables;
+            },
+        }
+    } else {
+        EnvFile::load(&project_path.join(".env"))
+    };
+    let resolved = dotenv.variables(&variables);
+    variables.extend(resolved);
+    variables
 }
 
-fn parse_env_line(line: &str) -> Option<(&str, &str)> {
-    let parts: Vec<&str> = line.splitn(2, '=').collect();
-    if parts.len() == 2 {
-        Some((parts[0].trim(), parts[1].trim()))
-    } else {
-        None
-    }
+/// `upm secrets recipients <add|remove|list>`, run from anywhere in the project.
+pub fn recipients_command(action: &str, name: Option<&str>, public_key: Option<&str>) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+    let Some((_project_name, info)) = find_project_for_dir(&db, &current_dir_str) else {
+        println!("This directory is not recognized as a UPM project.");
+        return;
+    };
+    recipients_manager(Path::new(&info.project_path), action, name, public_key);
 }
 
-pub fn secrets_manager(action: &str, secret: &str, secret_value: &str) {
+pub fn secrets_manager(action: &str, secret: Option<&str>, secret_value: &str, team: bool) {
     let current_dir = env::current_dir().unwrap();
     let current_dir_str = clean_path(&current_dir);
     let db = load_projects_db();
-    if db.projects.iter().any(|(_key, value)| current_dir_str.starts_with(&value.project_path)) {
-        if action == "save" || action == "add" {
-            let current_dir = env::current_dir().unwrap();
-            let env_file_path = current_dir.join(".env");
-            let mut secrets_map = load_secrets(&env_file_path);
-            secrets_map.insert(secret.to_string(), secret_value.to_string());
-            save_secrets(&env_file_path, &secrets_map);
-            println!("Secret added successfully.");
-        } else if action == "delete" || action == "remove" {
-            let current_dir = env::current_dir().unwrap();
-            let env_file_path = current_dir.join(".env");
+    let Some((_project_name, info)) = find_project_for_dir(&db, &current_dir_str) else {
+        println!("This directory is not recognized as a UPM project.");
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
 
-            let mut secrets_map = load_secrets(&env_file_path);
+    match action {
+        "keygen" => return keygen(),
+        "save" | "add" | "delete" | "remove" | "show" if team => {},
+        _ if team => {
+            println!("--team only applies to add, remove and show.");
+            return;
+        },
+        "encrypt" => return encrypt_env(project_path),
+        "decrypt" => return decrypt_env(project_path),
+        "unlock" => return unlock_env(project_path),
+        "lock" => return lock_env(project_path),
+        "save" | "add" | "delete" | "remove" | "show" => {},
+        _ => {
+            println!("Unsupported action '{}'.", action);
+            return;
+        },
+    }
+    let Some(secret) = secret else {
+        println!("Specify the secret to {}, e.g. 'upm secrets {} API_KEY'.", action, action);
+        return;
+    };
+    if matches!(action, "save" | "add") && !is_variable_name(secret) {
+        println!("'{}' is not a valid variable name. Use letters, digits and underscores, not starting with a digit.", secret);
+        return;
+    }
 
-            if secrets_map.remove(secret).is_some() {
-                save_secrets(&env_file_path, &secrets_map);
-                println!("Secret removed successfully.");
-            } else {
-                println!("Secret not found.");
-            }
-        } else if action == "show" {
-            let current_dir = env::current_dir().unwrap();
-            let env_file_path = current_dir.join(".env");
+    if team {
+        match action {
+            "save" | "add" => set_team_secret(project_path, secret, secret_value),
+            "delete" | "remove" => remove_team_secret(project_path, secret),
+            _ => show_team_secret(project_path, secret),
+        }
+        return;
+    }
 
-            let secrets_map = load_secrets(&env_file_path);
+    // Always the project root's .env, the one `upm run` and tasks load, even from a subdirectory.
+    // When the secrets are encrypted, .env.enc is edited in place instead.
+    let env_file_path = project_path.join(".env");
+    let (mut dotenv, key) = if is_encrypted(project_path) {
+        match unlock(project_path, true) {
+            Ok((dotenv, key)) => (dotenv, Some(key)),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            },
+        }
+    } else {
+        (EnvFile::load(&env_file_path), None)
+    };
+    let save = |dotenv: &EnvFile| {
+        let result = match &key {
+            Some(key) => save_encrypted(project_path, dotenv, key),
+            None => dotenv.save(&env_file_path),
+        };
+        if let Err(error) = result {
+            eprintln!("Failed to save the project's secrets: {}", error);
+            std::process::exit(1);
+        }
+    };
 
-            for (key, value) in secrets_map {
-                if key == secret {
-                    println!("{}={}", key, value);
-                    return;
-                }
-            }
+    if action == "save" || action == "add" {
+        dotenv.set(secret, secret_value);
+        save(&dotenv);
+        println!("Secret added successfully.");
+    } else if action == "delete" || action == "remove" {
+        if dotenv.remove(secret) {
+            save(&dotenv);
+            println!("Secret removed successfully.");
         } else {
-            println!("Unsupported action '{}'.", action);
+            println!("Secret not found.");
         }
     } else {
-        println!("This directory is not recognized as a UPM project.");
-        return;
+        match dotenv.get(secret) {
+            Some(value) => println!("{}={}", secret, value),
+            None => println!("Secret not found."),
+        }
     }
 }
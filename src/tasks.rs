@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::UNIX_EPOCH;
+
+use crate::child::{run_child, shell_command};
+use crate::project_database::{load_projects_db, save_project, TaskDef};
+use crate::secrets::project_env;
+use crate::util::{clean_path, project_state_dir, sha256_hex};
+use crate::watch::watch_and_restart;
+
+pub fn add_task(name: &str, task: TaskDef) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let mut db = load_projects_db();
+
+    let Some((project_name, project_info)) = db.projects.iter_mut().find(|(_key, value)| current_dir_str.starts_with(&value.project_path)) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+    let project_name = project_name.clone();
+
+    let mut tasks = project_info.tasks.clone();
+    tasks.insert(name.to_string(), task);
+    if let Err(err) = task_order(&tasks, name, true) {
+        eprintln!("{}", err);
+        return;
+    }
+    for dependency in &tasks[name].depends_on {
+        if !tasks.contains_key(dependency) {
+            println!("Warning: task '{}' depends on '{}', which is not defined yet.", name, dependency);
+        }
+    }
+
+    project_info.tasks = tasks;
+    save_project(&db, &project_name);
+    println!("Task '{}' saved.", name);
+}
+
+pub fn remove_task(name: &str) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let mut db = load_projects_db();
+
+    let Some((project_name, project_info)) = db.projects.iter_mut().find(|(_key, value)| current_dir_str.starts_with(&value.project_path)) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+    let project_name = project_name.clone();
+
+    if project_info.tasks.remove(name).is_none() {
+        println!("Task '{}' not found.", name);
+        return;
+    }
+    let dependents: Vec<&String> = project_info.tasks.iter().filter(|(_, task)| task.depends_on.iter().any(|dep| dep == name)).map(|(task_name, _)| task_name).collect();
+    if !dependents.is_empty() {
+        println!("Warning: {} still depend on '{}'.", dependents.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", "), name);
+    }
+    save_project(&db, &project_name);
+    println!("Task '{}' removed.", name);
+}
+
+pub fn list_tasks() {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((_, project_info)) = db.projects.iter().find(|(_key, value)| current_dir_str.starts_with(&value.project_path)) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+
+    if project_info.tasks.is_empty() {
+        println!("No tasks defined. Add one with 'upman task add <NAME> <COMMAND>'.");
+        return;
+    }
+    for (name, task) in &project_info.tasks {
+        println!("{}: {}", name, task.command);
+        if !task.depends_on.is_empty() {
+            println!("    depends on: {}", task.depends_on.join(", "));
+        }
+        if !task.inputs.is_empty() {
+            println!("    inputs: {}", task.inputs.join(", "));
+        }
+    }
+}
+
+/// Runs `name` after its dependencies, passing `args` to the named task only. Exits with the failing task's code.
+/// With `watch`, the named task is restarted (after re-running its dependencies) whenever project files change.
+pub fn run_task(name: &str, args: &[String], watch: bool) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((_, project_info)) = db.projects.iter().find(|(_key, value)| current_dir_str.starts_with(&value.project_path)) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+    let project_path = Path::new(&project_info.project_path);
+    let tasks = &project_info.tasks;
+
+    let order = match task_order(tasks, name, false) {
+        Ok(order) => order,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        },
+    };
+
+    if watch {
+        let (target, dependencies) = order.split_last().unwrap();
+        watch_and_restart(project_path, || {
+            let mut state = load_task_state(project_path);
+            for dependency in dependencies {
+                run_one(project_path, dependency, &tasks[dependency], &[], &mut state).ok()?;
+            }
+            println!("[{}] {}", target, tasks[target].command);
+            Some(task_command(project_path, &tasks[target], args))
+        });
+        return;
+    }
+
+    let mut state = load_task_state(project_path);
+    for task_name in &order {
+        let task_args = if task_name == name { args } else { &[] };
+        if let Err(code) = run_one(project_path, task_name, &tasks[task_name], task_args, &mut state) {
+            process::exit(code);
+        }
+    }
+}
+
+// Runs one task to completion unless its inputs are unchanged since it last succeeded.
+fn run_one(project_path: &Path, name: &str, task: &TaskDef, args: &[String], state: &mut HashMap<String, String>) -> Result<(), i32> {
+    let cacheable = !task.inputs.is_empty() && args.is_empty();
+    if cacheable && state.get(name) == Some(&task_fingerprint(project_path, task)) {
+        println!("[{}] inputs unchanged, skipping", name);
+        return Ok(());
+    }
+
+    println!("[{}] {}", name, task.command);
+    match run_child(&mut task_command(project_path, task, args)) {
+        Ok(0) => {
+            // Fingerprint after the run, so tasks that rewrite their own inputs (formatters) stay skipped.
+            if cacheable {
+                state.insert(name.to_string(), task_fingerprint(project_path, task));
+                save_task_state(project_path, state);
+            }
+            Ok(())
+        },
+        Ok(code) => {
+            eprintln!("Task '{}' failed with exit code {}.", name, code);
+            Err(code)
+        },
+        Err(err) => {
+            eprintln!("Failed to run task '{}': {}", name, err);
+            Err(1)
+        },
+    }
+}
+
+fn task_command(project_path: &Path, task: &TaskDef, args: &[String]) -> Command {
+    let mut command = shell_command(&task.command, args);
+    command
+        .current_dir(task.cwd.as_ref().map_or(project_path.to_path_buf(), |cwd| project_path.join(cwd)))
+        .envs(project_env(project_path))
+        .envs(&task.env);
+    command
+}
+
+/// Orders `target` after everything it depends on. Missing dependencies are allowed when `allow_missing` is set.
+fn task_order(tasks: &BTreeMap<String, TaskDef>, target: &str, allow_missing: bool) -> Result<Vec<String>, String> {
+    fn visit(
+        name: &str,
+        tasks: &BTreeMap<String, TaskDef>,
+        allow_missing: bool,
+        stack: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), String> {
+        if order.iter().any(|done| done == name) {
+            return Ok(());
+        }
+        if let Some(start) = stack.iter().position(|pending| pending == name) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(format!("Task dependency cycle: {}", cycle.join(" -> ")));
+        }
+        let Some(task) = tasks.get(name) else {
+            if allow_missing {
+                return Ok(());
+            }
+            return Err(match stack.last() {
+                Some(parent) => format!("Task '{}' depends on '{}', which is not defined.", parent, name),
+                None => format!("Task '{}' is not defined. Run 'upman task list' to see the project's tasks.", name),
+            });
+        };
+
+        stack.push(name.to_string());
+        for dependency in &task.depends_on {
+            visit(dependency, tasks, allow_missing, stack, order)?;
+        }
+        stack.pop();
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(target, tasks, allow_missing, &mut Vec::new(), &mut order)?;
+    Ok(order)
+}
+
+fn task_state_file(project_path: &Path) -> PathBuf {
+    project_state_dir(project_path).join("tasks.json")
+}
+
+fn load_task_state(project_path: &Path) -> HashMap<String, String> {
+    fs::read_to_string(task_state_file(project_path))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_task_state(project_path: &Path, state: &HashMap<String, String>) {
+    let contents = serde_json::to_string_pretty(state).expect("Failed to serialize task state");
+    if let Err(err) = fs::write(task_state_file(project_path), contents) {
+        eprintln!("Failed to record task state: {}", err);
+    }
+}
+
+fn collect_inputs(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.flatten() {
+                collect_inputs(&entry.path(), files);
+            }
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+}
+
+// Hashes the task definition with the size and modification time of every input file.
+fn task_fingerprint(project_path: &Path, task: &TaskDef) -> String {
+    let mut files = Vec::new();
+    for pattern in &task.inputs {
+        let pattern = project_path.join(pattern);
+        match glob::glob(&pattern.to_string_lossy()) {
+            Ok(paths) => paths.flatten().for_each(|path| collect_inputs(&path, &mut files)),
+            Err(err) => eprintln!("Invalid input pattern '{}': {}", pattern.display(), err),
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    // Serialized as JSON so the fields can't run together, and hashed with SHA-256 so the fingerprint stays the
+    // same across upman builds.
+    let files: Vec<(String, Option<u64>, Option<u64>)> = files
+        .iter()
+        .map(|file| {
+            let meta = fs::metadata(file).ok();
+            let modified = meta.as_ref().and_then(|meta| meta.modified().ok()).and_then(|time| time.duration_since(UNIX_EPOCH).ok()).map(|time| time.as_nanos() as u64);
+            (clean_path(file), meta.map(|meta| meta.len()), modified)
+        })
+        .collect();
+    let state = serde_json::to_vec(&(&task.command, &task.cwd, &task.env, files)).expect("Failed to serialize the task fingerprint");
+    sha256_hex(&state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(definitions: &[(&str, &[&str])]) -> BTreeMap<String, TaskDef> {
+        definitions
+            .iter()
+            .map(|(name, depends_on)| {
+                let task = TaskDef { command: format!("echo {}", name), depends_on: depends_on.iter().map(|dependency| dependency.to_string()).collect(), ..TaskDef::default() };
+                (name.to_string(), task)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diamond_runs_shared_dependency_once() {
+        let tasks = tasks(&[("build", &[]), ("lint", &["build"]), ("test", &["build"]), ("ci", &["lint", "test"])]);
+        assert_eq!(task_order(&tasks, "ci", false).unwrap(), ["build", "lint", "test", "ci"]);
+        assert_eq!(task_order(&tasks, "test", false).unwrap(), ["build", "test"]);
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let tasks = tasks(&[("a", &["b"]), ("b", &["a"]), ("self", &["self"])]);
+        assert_eq!(task_order(&tasks, "a", false).unwrap_err(), "Task dependency cycle: a -> b -> a");
+        assert_eq!(task_order(&tasks, "self", false).unwrap_err(), "Task dependency cycle: self -> self");
+    }
+
+    #[test]
+    fn unknown_tasks_and_dependencies() {
+        let tasks = tasks(&[("deploy", &["build", "package"]), ("build", &[])]);
+        assert_eq!(task_order(&tasks, "deploy", false).unwrap_err(), "Task 'deploy' depends on 'package', which is not defined.");
+        assert_eq!(task_order(&tasks, "deploy", true).unwrap(), ["build", "deploy"]);
+        assert!(task_order(&tasks, "release", false).unwrap_err().starts_with("Task 'release' is not defined."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn changed_input_invalidates_cached_success() {
+        let project = env::temp_dir().join(format!("upman-tasks-{}", process::id()));
+        let _ = fs::remove_dir_all(&project);
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src").join("main.c"), "int main(void) { return 0; }\n").unwrap();
+        let task = TaskDef { command: "echo ran >> runs.txt".to_string(), inputs: vec!["src/*".to_string()], ..TaskDef::default() };
+        let runs = || fs::read_to_string(project.join("runs.txt")).unwrap_or_default().lines().count();
+
+        let mut state = HashMap::new();
+        run_one(&project, "build", &task, &[], &mut state).unwrap();
+        run_one(&project, "build", &task, &[], &mut state).unwrap();
+        assert_eq!(runs(), 1);
+        // The recorded success survives a reload of the state file.
+        assert_eq!(load_task_state(&project), state);
+
+        fs::write(project.join("src").join("main.c"), "int main(void) { return 42; }\n").unwrap();
+        assert_ne!(state.get("build"), Some(&task_fingerprint(&project, &task)));
+        run_one(&project, "build", &task, &[], &mut state).unwrap();
+        assert_eq!(runs(), 2);
+
+        // Arguments bypass the cache.
+        run_one(&project, "build", &task, &["again".to_string()], &mut state).unwrap();
+        assert_eq!(runs(), 3);
+        fs::remove_dir_all(project).unwrap();
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+use crate::util::{user_config_dir, write_atomic, write_private_file};
+
+/// Points at an identity file other than the default one, e.g. in CI.
+pub const IDENTITY_VARIABLE: &str = "UPMAN_IDENTITY";
+const PUBLIC_PREFIX: &str = "upman-pk:";
+const SECRET_PREFIX: &str = "UPMAN-SK:";
+const WRAP_INFO: &[u8] = b"upman secrets.enc v1 key wrap";
+
+/// `secrets.enc`, meant to be committed. Every value has its own data key, wrapped for each recipient
+/// with X25519 and HKDF-SHA256; values and keys are sealed with XChaCha20-Poly1305.
+#[derive(Serialize, Deserialize)]
+struct TeamSecrets {
+    version: u32,
+    /// Recipient name to public key.
+    #[serde(default)]
+    recipients: BTreeMap<String, String>,
+    #[serde(default)]
+    secrets: BTreeMap<String, SealedValue>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SealedValue {
+    /// The ephemeral public key the data key was wrapped with.
+    ephemeral: String,
+    nonce: String,
+    /// The value, encrypted with the data key and bound to the secret's name.
+    value: String,
+    /// Recipient name to wrapped data key (nonce followed by ciphertext).
+    keys: BTreeMap<String, String>,
+}
+
+pub fn team_secrets_path(project_path: &Path) -> PathBuf {
+    project_path.join("secrets.enc")
+}
+
+fn identity_path() -> Option<PathBuf> {
+    match env::var_os(IDENTITY_VARIABLE) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(user_config_dir()?.join("identity.key")),
+    }
+}
+
+/// `upm secrets keygen`: creates this user's identity and prints the public key to share with the team.
+pub fn keygen() {
+    let Some(path) = identity_path() else {
+        fail("Failed to locate your config directory; set HOME or UPMAN_IDENTITY.");
+    };
+    if let Ok(identity) = load_identity() {
+        println!("An identity already exists at {}.", path.display());
+        println!("Public key: {}", encode_public(&PublicKey::from(&identity)));
+        return;
+    }
+    let identity = StaticSecret::random_from_rng(OsRng);
+    let contents = Zeroizing::new(format!("# upman identity, keep this file private\n{}{}\n", SECRET_PREFIX, BASE64.encode(identity.as_bytes())));
+    if let Err(error) = write_private_file(&path, contents.as_bytes()) {
+        fail(&format!("Failed to write {}: {}", path.display(), error));
+    }
+    let public = encode_public(&PublicKey::from(&identity));
+    println!("Wrote a new identity to {}.", path.display());
+    println!("Public key: {}", public);
+    println!("A teammate can now run 'upm secrets recipients add <YOUR_NAME> {}'.", public);
+}
+
+/// `upm secrets recipients add|remove|list`.
+pub fn recipients_manager(project_path: &Path, action: &str, name: Option<&str>, public_key: Option<&str>) {
+    let mut secrets = load(project_path).unwrap_or_else(|error| fail(&error));
+    match action {
+        "list" => {
+            if secrets.recipients.is_empty() {
+                println!("secrets.enc has no recipients. Add one with 'upm secrets recipients add <NAME> <PUBLIC_KEY>'.");
+                return;
+            }
+            let own_key = load_identity().ok().map(|identity| encode_public(&PublicKey::from(&identity)));
+            let width = secrets.recipients.keys().map(String::len).max().unwrap_or(0);
+            for (name, key) in &secrets.recipients {
+                let you = if own_key.as_deref() == Some(key.as_str()) { "  (you)" } else { "" };
+                println!("{:<width$}  {}{}", name, key, you);
+            }
+        },
+        "add" => {
+            let (Some(name), Some(public_key)) = (name, public_key) else {
+                println!("Usage: upm secrets recipients add <NAME> <PUBLIC_KEY>");
+                return;
+            };
+            if decode_public(public_key).is_none_or(|key| !StaticSecret::random_from_rng(OsRng).diffie_hellman(&key).was_contributory()) {
+                fail(&format!("'{}' is not a upman public key. Run 'upm secrets keygen' to get one.", public_key));
+            }
+            let values = open_all(&secrets).unwrap_or_else(|error| fail(&error));
+            let replaced = secrets.recipients.insert(name.to_string(), public_key.to_string()).is_some();
+            reseal_all(&mut secrets, &values).unwrap_or_else(|error| fail(&error));
+            save(project_path, &secrets);
+            let action = if replaced { "Updated" } else { "Added" };
+            println!("{} recipient '{}' and re-encrypted {} secret(s).", action, name, values.len());
+        },
+        "remove" => {
+            let Some(name) = name else {
+                println!("Usage: upm secrets recipients remove <NAME>");
+                return;
+            };
+            if !secrets.recipients.contains_key(name) {
+                println!("Recipient '{}' not found.", name);
+                return;
+            }
+            if secrets.recipients.len() == 1 && !secrets.secrets.is_empty() {
+                fail("Can't remove the last recipient: nobody could read the secrets anymore.");
+            }
+            let values = open_all(&secrets).unwrap_or_else(|error| fail(&error));
+            secrets.recipients.remove(name);
+            reseal_all(&mut secrets, &values).unwrap_or_else(|error| fail(&error));
+            save(project_path, &secrets);
+            println!("Removed recipient '{}' and re-encrypted {} secret(s) with new keys.", name, values.len());
+            if !values.is_empty() {
+                println!("They can still read the old values from version control history, so rotate any they had access to.");
+            }
+        },
+        _ => println!("Unsupported recipients action '{}'. Use add, remove or list.", action),
+    }
+}
+
+/// `upm secrets add --team`: encrypts `value` to every recipient. Only public keys are needed.
+pub fn set_team_secret(project_path: &Path, name: &str, value: &str) {
+    let mut secrets = load(project_path).unwrap_or_else(|error| fail(&error));
+    if secrets.recipients.is_empty() {
+        fail("secrets.enc has no recipients yet. Add yourself first with 'upm secrets recipients add <NAME> <PUBLIC_KEY>'.");
+    }
+    let sealed = seal(name, value, &secrets.recipients).unwrap_or_else(|error| fail(&error));
+    secrets.secrets.insert(name.to_string(), sealed);
+    save(project_path, &secrets);
+    println!("Secret added to secrets.enc for {} recipient(s).", secrets.recipients.len());
+}
+
+pub fn remove_team_secret(project_path: &Path, name: &str) {
+    let mut secrets = load(project_path).unwrap_or_else(|error| fail(&error));
+    if secrets.secrets.remove(name).is_some() {
+        save(project_path, &secrets);
+        println!("Secret removed successfully.");
+    } else {
+        println!("Secret not found.");
+    }
+}
+
+pub fn show_team_secret(project_path: &Path, name: &str) {
+    let secrets = load(project_path).unwrap_or_else(|error| fail(&error));
+    let Some(sealed) = secrets.secrets.get(name) else {
+        println!("Secret not found.");
+        return;
+    };
+    let identity = load_identity().unwrap_or_else(|error| fail(&error));
+    match open(name, sealed, &secrets, &identity) {
+        Ok(value) => println!("{}={}", name, *value),
+        Err(error) => fail(&error),
+    }
+}
+
+/// The decrypted values of the project's `secrets.enc`, for `upm run` and tasks.
+pub fn team_env(project_path: &Path) -> Result<Vec<(String, String)>, String> {
+    let secrets = load(project_path)?;
+    if secrets.secrets.is_empty() {
+        return Ok(Vec::new());
+    }
+    let values = open_all(&secrets)?;
+    Ok(values.into_iter().map(|(name, value)| (name, value.to_string())).collect())
+}
+
+fn load(project_path: &Path) -> Result<TeamSecrets, String> {
+    let Ok(contents) = fs::read_to_string(team_secrets_path(project_path)) else {
+        return Ok(TeamSecrets { version: 1, recipients: BTreeMap::new(), secrets: BTreeMap::new() });
+    };
+    let secrets: TeamSecrets = toml::from_str(&contents).map_err(|error| format!("Failed to parse secrets.enc: {}", error))?;
+    if secrets.version != 1 {
+        return Err(format!("secrets.enc uses an unsupported format (version {}).", secrets.version));
+    }
+    Ok(secrets)
+}
+
+fn save(project_path: &Path, secrets: &TeamSecrets) {
+    let header = "# Team secrets encrypted by upman to each recipient. Safe to commit; edit with `upm secrets add --team`.\n";
+    let contents = format!("{}{}", header, toml::to_string(secrets).expect("Failed to serialize secrets.enc"));
+    if let Err(error) = write_atomic(&team_secrets_path(project_path), contents.as_bytes()) {
+        fail(&format!("Failed to write secrets.enc: {}", error));
+    }
+}
+
+// Decrypts every value, which requires being one of the current recipients.
+fn open_all(secrets: &TeamSecrets) -> Result<Vec<(String, Zeroizing<String>)>, String> {
+    if secrets.secrets.is_empty() {
+        return Ok(Vec::new());
+    }
+    let identity = load_identity()?;
+    secrets.secrets.iter().map(|(name, sealed)| Ok((name.clone(), open(name, sealed, secrets, &identity)?))).collect()
+}
+
+// Encrypts every value again with fresh data keys, so only the current recipients can read them.
+fn reseal_all(secrets: &mut TeamSecrets, values: &[(String, Zeroizing<String>)]) -> Result<(), String> {
+    for (name, value) in values {
+        let sealed = seal(name, value, &secrets.recipients)?;
+        secrets.secrets.insert(name.clone(), sealed);
+    }
+    Ok(())
+}
+
+// Fails rather than leaving out a recipient whose key can't be used, so nobody loses access unnoticed.
+fn seal(name: &str, value: &str, recipients: &BTreeMap<String, String>) -> Result<SealedValue, String> {
+    let mut data_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(data_key.as_mut());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let value = XChaCha20Poly1305::new(data_key.as_ref().into())
+        .encrypt(&nonce, Payload { msg: value.as_bytes(), aad: name.as_bytes() })
+        .expect("Failed to encrypt the secret");
+
+    let ephemeral = StaticSecret::random_from_rng(OsRng);
+    let ephemeral_public = PublicKey::from(&ephemeral);
+    let keys = recipients
+        .iter()
+        .map(|(recipient, public_key)| {
+            let unusable = || format!("The public key of recipient '{}' in secrets.enc is not usable; replace it with 'upm secrets recipients add {} <PUBLIC_KEY>'.", recipient, recipient);
+            let recipient_public = decode_public(public_key).ok_or_else(unusable)?;
+            let wrap_key = wrap_key(&ephemeral, &recipient_public, &ephemeral_public, &recipient_public).ok_or_else(unusable)?;
+            let wrap_nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+            let wrapped = XChaCha20Poly1305::new(wrap_key.as_ref().into()).encrypt(&wrap_nonce, data_key.as_ref()).map_err(|_| unusable())?;
+            Ok((recipient.clone(), BASE64.encode([wrap_nonce.as_slice(), &wrapped].concat())))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(SealedValue { ephemeral: BASE64.encode(ephemeral_public.as_bytes()), nonce: BASE64.encode(nonce), value: BASE64.encode(value), keys })
+}
+
+fn open(name: &str, sealed: &SealedValue, secrets: &TeamSecrets, identity: &StaticSecret) -> Result<Zeroizing<String>, String> {
+    let own_key = encode_public(&PublicKey::from(identity));
+    let not_recipient = || format!("Your identity is not a recipient of '{}' in secrets.enc. Ask a teammate to run 'upm secrets recipients add <YOUR_NAME> {}'.", name, own_key);
+    let recipient = secrets.recipients.iter().find(|(_, key)| **key == own_key).map(|(recipient, _)| recipient).ok_or_else(not_recipient)?;
+    let wrapped = sealed.keys.get(recipient).ok_or_else(not_recipient)?;
+
+    let corrupted = || format!("The value of '{}' in secrets.enc is corrupted or was tampered with.", name);
+    let ephemeral: [u8; 32] = BASE64.decode(&sealed.ephemeral).ok().and_then(|bytes| bytes.try_into().ok()).ok_or_else(corrupted)?;
+    let ephemeral = PublicKey::from(ephemeral);
+    let wrapped = BASE64.decode(wrapped).map_err(|_| corrupted())?;
+    if wrapped.len() < 24 {
+        return Err(corrupted());
+    }
+    let (wrap_nonce, wrapped) = wrapped.split_at(24);
+    let wrap_key = wrap_key(identity, &ephemeral, &ephemeral, &PublicKey::from(identity)).ok_or_else(corrupted)?;
+    let data_key = Zeroizing::new(XChaCha20Poly1305::new(wrap_key.as_ref().into()).decrypt(XNonce::from_slice(wrap_nonce), wrapped).map_err(|_| corrupted())?);
+    if data_key.len() != 32 {
+        return Err(corrupted());
+    }
+
+    let nonce = BASE64.decode(&sealed.nonce).ok().filter(|nonce| nonce.len() == 24).ok_or_else(corrupted)?;
+    let value = BASE64.decode(&sealed.value).map_err(|_| corrupted())?;
+    let plaintext = XChaCha20Poly1305::new(data_key.as_slice().into())
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &value, aad: name.as_bytes() })
+        .map_err(|_| corrupted())?;
+    String::from_utf8(plaintext).map(Zeroizing::new).map_err(|_| corrupted())
+}
+
+// HKDF-SHA256 over the X25519 shared secret of `secret` and `peer`, salted with the ephemeral and recipient
+// public keys so a wrapped key is tied to the exchange it was made for.
+fn wrap_key(secret: &StaticSecret, peer: &PublicKey, ephemeral: &PublicKey, recipient: &PublicKey) -> Option<Zeroizing<[u8; 32]>> {
+    let shared = secret.diffie_hellman(peer);
+    if !shared.was_contributory() {
+        return None;
+    }
+    let salt = [ephemeral.as_bytes().as_slice(), recipient.as_bytes()].concat();
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes()).expand(WRAP_INFO, key.as_mut()).ok()?;
+    Some(key)
+}
+
+fn load_identity() -> Result<StaticSecret, String> {
+    let path = identity_path().ok_or("Failed to locate your config directory; set HOME or UPMAN_IDENTITY.")?;
+    let contents = Zeroizing::new(fs::read_to_string(&path).map_err(|_| format!("No identity found at {}. Create one with 'upm secrets keygen'.", path.display()))?);
+    let encoded = contents.lines().find_map(|line| line.trim().strip_prefix(SECRET_PREFIX)).ok_or_else(|| format!("{} is not a upman identity file.", path.display()))?;
+    let bytes = Zeroizing::new(BASE64.decode(encoded).map_err(|_| format!("{} is not a upman identity file.", path.display()))?);
+    let bytes: [u8; 32] = bytes.as_slice().try_into().map_err(|_| format!("{} is not a upman identity file.", path.display()))?;
+    Ok(StaticSecret::from(bytes))
+}
+
+fn encode_public(key: &PublicKey) -> String {
+    format!("{}{}", PUBLIC_PREFIX, BASE64.encode(key.as_bytes()))
+}
+
+fn decode_public(key: &str) -> Option<PublicKey> {
+    let bytes: [u8; 32] = BASE64.decode(key.trim().strip_prefix(PUBLIC_PREFIX)?).ok()?.try_into().ok()?;
+    Some(PublicKey::from(bytes))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team(names: &[&str]) -> (TeamSecrets, Vec<StaticSecret>) {
+        let identities: Vec<StaticSecret> = names.iter().map(|_| StaticSecret::random_from_rng(OsRng)).collect();
+        let recipients = names.iter().zip(&identities).map(|(name, identity)| (name.to_string(), encode_public(&PublicKey::from(identity)))).collect();
+        (TeamSecrets { version: 1, recipients, secrets: BTreeMap::new() }, identities)
+    }
+
+    fn add(secrets: &mut TeamSecrets, name: &str, value: &str) {
+        let sealed = seal(name, value, &secrets.recipients).unwrap();
+        secrets.secrets.insert(name.to_string(), sealed);
+    }
+
+    fn open_as(secrets: &TeamSecrets, name: &str, identity: &StaticSecret) -> Result<String, String> {
+        open(name, &secrets.secrets[name], secrets, identity).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn every_recipient_can_open() {
+        let (mut secrets, identities) = team(&["alice", "bob"]);
+        add(&mut secrets, "API_TOKEN", "s3cr3t");
+        assert_eq!(secrets.secrets["API_TOKEN"].keys.len(), 2);
+        for identity in &identities {
+            assert_eq!(open_as(&secrets, "API_TOKEN", identity).as_deref(), Ok("s3cr3t"));
+        }
+        // The file round-trips through TOML.
+        let reloaded: TeamSecrets = toml::from_str(&toml::to_string(&secrets).unwrap()).unwrap();
+        assert_eq!(open_as(&reloaded, "API_TOKEN", &identities[1]).as_deref(), Ok("s3cr3t"));
+    }
+
+    #[test]
+    fn non_recipient_cannot_open() {
+        let (mut secrets, _) = team(&["alice"]);
+        add(&mut secrets, "API_TOKEN", "s3cr3t");
+        let outsider = StaticSecret::random_from_rng(OsRng);
+        let error = open_as(&secrets, "API_TOKEN", &outsider).unwrap_err();
+        assert!(error.starts_with("Your identity is not a recipient of 'API_TOKEN'"));
+
+        // Claiming a recipient's slot with one's own public key doesn't help either.
+        secrets.recipients.insert("alice".to_string(), encode_public(&PublicKey::from(&outsider)));
+        assert!(open_as(&secrets, "API_TOKEN", &outsider).unwrap_err().contains("corrupted or was tampered with"));
+    }
+
+    #[test]
+    fn value_is_bound_to_its_name() {
+        let (mut secrets, identities) = team(&["alice"]);
+        add(&mut secrets, "API_TOKEN", "s3cr3t");
+        add(&mut secrets, "DB_PASSWORD", "hunter2");
+        // Swapping two sealed values changes the AAD each is opened with.
+        let token = secrets.secrets.remove("API_TOKEN").unwrap();
+        let password = secrets.secrets.remove("DB_PASSWORD").unwrap();
+        secrets.secrets.insert("API_TOKEN".to_string(), password);
+        secrets.secrets.insert("DB_PASSWORD".to_string(), token);
+        assert_eq!(open_as(&secrets, "API_TOKEN", &identities[0]), Err("The value of 'API_TOKEN' in secrets.enc is corrupted or was tampered with.".to_string()));
+        assert!(open_as(&secrets, "DB_PASSWORD", &identities[0]).is_err());
+    }
+
+    #[test]
+    fn removed_recipient_loses_access_after_reseal() {
+        let (mut secrets, identities) = team(&["alice", "bob"]);
+        add(&mut secrets, "API_TOKEN", "s3cr3t");
+        let old = secrets.secrets["API_TOKEN"].value.clone();
+
+        secrets.recipients.remove("bob");
+        reseal_all(&mut secrets, &[("API_TOKEN".to_string(), Zeroizing::new("s3cr3t".to_string()))]).unwrap();
+        let sealed = &secrets.secrets["API_TOKEN"];
+        assert_ne!(sealed.value, old);
+        assert_eq!(sealed.keys.keys().collect::<Vec<_>>(), ["alice"]);
+        assert_eq!(open_as(&secrets, "API_TOKEN", &identities[0]).as_deref(), Ok("s3cr3t"));
+        assert!(open_as(&secrets, "API_TOKEN", &identities[1]).unwrap_err().starts_with("Your identity is not a recipient"));
+
+        // Even with the old recipient entry put back, the new data key was never wrapped for them.
+        secrets.recipients.insert("bob".to_string(), encode_public(&PublicKey::from(&identities[1])));
+        assert!(open_as(&secrets, "API_TOKEN", &identities[1]).unwrap_err().starts_with("Your identity is not a recipient"));
+    }
+
+    #[test]
+    fn unusable_public_keys_are_rejected() {
+        let (mut secrets, _) = team(&["alice"]);
+        // A low-order point gives an all-zero shared secret.
+        secrets.recipients.insert("zero".to_string(), encode_public(&PublicKey::from([0u8; 32])));
+        let error = seal("API_TOKEN", "s3cr3t", &secrets.recipients).err().unwrap();
+        assert!(error.starts_with("The public key of recipient 'zero' in secrets.enc is not usable"));
+
+        secrets.recipients.insert("zero".to_string(), "upman-pk:not-base64!".to_string());
+        assert!(seal("API_TOKEN", "s3cr3t", &secrets.recipients).is_err());
+        assert!(decode_public("upman-pk:AAAA").is_none());
+        assert!(decode_public(&BASE64.encode([9u8; 32])).is_none());
+    }
+}
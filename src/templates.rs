@@ -2,6 +2,7 @@ use std::fs;
 use std::io;
 use std::path::Path;
 use std::env;
+use crate::manifest::{load_manifest, save_manifest};
 use crate::project_init::init_project;
 use crate::util::get_install_path;
 
@@ -78,23 +79,41 @@ pub fn template_manager(action: &str, template_name: &str, project_name: Option<
                 return;
             }
 
-            let project_language = match project_language {
-                Some(lang) => lang.to_string(),
-                None => {
-                    let mut input = String::new();
-                    println!("Enter the project language (e.g., python, rust, cpp):");
-                    io::stdin().read_line(&mut input).expect("Failed to read line");
-                    input.trim().to_string()
+            // Templates saved from a project with upm.toml carry its language and main file; a language or main
+            // file given on the command line still takes precedence.
+            let template_manifest = load_manifest(&templates_dir);
+            let (project_language, project_main) = match &template_manifest {
+                Some(manifest) => {
+                    let chosen = |option: Option<&str>, setting: &str, template_value: &str| match option {
+                        Some(value) if value != template_value => {
+                            println!("Using {} '{}' instead of the template's '{}'.", setting, value, template_value);
+                            value.to_string()
+                        },
+                        _ => template_value.to_string(),
+                    };
+                    (chosen(project_language, "language", &manifest.project.language), chosen(project_main, "main file", &manifest.project.main))
                 },
-            };
-
-            let project_main = match project_main {
-                Some(main) => main.to_string(),
                 None => {
-                    let mut input = String::new();
-                    println!("Enter the project's main entry point (e.g., src/main.py, src/main.rs):");
-                    io::stdin().read_line(&mut input).expect("Failed to read line");
-                    input.trim().to_string()
+                    let project_language = match project_language {
+                        Some(lang) => lang.to_string(),
+                        None => {
+                            let mut input = String::new();
+                            println!("Enter the project language (e.g., python, rust, cpp):");
+                            io::stdin().read_line(&mut input).expect("Failed to read line");
+                            input.trim().to_string()
+                        },
+                    };
+
+                    let project_main = match project_main {
+                        Some(main) => main.to_string(),
+                        None => {
+                            let mut input = String::new();
+                            println!("Enter the project's main entry point (e.g., src/main.py, src/main.rs):");
+                            io::stdin().read_line(&mut input).expect("Failed to read line");
+                            input.trim().to_string()
+                        },
+                    };
+                    (project_language, project_main)
                 },
             };
 
@@ -108,12 +127,19 @@ pub fn template_manager(action: &str, template_name: &str, project_name: Option<
                 return;
             }
 
+            let mut manifest = template_manifest.unwrap_or_default();
+            manifest.project.name = project_name.clone();
+            manifest.project.language = project_language;
+            manifest.project.main = project_main;
+            manifest.project.template = Some(template_name.to_string());
+            save_manifest(&dest_path, &manifest);
+
             if let Err(err) = env::set_current_dir(&dest_path) {
                 eprintln!("Failed to navigate into project directory: {}", err);
                 return;
             }
 
-            init_project(Some(&project_language), Some(&project_main));
+            init_project(None, None);
             println!("Created project '{}' from template '{}'", project_name, template_name);
         },
         "delete" => {
@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::util::{node_tool, venv_bin};
+
+pub const JUNIT_CONSOLE_JAR: &str = "lib/junit-platform-console-standalone-1.10.2.jar";
+const JUNIT_CONSOLE_URL: &str = "https://repo1.maven.org/maven2/org/junit/platform/junit-platform-console-standalone/1.10.2/junit-platform-console-standalone-1.10.2.jar";
+
+pub fn initialize_tests(project_path: &Path, language: &str) {
+    let project_name = project_path.file_name().unwrap().to_string_lossy().to_string();
+
+    match language {
+        "python" => initialize_python_tests(project_path),
+        "rust" => initialize_rust_tests(project_path, &project_name),
+        "javascript" => initialize_javascript_tests(project_path),
+        "typescript" => initialize_typescript_tests(project_path),
+        "go" => initialize_go_tests(project_path),
+        "java" => initialize_java_tests(project_path),
+        "cs" => initialize_cs_tests(project_path, &project_name),
+        "ruby" => initialize_ruby_tests(project_path),
+        "swift" => initialize_swift_tests(project_path, &project_name),
+        "c" => initialize_c_tests(project_path, "c"),
+        "cpp" => initialize_c_tests(project_path, "cpp"),
+        // Scala tests are generated alongside the sbt build; Dart and React scaffolds ship their own.
+        "scala" | "dart" | "react" => {},
+        _ => {
+            fs::create_dir_all(project_path.join("tests")).expect("Failed to create tests directory");
+        },
+    }
+    println!("Initialized tests directory.");
+}
+
+fn write_file(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create test directories");
+    }
+    fs::write(path, contents).unwrap_or_else(|err| panic!("Failed to create {}: {}", path.display(), err));
+}
+
+fn run_setup(command: &mut Command, description: &str) {
+    match command.status() {
+        Ok(status) if status.success() => {},
+        Ok(_) => eprintln!("Failed to {}. Run it manually before 'upman test'.", description),
+        Err(err) => eprintln!("Failed to {}: {}", description, err),
+    }
+}
+
+fn initialize_python_tests(project_path: &Path) {
+    write_file(&project_path.join("pytest.ini"), "[pytest]\ntestpaths = tests\n");
+    write_file(
+        &project_path.join("tests").join("conftest.py"),
+        "import os\nimport sys\n\n# Make the modules in src/ importable from the tests.\nsys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))\n",
+    );
+    write_file(
+        &project_path.join("tests").join("test_main.py"),
+        "from main import main\n\n\ndef test_main_prints_greeting(capsys):\n    main()\n    assert capsys.readouterr().out == 'Hello, world!\\n'\n",
+    );
+    write_file(&project_path.join("requirements-dev.txt"), "pytest\n");
+
+    let pip_path = venv_bin(project_path, "pip");
+    if pip_path.exists() {
+        run_setup(
+            Command::new(pip_path).args(["install", "-r", "requirements-dev.txt"]).current_dir(project_path),
+            "install pytest into the venv",
+        );
+    }
+}
+
+fn initialize_rust_tests(project_path: &Path, project_name: &str) {
+    write_file(
+        &project_path.join("tests").join("integration_test.rs"),
+        &format!(
+            "use std::process::Command;\n\n#[test]\nfn prints_greeting() {{\n    let output = Command::new(env!(\"CARGO_BIN_EXE_{}\"))\n        .output()\n        .expect(\"failed to run binary\");\n\n    assert!(output.status.success());\n    assert_eq!(String::from_utf8_lossy(&output.stdout), \"Hello, world!\\n\");\n}}\n",
+            project_name
+        ),
+    );
+}
+
+fn initialize_javascript_tests(project_path: &Path) {
+    write_file(
+        &project_path.join("tests").join("main.test.js"),
+        "test('prints a greeting', () => {\n  const log = jest.spyOn(console, 'log').mockImplementation(() => {});\n  require('../src/main');\n  expect(log).toHaveBeenCalledWith('Hello, World!');\n  log.mockRestore();\n});\n",
+    );
+    run_setup(Command::new(node_tool("npm")).args(["pkg", "set", "scripts.test=jest"]).current_dir(project_path), "set the npm test script");
+    run_setup(Command::new(node_tool("npm")).args(["install", "--save-dev", "jest"]).current_dir(project_path), "install jest");
+}
+
+fn initialize_typescript_tests(project_path: &Path) {
+    write_file(
+        &project_path.join("tests").join("main.test.ts"),
+        "import { expect, test, vi } from 'vitest';\n\ntest('prints a greeting', async () => {\n  const log = vi.spyOn(console, 'log').mockImplementation(() => {});\n  await import('../src/main');\n  expect(log).toHaveBeenCalledWith('Hello, TypeScript!');\n  log.mockRestore();\n});\n",
+    );
+    run_setup(Command::new(node_tool("npm")).args(["pkg", "set", "scripts.test=vitest run"]).current_dir(project_path), "set the npm test script");
+    run_setup(Command::new(node_tool("npm")).args(["install", "--save-dev", "vitest"]).current_dir(project_path), "install vitest");
+}
+
+fn initialize_go_tests(project_path: &Path) {
+    write_file(
+        &project_path.join("main_test.go"),
+        "package main\n\nimport \"testing\"\n\nfunc TestMainRuns(t *testing.T) {\n\tmain()\n}\n",
+    );
+}
+
+fn initialize_java_tests(project_path: &Path) {
+    write_file(
+        &project_path.join("tests").join("MainTest.java"),
+        "import org.junit.jupiter.api.Test;\n\nimport static org.junit.jupiter.api.Assertions.assertDoesNotThrow;\n\npublic class MainTest {\n    @Test\n    void mainRuns() {\n        assertDoesNotThrow(() -> Main.main(new String[0]));\n    }\n}\n",
+    );
+
+    // Without a build tool, tests run through the standalone JUnit launcher kept in lib/.
+    let jar_path = project_path.join(JUNIT_CONSOLE_JAR);
+    fs::create_dir_all(jar_path.parent().unwrap()).expect("Failed to create lib directory");
+    run_setup(
+        Command::new("curl").args(["-fsSL", "-o", JUNIT_CONSOLE_JAR, JUNIT_CONSOLE_URL]).current_dir(project_path),
+        "download the JUnit console launcher",
+    );
+}
+
+fn initialize_cs_tests(project_path: &Path, project_name: &str) {
+    let test_project = format!("{}.Tests", project_name);
+    let test_dir = format!("tests/{}", test_project);
+    run_setup(Command::new("dotnet").args(["new", "xunit", "-o", &test_dir]).current_dir(project_path), "create the xUnit project");
+    run_setup(
+        Command::new("dotnet")
+            .args(["add", &format!("{}/{}.csproj", test_dir, test_project), "reference", &format!("{}.csproj", project_name)])
+            .current_dir(project_path),
+        "reference the project from its tests",
+    );
+
+    // The SDK globs every .cs file below the project, which would pull the tests into the app.
+    let csproj_path = project_path.join(format!("{}.csproj", project_name));
+    if let Ok(csproj) = fs::read_to_string(&csproj_path) {
+        if !csproj.contains("Compile Remove=\"tests/**\"") {
+            let updated = csproj.replacen("</Project>", "  <ItemGroup>\n    <Compile Remove=\"tests/**\" />\n  </ItemGroup>\n\n</Project>", 1);
+            fs::write(&csproj_path, updated).expect("Failed to update project file");
+        }
+    }
+}
+
+fn initialize_ruby_tests(project_path: &Path) {
+    write_file(
+        &project_path.join("tests").join("test_main.rb"),
+        "require 'minitest/autorun'\n\nclass TestMain < Minitest::Test\n  def test_prints_greeting\n    assert_output(\"Hello, World!\\n\") { load File.expand_path('../src/main.rb', __dir__) }\n  end\nend\n",
+    );
+    write_file(
+        &project_path.join("Rakefile"),
+        "require 'rake/testtask'\n\nRake::TestTask.new do |t|\n  t.test_files = FileList['tests/**/test_*.rb']\nend\n\ntask default: :test\n",
+    );
+}
+
+fn initialize_swift_tests(project_path: &Path, project_name: &str) {
+    let module = swift_module_name(project_name);
+    let test_target = format!("{}Tests", module);
+    write_file(
+        &project_path.join("Tests").join(&test_target).join(format!("{}.swift", test_target)),
+        &format!(
+            "import XCTest\n@testable import {}\n\nfinal class {}: XCTestCase {{\n    func testExample() {{\n        XCTAssertTrue(true)\n    }}\n}}\n",
+            module, test_target
+        ),
+    );
+
+    let manifest_path = project_path.join("Package.swift");
+    let Ok(manifest) = fs::read_to_string(&manifest_path) else {
+        eprintln!("Package.swift not found; add a test target for {} manually.", test_target);
+        return;
+    };
+    match add_swift_test_target(&manifest, &test_target, project_name) {
+        Ok(Some(updated)) => fs::write(&manifest_path, updated).expect("Failed to update Package.swift"),
+        Ok(None) => {},
+        Err(message) => eprintln!("{}", message),
+    }
+}
+
+// Inserts a `.testTarget` at the start of the package's targets list. Returns `None` when the manifest already
+// has a test target.
+fn add_swift_test_target(manifest: &str, test_target: &str, dependency: &str) -> Result<Option<String>, String> {
+    if manifest.contains(".testTarget(") {
+        return Ok(None);
+    }
+    let Some(index) = manifest.find("targets: [") else {
+        return Err(format!("Could not find the targets list in Package.swift; add a test target for {} manually.", test_target));
+    };
+    let insert_at = index + "targets: [".len();
+    let entry = format!("\n        .testTarget(name: \"{}\", dependencies: [\"{}\"]),", test_target, dependency);
+    Ok(Some(format!("{}{}{}", &manifest[..insert_at], entry, &manifest[insert_at..])))
+}
+
+// SwiftPM names a target's module after the target with every character that can't appear in an identifier
+// replaced by `_`, and a leading digit prefixed with one, so `my-app` is imported as `my_app`.
+fn swift_module_name(target: &str) -> String {
+    let module: String = target.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+    if module.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", module)
+    } else {
+        module
+    }
+}
+
+fn initialize_c_tests(project_path: &Path, extension: &str) {
+    let include = if extension == "c" { "assert.h" } else { "cassert" };
+    write_file(
+        &project_path.join("tests").join(format!("test_main.{}", extension)),
+        &format!("#include <{}>\n\nint main() {{\n    assert(1 + 1 == 2);\n    return 0;\n}}\n", include),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGE: &str = "\
+// swift-tools-version:5.9
+import PackageDescription
+
+let package = Package(
+    name: \"my-app\",
+    targets: [
+        .executableTarget(name: \"my-app\", path: \"Sources\"),
+    ]
+)
+";
+
+    #[test]
+    fn swift_module_names() {
+        assert_eq!(swift_module_name("my-app"), "my_app");
+        assert_eq!(swift_module_name("1tool"), "_1tool");
+        assert_eq!(swift_module_name("My.Tool v2"), "My_Tool_v2");
+        assert_eq!(swift_module_name("already_fine"), "already_fine");
+    }
+
+    #[test]
+    fn swift_test_target_is_added_once() {
+        let updated = add_swift_test_target(PACKAGE, "my_appTests", "my-app").unwrap().unwrap();
+        assert!(updated.contains(
+            "    targets: [\n        .testTarget(name: \"my_appTests\", dependencies: [\"my-app\"]),\n        .executableTarget(name: \"my-app\""
+        ));
+        assert_eq!(add_swift_test_target(&updated, "my_appTests", "my-app"), Ok(None));
+    }
+
+    #[test]
+    fn swift_manifest_without_targets_list() {
+        let manifest = "let package = Package(name: \"tool\", products: [])\n";
+        let error = add_swift_test_target(manifest, "toolTests", "tool").unwrap_err();
+        assert_eq!(error, "Could not find the targets list in Package.swift; add a test target for toolTests manually.");
+    }
+}
@@ -0,0 +1,884 @@
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, ExitStatus, Stdio};
+use std::time::{Instant, SystemTime};
+use regex::Regex;
+
+use crate::build::{collect_sources, gradle_command, java_classpath, maven_command, native_compile_args, native_link_args, native_toolchain};
+use crate::project_database::{find_project_for_dir, load_projects_db, ProjectInfo};
+use crate::test_scaffold::JUNIT_CONSOLE_JAR;
+use crate::util::{clean_path, command_exists, node_tool, project_state_dir, venv_bin};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+pub struct TestCase {
+    pub suite: String,
+    pub name: String,
+    pub status: TestStatus,
+    pub time: f64,
+    pub message: Option<String>,
+}
+
+/// Results from one test run. Runners that only report an exit status leave `cases` empty.
+#[derive(Default)]
+pub struct TestSummary {
+    pub cases: Vec<TestCase>,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl TestSummary {
+    fn from_cases(cases: Vec<TestCase>) -> TestSummary {
+        let count = |status| cases.iter().filter(|case| case.status == status).count();
+        TestSummary {
+            passed: count(TestStatus::Passed),
+            failed: count(TestStatus::Failed),
+            skipped: count(TestStatus::Skipped),
+            cases,
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.passed + self.failed + self.skipped
+    }
+}
+
+// How a runner reports results once it has finished.
+enum Results {
+    JUnitDir(PathBuf),
+    JUnitFile(PathBuf),
+    Trx(PathBuf),
+    /// A runner-specific report file, read with the given parser.
+    Report(PathBuf, fn(&str) -> TestSummary),
+    Text(fn(&str) -> TestSummary),
+    GoJson,
+    ExitStatus,
+}
+
+struct TestRun {
+    command: Command,
+    results: Results,
+}
+
+pub fn run_tests(filter: Option<&str>, coverage: bool, junit_output: Option<&str>) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let db = load_projects_db();
+
+    let Some((project_name, info)) = find_project_for_dir(&db, &current_dir_str) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+    let project_path = Path::new(&info.project_path);
+    let results_dir = project_state_dir(project_path).join("test-results");
+    let _ = fs::remove_dir_all(&results_dir);
+    fs::create_dir_all(&results_dir).expect("Failed to create test results directory");
+
+    let language = info.project_language.to_lowercase();
+    let (summary, status) = if language == "c" || language == "cpp" || language == "c++" {
+        run_c_tests(project_path, info, &language, filter, coverage, &results_dir)
+    } else {
+        let Some(mut run) = test_command(project_path, &language, filter, coverage, &results_dir) else {
+            eprintln!("Testing is not supported for {} projects.", info.project_language);
+            return;
+        };
+        run.command.current_dir(project_path);
+
+        let started = SystemTime::now();
+        let echo = !matches!(run.results, Results::GoJson);
+        let (status, output) = match run_captured(&mut run.command, echo) {
+            Ok(result) => result,
+            Err(err) => {
+                eprintln!("Failed to run tests: {}", err);
+                process::exit(1);
+            }
+        };
+        let summary = match run.results {
+            Results::JUnitDir(dir) => parse_junit_files(&junit_files_in(&dir, started)),
+            Results::JUnitFile(file) => parse_junit_files(&[file]),
+            Results::Trx(file) => parse_trx(&file),
+            Results::Report(file, parser) => parser(&fs::read_to_string(&file).unwrap_or_default()),
+            Results::Text(parser) => parser(&output),
+            Results::GoJson => parse_go_json(&output),
+            Results::ExitStatus => TestSummary::default(),
+        };
+        (summary, status.success())
+    };
+
+    print_summary(&summary, status);
+    if let Some(path) = junit_output {
+        match fs::write(path, junit_report(project_name, &summary)) {
+            Ok(_) => println!("Wrote JUnit report to {}", path),
+            Err(err) => eprintln!("Failed to write JUnit report: {}", err),
+        }
+    }
+
+    if !status || summary.failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn test_command(project_path: &Path, language: &str, filter: Option<&str>, coverage: bool, results_dir: &Path) -> Option<TestRun> {
+    let run = match language {
+        "rust" | "rs" => {
+            let mut command = Command::new("cargo");
+            if coverage && command_exists_with("cargo", &["llvm-cov", "--version"]) {
+                command.args(["llvm-cov", "test"]);
+            } else {
+                if coverage {
+                    eprintln!("Coverage for Rust needs cargo-llvm-cov ('cargo install cargo-llvm-cov').");
+                }
+                command.arg("test");
+            }
+            command.args(filter);
+            TestRun { command, results: Results::Text(parse_cargo_output) }
+        },
+        "python" => {
+            let pytest = venv_bin(project_path, "pytest");
+            let mut command = if pytest.exists() { Command::new(pytest) } else { Command::new("pytest") };
+            let report = results_dir.join("pytest.xml");
+            command.arg(format!("--junitxml={}", report.display()));
+            if let Some(filter) = filter {
+                command.args(["-k", filter]);
+            }
+            if coverage {
+                command.args(["--cov=src", "--cov-report=term"]);
+            }
+            TestRun { command, results: Results::JUnitFile(report) }
+        },
+        "javascript" | "js" | "typescript" | "ts" | "react" => node_test_command(project_path, filter, coverage, results_dir),
+        "go" => {
+            let mut command = Command::new("go");
+            command.args(["test", "-json"]);
+            if let Some(filter) = filter {
+                command.args(["-run", filter]);
+            }
+            if coverage {
+                command.arg("-cover");
+            }
+            command.arg("./...");
+            TestRun { command, results: Results::GoJson }
+        },
+        "cs" | "c#" => {
+            let mut command = Command::new("dotnet");
+            command.arg("test");
+            if let Some(test_project) = find_dotnet_test_project(project_path) {
+                command.arg(test_project);
+            }
+            if let Some(filter) = filter {
+                command.args(["--filter", filter]);
+            }
+            if coverage {
+                command.args(["--collect", "XPlat Code Coverage"]);
+            }
+            command.arg("--results-directory").arg(results_dir).args(["--logger", "trx;LogFileName=dotnet.trx"]);
+            TestRun { command, results: Results::Trx(results_dir.join("dotnet.trx")) }
+        },
+        "java" => java_test_command(project_path, filter, coverage, results_dir)?,
+        "ruby" => {
+            if project_path.join("spec").is_dir() {
+                let mut command = if project_path.join("Gemfile").exists() {
+                    let mut command = Command::new("bundle");
+                    command.args(["exec", "rspec"]);
+                    command
+                } else {
+                    Command::new("rspec")
+                };
+                // Keep the usual console output and write every example's result to a JSON file as well.
+                let report = results_dir.join("rspec.json");
+                command.args(["--format", "progress", "--format", "json", "--out"]).arg(&report);
+                if let Some(filter) = filter {
+                    command.args(["-e", filter]);
+                }
+                TestRun { command, results: Results::Report(report, parse_rspec_json) }
+            } else {
+                let mut command = Command::new("rake");
+                command.arg("test");
+                let mut options = String::from("-v");
+                if let Some(filter) = filter {
+                    options.push_str(&format!(" -n /{}/", filter));
+                }
+                command.env("TESTOPTS", options);
+                TestRun { command, results: Results::Text(parse_minitest_output) }
+            }
+        },
+        "swift" => {
+            let mut command = Command::new("swift");
+            let report = results_dir.join("swift.xml");
+            command.arg("test").arg("--xunit-output").arg(&report);
+            if let Some(filter) = filter {
+                command.args(["--filter", filter]);
+            }
+            if coverage {
+                command.arg("--enable-code-coverage");
+            }
+            TestRun { command, results: Results::JUnitFile(report) }
+        },
+        "scala" => {
+            let mut command = Command::new("sbt");
+            match filter {
+                Some(filter) => command.arg(format!("testOnly *{}*", filter)),
+                None => command.arg("test"),
+            };
+            if coverage {
+                eprintln!("Coverage for Scala needs the sbt-scoverage plugin; run 'sbt coverage test' directly.");
+            }
+            TestRun { command, results: Results::JUnitDir(project_path.join("target").join("test-reports")) }
+        },
+        "dart" => {
+            let mut command = Command::new("dart");
+            let report = results_dir.join("dart.json");
+            command.arg("test").arg(format!("--file-reporter=json:{}", report.display()));
+            if let Some(filter) = filter {
+                command.args(["--name", filter]);
+            }
+            if coverage {
+                command.arg("--coverage=coverage");
+            }
+            TestRun { command, results: Results::Report(report, parse_dart_json) }
+        },
+        _ => return None,
+    };
+    Some(run)
+}
+
+fn node_test_command(project_path: &Path, filter: Option<&str>, coverage: bool, results_dir: &Path) -> TestRun {
+    let test_script = fs::read_to_string(project_path.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .and_then(|json| json.get("scripts")?.get("test")?.as_str().map(String::from))
+        .unwrap_or_default();
+
+    let mut command = Command::new(node_tool("npm"));
+    command.args(["test", "--"]);
+    // react-scripts and vitest default to watch mode outside CI.
+    command.env("CI", "true");
+
+    let mut results = Results::ExitStatus;
+    if test_script.contains("vitest") {
+        let report = results_dir.join("vitest.xml");
+        command.args(["--reporter=default", "--reporter=junit"]).arg(format!("--outputFile={}", report.display()));
+        results = Results::JUnitFile(report);
+    } else if project_path.join("node_modules").join("jest-junit").is_dir() {
+        command.args(["--reporters=default", "--reporters=jest-junit"]);
+        command.env("JEST_JUNIT_OUTPUT_DIR", results_dir);
+        results = Results::JUnitDir(results_dir.to_path_buf());
+    }
+    if let Some(filter) = filter {
+        command.args(["-t", filter]);
+    }
+    if coverage {
+        command.arg("--coverage");
+    }
+    TestRun { command, results }
+}
+
+fn java_test_command(project_path: &Path, filter: Option<&str>, coverage: bool, results_dir: &Path) -> Option<TestRun> {
+    if coverage {
+        eprintln!("Coverage for Java needs JaCoCo configured in the build; running tests without it.");
+    }
+
+    if let Some(mut command) = maven_command(project_path) {
+        command.arg("test");
+        if let Some(filter) = filter {
+            command.arg(format!("-Dtest={}", filter));
+        }
+        return Some(TestRun { command, results: Results::JUnitDir(project_path.join("target").join("surefire-reports")) });
+    }
+
+    if let Some(mut command) = gradle_command(project_path) {
+        command.arg("test");
+        if let Some(filter) = filter {
+            command.args(["--tests", filter]);
+        }
+        return Some(TestRun { command, results: Results::JUnitDir(project_path.join("build").join("test-results").join("test")) });
+    }
+
+    // Plain javac projects: compile sources and tests, then use the JUnit console launcher.
+    if !project_path.join(JUNIT_CONSOLE_JAR).exists() {
+        eprintln!("JUnit console launcher not found at {}. Re-run 'upman new --tests' or download it there.", JUNIT_CONSOLE_JAR);
+        return None;
+    }
+    let classes = project_path.join("build").join("classes");
+    let test_classes = project_path.join("build").join("test-classes");
+
+    let mut sources = Vec::new();
+    collect_sources(&project_path.join("src"), &["java"], &mut sources);
+    let mut test_sources = Vec::new();
+    collect_sources(&project_path.join("tests"), &["java"], &mut test_sources);
+
+    // Both compilations and the launcher also see the project's `lib/*` jars, as in `upm build`.
+    let mut compile_sources = Command::new("javac");
+    compile_sources.arg("-d").arg(&classes);
+    let classpath = java_classpath(project_path, &[]);
+    if !classpath.is_empty() {
+        compile_sources.arg("-cp").arg(classpath);
+    }
+    let compiled = compile_sources.args(&sources).current_dir(project_path).status().is_ok_and(|status| status.success())
+        && Command::new("javac")
+            .arg("-d")
+            .arg(&test_classes)
+            .arg("-cp")
+            .arg(java_classpath(project_path, &[&classes, Path::new(JUNIT_CONSOLE_JAR)]))
+            .args(&test_sources)
+            .current_dir(project_path)
+            .status()
+            .is_ok_and(|status| status.success());
+    if !compiled {
+        eprintln!("Compilation failed");
+        process::exit(1);
+    }
+
+    let reports = results_dir.join("junit");
+    let mut command = Command::new("java");
+    command
+        .args(["-jar", JUNIT_CONSOLE_JAR, "--scan-classpath", "--disable-banner"])
+        .arg("--class-path")
+        .arg(java_classpath(project_path, &[&classes, &test_classes]))
+        .arg("--reports-dir")
+        .arg(&reports);
+    if let Some(filter) = filter {
+        command.arg("--include-classname").arg(format!(".*{}.*", regex::escape(filter)));
+    }
+    Some(TestRun { command, results: Results::JUnitDir(reports) })
+}
+
+fn run_c_tests(project_path: &Path, info: &ProjectInfo, language: &str, filter: Option<&str>, coverage: bool, results_dir: &Path) -> (TestSummary, bool) {
+    let (compiler, extensions) = native_toolchain(language);
+
+    let main_path = project_path.join(&info.project_main);
+    let main_path = main_path.canonicalize().unwrap_or(main_path);
+    let mut sources = Vec::new();
+    collect_sources(&project_path.join("src"), extensions, &mut sources);
+    sources.retain(|source| source.canonicalize().map(|source| source != main_path).unwrap_or(true));
+
+    let mut tests = Vec::new();
+    collect_sources(&project_path.join("tests"), extensions, &mut tests);
+    tests.sort();
+
+    let mut cases = Vec::new();
+    for test in tests {
+        let name = test.file_stem().unwrap().to_string_lossy().to_string();
+        if filter.is_some_and(|filter| !name.contains(filter)) {
+            continue;
+        }
+        let binary = results_dir.join(if cfg!(windows) { format!("{}.exe", name) } else { name.clone() });
+
+        let started = Instant::now();
+        let mut compile = Command::new(compiler);
+        compile
+            .arg(&test)
+            .args(&sources)
+            .args(native_compile_args(project_path, &info.native_build))
+            .arg("-o")
+            .arg(&binary)
+            .args(native_link_args(&info.native_build));
+        if coverage {
+            compile.arg("--coverage");
+        }
+        let compiled = compile.current_dir(project_path).status().is_ok_and(|status| status.success());
+        let (status, message) = if !compiled {
+            (TestStatus::Failed, Some("compilation failed".to_string()))
+        } else {
+            match Command::new(&binary).current_dir(project_path).status() {
+                Ok(status) if status.success() => (TestStatus::Passed, None),
+                Ok(status) => (TestStatus::Failed, Some(format!("exited with {}", status))),
+                Err(err) => (TestStatus::Failed, Some(err.to_string())),
+            }
+        };
+        println!("test {} ... {}", name, if status == TestStatus::Passed { "ok" } else { "FAILED" });
+        cases.push(TestCase { suite: "tests".to_string(), name, status, time: started.elapsed().as_secs_f64(), message });
+    }
+
+    let summary = TestSummary::from_cases(cases);
+    let success = summary.failed == 0;
+    (summary, success)
+}
+
+fn command_exists_with(program: &str, args: &[&str]) -> bool {
+    command_exists(program)
+        && Command::new(program).args(args).stdout(Stdio::null()).stderr(Stdio::null()).status().is_ok_and(|status| status.success())
+}
+
+fn find_dotnet_test_project(project_path: &Path) -> Option<PathBuf> {
+    let mut projects = Vec::new();
+    collect_sources(&project_path.join("tests"), &["csproj"], &mut projects);
+    projects.into_iter().next()
+}
+
+/// Runs `command`, echoing its stdout as it arrives and returning it for parsing.
+fn run_captured(command: &mut Command, echo: bool) -> std::io::Result<(ExitStatus, String)> {
+    let mut child = command.stdout(Stdio::piped()).stderr(Stdio::inherit()).spawn()?;
+    let mut output = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if echo {
+                println!("{}", line);
+            }
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    Ok((child.wait()?, output))
+}
+
+fn junit_files_in(dir: &Path, since: SystemTime) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xml"))
+        // Build tools keep reports from earlier runs around; only read this run's.
+        .filter(|path| fs::metadata(path).and_then(|meta| meta.modified()).map(|modified| modified >= since).unwrap_or(true))
+        .collect()
+}
+
+pub fn parse_junit_files(files: &[PathBuf]) -> TestSummary {
+    let mut cases = Vec::new();
+    for file in files {
+        match fs::read_to_string(file) {
+            Ok(contents) => cases.extend(parse_junit(&contents)),
+            Err(err) => eprintln!("Failed to read test report {}: {}", file.display(), err),
+        }
+    }
+    TestSummary::from_cases(cases)
+}
+
+pub fn parse_junit(contents: &str) -> Vec<TestCase> {
+    let Ok(document) = roxmltree::Document::parse(contents) else {
+        eprintln!("Failed to parse JUnit XML report.");
+        return Vec::new();
+    };
+
+    document
+        .descendants()
+        .filter(|node| node.has_tag_name("testcase"))
+        .map(|node| {
+            let suite = node
+                .attribute("classname")
+                .or_else(|| node.ancestors().find(|parent| parent.has_tag_name("testsuite")).and_then(|parent| parent.attribute("name")))
+                .unwrap_or("")
+                .to_string();
+            let failure = node.children().find(|child| child.has_tag_name("failure") || child.has_tag_name("error"));
+            let skipped = node.children().any(|child| child.has_tag_name("skipped"));
+            let (status, message) = match failure {
+                Some(failure) => (TestStatus::Failed, failure.attribute("message").or(failure.text()).map(|text| text.trim().to_string())),
+                None if skipped => (TestStatus::Skipped, None),
+                None => (TestStatus::Passed, None),
+            };
+            TestCase {
+                suite,
+                name: node.attribute("name").unwrap_or("").to_string(),
+                status,
+                time: node.attribute("time").and_then(|time| time.parse().ok()).unwrap_or(0.0),
+                message,
+            }
+        })
+        .collect()
+}
+
+fn parse_trx(file: &Path) -> TestSummary {
+    let Ok(contents) = fs::read_to_string(file) else {
+        return TestSummary::default();
+    };
+    let Ok(document) = roxmltree::Document::parse(&contents) else {
+        eprintln!("Failed to parse TRX report.");
+        return TestSummary::default();
+    };
+
+    let cases = document
+        .descendants()
+        .filter(|node| node.has_tag_name("UnitTestResult"))
+        .map(|node| {
+            let full_name = node.attribute("testName").unwrap_or("");
+            let (suite, name) = full_name.rsplit_once('.').unwrap_or(("", full_name));
+            let status = match node.attribute("outcome") {
+                Some("Passed") => TestStatus::Passed,
+                Some("NotExecuted") | Some("Inconclusive") => TestStatus::Skipped,
+                _ => TestStatus::Failed,
+            };
+            let message = node
+                .descendants()
+                .find(|child| child.has_tag_name("Message"))
+                .and_then(|child| child.text())
+                .map(|text| text.trim().to_string());
+            TestCase {
+                suite: suite.to_string(),
+                name: name.to_string(),
+                status,
+                time: node.attribute("duration").map(parse_trx_duration).unwrap_or(0.0),
+                message,
+            }
+        })
+        .collect();
+    TestSummary::from_cases(cases)
+}
+
+// TRX durations look like "00:00:01.2345678".
+fn parse_trx_duration(duration: &str) -> f64 {
+    duration
+        .split(':')
+        .filter_map(|part| part.parse::<f64>().ok())
+        .fold(0.0, |total, part| total * 60.0 + part)
+}
+
+fn parse_cargo_output(output: &str) -> TestSummary {
+    // Doctest names contain spaces: "test src/lib.rs - Parser::parse (line 12) ... ok".
+    let line = Regex::new(r"^test (.+?) \.\.\. (ok|FAILED|ignored)").unwrap();
+    let cases = output
+        .lines()
+        .filter_map(|text| line.captures(text))
+        .map(|captures| {
+            let full_name = &captures[1];
+            let (suite, name) = full_name.split_once(" - ").or_else(|| full_name.rsplit_once("::")).unwrap_or(("", full_name));
+            let status = match &captures[2] {
+                "ok" => TestStatus::Passed,
+                "ignored" => TestStatus::Skipped,
+                _ => TestStatus::Failed,
+            };
+            TestCase { suite: suite.to_string(), name: name.to_string(), status, time: 0.0, message: None }
+        })
+        .collect();
+    TestSummary::from_cases(cases)
+}
+
+fn parse_go_json(output: &str) -> TestSummary {
+    let mut cases = Vec::new();
+    for line in output.lines() {
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+            println!("{}", line);
+            continue;
+        };
+        if let Some(text) = event.get("Output").and_then(|text| text.as_str()) {
+            print!("{}", text);
+        }
+        let Some(test) = event.get("Test").and_then(|test| test.as_str()) else {
+            continue;
+        };
+        let status = match event.get("Action").and_then(|action| action.as_str()) {
+            Some("pass") => TestStatus::Passed,
+            Some("fail") => TestStatus::Failed,
+            Some("skip") => TestStatus::Skipped,
+            _ => continue,
+        };
+        cases.push(TestCase {
+            suite: event.get("Package").and_then(|package| package.as_str()).unwrap_or("").to_string(),
+            name: test.to_string(),
+            status,
+            time: event.get("Elapsed").and_then(|elapsed| elapsed.as_f64()).unwrap_or(0.0),
+            message: None,
+        });
+    }
+    TestSummary::from_cases(cases)
+}
+
+fn parse_minitest_output(output: &str) -> TestSummary {
+    // Verbose minitest prints "TestMain#test_prints_greeting = 0.00 s = ."
+    let line = Regex::new(r"^(\w[\w:]*)#(\S+) = ([\d.]+) s = ([.FES])").unwrap();
+    let cases = output
+        .lines()
+        .filter_map(|text| line.captures(text))
+        .map(|captures| TestCase {
+            suite: captures[1].to_string(),
+            name: captures[2].to_string(),
+            status: match &captures[4] {
+                "." => TestStatus::Passed,
+                "S" => TestStatus::Skipped,
+                _ => TestStatus::Failed,
+            },
+            time: captures[3].parse().unwrap_or(0.0),
+            message: None,
+        })
+        .collect();
+    TestSummary::from_cases(cases)
+}
+
+// The report written by `rspec --format json`.
+fn parse_rspec_json(report: &str) -> TestSummary {
+    let Ok(report) = serde_json::from_str::<serde_json::Value>(report) else {
+        return TestSummary::default();
+    };
+    let text = |example: &serde_json::Value, key: &str| example.get(key).and_then(|value| value.as_str()).unwrap_or("").to_string();
+    let cases = report
+        .get("examples")
+        .and_then(|examples| examples.as_array())
+        .into_iter()
+        .flatten()
+        .map(|example| {
+            let name = text(example, "description");
+            // The full description is the example group's description followed by the example's.
+            let full_description = text(example, "full_description");
+            let suite = full_description.strip_suffix(&name).map(str::trim).filter(|suite| !suite.is_empty()).map_or_else(|| text(example, "file_path"), String::from);
+            let status = match example.get("status").and_then(|status| status.as_str()) {
+                Some("passed") => TestStatus::Passed,
+                Some("pending") => TestStatus::Skipped,
+                _ => TestStatus::Failed,
+            };
+            TestCase {
+                suite,
+                name,
+                status,
+                time: example.get("run_time").and_then(|time| time.as_f64()).unwrap_or(0.0),
+                message: example.pointer("/exception/message").and_then(|message| message.as_str()).map(|message| message.trim().to_string()),
+            }
+        })
+        .collect();
+    TestSummary::from_cases(cases)
+}
+
+// The event stream written by `dart test --file-reporter=json:<file>`, one JSON object per line.
+fn parse_dart_json(report: &str) -> TestSummary {
+    let mut suites = std::collections::HashMap::new();
+    let mut tests = std::collections::HashMap::new();
+    let mut errors = std::collections::HashMap::new();
+    let mut cases = Vec::new();
+    for event in report.lines().filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok()) {
+        let time = event.get("time").and_then(|time| time.as_f64()).unwrap_or(0.0);
+        match event.get("type").and_then(|kind| kind.as_str()) {
+            Some("suite") => {
+                if let (Some(id), Some(path)) = (event.pointer("/suite/id").and_then(|id| id.as_u64()), event.pointer("/suite/path").and_then(|path| path.as_str())) {
+                    suites.insert(id, path.to_string());
+                }
+            },
+            Some("testStart") => {
+                let Some(id) = event.pointer("/test/id").and_then(|id| id.as_u64()) else {
+                    continue;
+                };
+                let name = event.pointer("/test/name").and_then(|name| name.as_str()).unwrap_or("").to_string();
+                let suite = event.pointer("/test/suiteID").and_then(|id| id.as_u64());
+                tests.insert(id, (name, suite, time));
+            },
+            Some("error") => {
+                if let (Some(id), Some(error)) = (event.get("testID").and_then(|id| id.as_u64()), event.get("error").and_then(|error| error.as_str())) {
+                    errors.entry(id).or_insert_with(|| error.trim().to_string());
+                }
+            },
+            // Hidden tests are the runner's own "loading <file>" steps.
+            Some("testDone") if event.get("hidden").and_then(|hidden| hidden.as_bool()) != Some(true) => {
+                let Some(id) = event.get("testID").and_then(|id| id.as_u64()) else {
+                    continue;
+                };
+                let Some((name, suite, started)) = tests.get(&id) else {
+                    continue;
+                };
+                let status = if event.get("skipped").and_then(|skipped| skipped.as_bool()) == Some(true) {
+                    TestStatus::Skipped
+                } else if event.get("result").and_then(|result| result.as_str()) == Some("success") {
+                    TestStatus::Passed
+                } else {
+                    TestStatus::Failed
+                };
+                cases.push(TestCase {
+                    suite: suite.and_then(|suite| suites.get(&suite)).cloned().unwrap_or_default(),
+                    name: name.clone(),
+                    status,
+                    time: (time - started) / 1000.0,
+                    message: errors.remove(&id),
+                });
+            },
+            _ => {},
+        }
+    }
+    TestSummary::from_cases(cases)
+}
+
+fn print_summary(summary: &TestSummary, success: bool) {
+    println!(" ");
+    if summary.total() == 0 {
+        println!("Test run {}.", if success { "passed" } else { "failed" });
+        return;
+    }
+
+    let failures: Vec<&TestCase> = summary.cases.iter().filter(|case| case.status == TestStatus::Failed).collect();
+    if !failures.is_empty() {
+        println!("Failures:");
+        for case in failures {
+            let name = if case.suite.is_empty() { case.name.clone() } else { format!("{}::{}", case.suite, case.name) };
+            match &case.message {
+                Some(message) => println!("  {} - {}", name, message.lines().next().unwrap_or("")),
+                None => println!("  {}", name),
+            }
+        }
+        println!(" ");
+    }
+    println!(
+        "Tests: {} passed, {} failed, {} skipped ({} total)",
+        summary.passed,
+        summary.failed,
+        summary.skipped,
+        summary.total()
+    );
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Renders the run as a single JUnit document with one `<testsuite>` per suite.
+pub fn junit_report(project_name: &str, summary: &TestSummary) -> String {
+    let mut suites: Vec<&str> = summary.cases.iter().map(|case| case.suite.as_str()).collect();
+    suites.sort();
+    suites.dedup();
+
+    let total_time: f64 = summary.cases.iter().map(|case| case.time).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        xml_escape(project_name),
+        summary.total(),
+        summary.failed,
+        summary.skipped,
+        total_time
+    ));
+
+    for suite in suites {
+        let cases: Vec<&TestCase> = summary.cases.iter().filter(|case| case.suite == suite).collect();
+        let count = |status| cases.iter().filter(|case| case.status == status).count();
+        let suite_name = if suite.is_empty() { project_name } else { suite };
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(suite_name),
+            cases.len(),
+            count(TestStatus::Failed),
+            count(TestStatus::Skipped),
+            cases.iter().map(|case| case.time).sum::<f64>()
+        ));
+        for case in cases {
+            let open = format!(
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+                xml_escape(suite_name),
+                xml_escape(&case.name),
+                case.time
+            );
+            match case.status {
+                TestStatus::Passed => xml.push_str(&format!("{} />\n", open)),
+                TestStatus::Skipped => xml.push_str(&format!("{}>\n      <skipped />\n    </testcase>\n", open)),
+                TestStatus::Failed => xml.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\" />\n    </testcase>\n",
+                    open,
+                    xml_escape(case.message.as_deref().unwrap_or("failed"))
+                )),
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(summary: &TestSummary) -> (usize, usize, usize) {
+        (summary.passed, summary.failed, summary.skipped)
+    }
+
+    fn case<'a>(summary: &'a TestSummary, name: &str) -> &'a TestCase {
+        summary.cases.iter().find(|case| case.name == name).unwrap()
+    }
+
+    #[test]
+    fn junit_failures_errors_and_skips() {
+        let summary = TestSummary::from_cases(parse_junit(include_str!("../tests/fixtures/testing/pytest-junit.xml")));
+        assert_eq!(counts(&summary), (2, 2, 1));
+        assert_eq!(case(&summary, "test_divides").message.as_deref(), Some("assert 1 == 2"));
+        assert_eq!(case(&summary, "test_connects").suite, "tests.test_db");
+        // Without a classname the enclosing suite's name is used.
+        assert_eq!(case(&summary, "starts").suite, "smoke");
+        assert_eq!(case(&summary, "starts").time, 0.25);
+    }
+
+    #[test]
+    fn trx_results() {
+        let summary = parse_trx(Path::new("tests/fixtures/testing/dotnet.trx"));
+        assert_eq!(counts(&summary), (1, 1, 1));
+        let divides = case(&summary, "Divides");
+        assert_eq!(divides.suite, "Demo.Tests.CalculatorTests");
+        assert_eq!(divides.time, 1.5);
+        assert!(divides.message.as_deref().unwrap().starts_with("Assert.Equal() Failure"));
+    }
+
+    #[test]
+    fn cargo_unit_and_doc_tests() {
+        let summary = parse_cargo_output(include_str!("../tests/fixtures/testing/cargo-test.txt"));
+        assert_eq!(counts(&summary), (2, 1, 2));
+        assert_eq!(case(&summary, "rejects_empty").suite, "parser::tests");
+        assert!(case(&summary, "slow").status == TestStatus::Skipped);
+        let doctest = case(&summary, "parser::Parser::parse (line 12)");
+        assert_eq!(doctest.suite, "src/parser.rs");
+        assert!(doctest.status == TestStatus::Skipped);
+        assert!(case(&summary, "add (line 3)").status == TestStatus::Passed);
+    }
+
+    #[test]
+    fn go_json_events() {
+        let summary = parse_go_json(include_str!("../tests/fixtures/testing/go-test.json"));
+        // The package-level "fail" event is not a test.
+        assert_eq!(counts(&summary), (1, 1, 1));
+        assert_eq!(case(&summary, "TestAdd").suite, "example.com/demo");
+        assert_eq!(case(&summary, "TestAdd").time, 0.01);
+    }
+
+    #[test]
+    fn minitest_verbose_output() {
+        let summary = parse_minitest_output(include_str!("../tests/fixtures/testing/minitest.txt"));
+        // Errors count as failures.
+        assert_eq!(counts(&summary), (1, 2, 1));
+        assert_eq!(case(&summary, "test_totals").suite, "Billing::InvoiceTest");
+    }
+
+    #[test]
+    fn rspec_json_report() {
+        let summary = parse_rspec_json(include_str!("../tests/fixtures/testing/rspec.json"));
+        assert_eq!(counts(&summary), (1, 1, 1));
+        let divides = case(&summary, "divides");
+        assert_eq!(divides.suite, "Calculator #divide");
+        assert!(divides.message.as_deref().unwrap().starts_with("expected: 2"));
+        assert_eq!(case(&summary, "rounds").suite, "Calculator");
+        assert_eq!(counts(&parse_rspec_json("")), (0, 0, 0));
+    }
+
+    #[test]
+    fn dart_json_report() {
+        let summary = parse_dart_json(include_str!("../tests/fixtures/testing/dart-test.json"));
+        // The hidden "loading" test is left out.
+        assert_eq!(summary.cases.len(), 3);
+        assert_eq!(counts(&summary), (1, 1, 1));
+        let divides = case(&summary, "divides");
+        assert_eq!(divides.suite, "test/calculator_test.dart");
+        assert_eq!(divides.message.as_deref(), Some("Expected: <2>\n  Actual: <1>"));
+        assert_eq!(divides.time, 0.005);
+    }
+
+    #[test]
+    fn combined_junit_report() {
+        let mut cases = parse_junit(include_str!("../tests/fixtures/testing/pytest-junit.xml"));
+        cases.extend(parse_rspec_json(include_str!("../tests/fixtures/testing/rspec.json")).cases);
+        let summary = TestSummary::from_cases(cases);
+        let report = junit_report("demo & co", &summary);
+
+        assert!(report.contains("<testsuites name=\"demo &amp; co\" tests=\"8\" failures=\"3\" skipped=\"2\""));
+        assert!(report.contains("<testsuite name=\"tests.test_calc\" tests=\"3\" failures=\"1\" skipped=\"1\""));
+        assert!(report.contains("<failure message=\"failed on setup with &quot;fixture &apos;db&apos; not found&quot;\" />"));
+
+        // Every counted test has its own <testcase>, so reading the report back gives the same totals.
+        let reread = TestSummary::from_cases(parse_junit(&report));
+        assert_eq!(counts(&reread), counts(&summary));
+        assert_eq!(case(&reread, "divides").suite, "Calculator #divide");
+    }
+}
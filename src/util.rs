@@ -1,7 +1,8 @@
 use std::fs;
-use std::io;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn clean_path(path: &Path) -> String {
     let mut path_str = path.to_string_lossy().into_owned();
@@ -23,4 +24,225 @@ pub fn get_install_path() -> io::Result<String> {
     }
 
     Ok(upman_path.to_string_lossy().to_string())
-}
\ No newline at end of file
+}
+pub fn current_year() -> i64 {
+    civil_from_days(unix_seconds().div_euclid(86_400)).0
+}
+
+/// The current UTC time as an RFC 3339 timestamp, e.g. `2024-05-03T15:21:07Z`.
+pub fn utc_timestamp() -> String {
+    let secs = unix_seconds();
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let time = secs.rem_euclid(86_400);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3_600, time % 3_600 / 60, time % 60)
+}
+
+pub fn unix_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// Civil-from-days conversion (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// The per-user configuration directory for upman: `$XDG_CONFIG_HOME/upman`, `~/.config/upman`, or
+/// `%APPDATA%\upman` on Windows.
+pub fn user_config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).filter(|dir| dir.is_absolute()).or_else(|| home_dir().map(|home| home.join(".config")))
+    };
+    base.map(|dir| dir.join("upman"))
+}
+
+/// Where short-lived per-user state such as unlocked keys goes: `$XDG_RUNTIME_DIR/upman`, which is private
+/// and cleared at logout, falling back to `$XDG_CACHE_HOME/upman`, `~/.cache/upman`, or `%LOCALAPPDATA%\upman`.
+pub fn user_runtime_dir() -> Option<PathBuf> {
+    let xdg_dir = |variable: &str| env::var_os(variable).map(PathBuf::from).filter(|dir| dir.is_absolute());
+    let base = if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        xdg_dir("XDG_RUNTIME_DIR").or_else(|| xdg_dir("XDG_CACHE_HOME")).or_else(|| home_dir().map(|home| home.join(".cache")))
+    };
+    base.map(|dir| dir.join("upman"))
+}
+
+/// The SHA-256 digest of `data` in lowercase hex, stable across builds and platforms.
+pub fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Node tooling (npm, npx) is installed as `.cmd` shims on Windows.
+pub fn node_tool(name: &str) -> String {
+    if cfg!(windows) {
+        format!("C:\\Program Files\\nodejs\\{}.cmd", name)
+    } else {
+        name.to_string()
+    }
+}
+
+pub fn venv_bin(project_path: &Path, tool: &str) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        project_path.join("venv").join("Scripts").join(format!("{}.exe", tool))
+    } else {
+        project_path.join("venv").join("bin").join(tool)
+    }
+}
+
+/// The site-packages directories of the project's `venv` or `.venv`, on Windows and Unix layouts.
+pub fn site_packages_dirs(project_path: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for venv in ["venv", ".venv"] {
+        let venv_path = project_path.join(venv);
+        dirs.push(venv_path.join("Lib").join("site-packages"));
+        if let Ok(entries) = fs::read_dir(venv_path.join("lib")) {
+            dirs.extend(entries.flatten().map(|entry| entry.path().join("site-packages")));
+        }
+    }
+    dirs.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+/// Per-project state kept by upman (test results, build artifacts, caches). When the directory is first created
+/// it's added to the project's .gitignore, if there is one.
+pub fn project_state_dir(project_path: &Path) -> PathBuf {
+    let state_dir = project_path.join(".upm");
+    if state_dir.is_dir() {
+        return state_dir;
+    }
+    if let Err(err) = fs::create_dir_all(&state_dir) {
+        eprintln!("Failed to create {}: {}", state_dir.display(), err);
+    }
+    let gitignore = project_path.join(".gitignore");
+    if let Ok(contents) = fs::read_to_string(&gitignore) {
+        let ignored = contents.lines().any(|line| matches!(line.trim(), ".upm" | ".upm/" | "/.upm" | "/.upm/"));
+        if !ignored {
+            let separator = if contents.is_empty() || contents.ends_with('\n') { "" } else { "\n" };
+            if let Err(err) = fs::write(&gitignore, format!("{}{}.upm/\n", contents, separator)) {
+                eprintln!("Failed to add .upm/ to {}: {}", gitignore.display(), err);
+            }
+        }
+    }
+    state_dir
+}
+
+/// Replaces `path` through a temporary file in the same directory, so a crash or a full disk leaves the old
+/// contents intact instead of a truncated file. The file keeps its permissions.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let file_name = path.file_name().ok_or_else(|| io::Error::other(format!("{} is not a file path", path.display())))?;
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id()));
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Writes a file that only the current user can access on Unix. Missing directories are created private too;
+/// existing ones are left as they are. An existing file is replaced rather than rewritten, so one created with
+/// looser permissions isn't reused.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    if !dir.is_dir() {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(dir)?;
+    }
+
+    let _ = fs::remove_file(path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+pub fn command_exists(program: &str) -> bool {
+    std::process::Command::new(program)
+        .arg("--version")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Orders version strings the way most ecosystems do: numeric parts numerically, a leading `v` and build
+/// metadata ignored, and a pre-release (`1.0.0-beta`, `2.0rc1`) before its release.
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let parts = |version: &str| -> Vec<String> {
+        let version = version.trim().trim_start_matches('v');
+        let version = version.split('+').next().unwrap_or(version);
+        // Split "2.0rc1" into 2, 0, rc, 1 so letters and numbers compare separately.
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        for c in version.chars() {
+            let boundary = !c.is_ascii_alphanumeric() || current.chars().last().is_some_and(|last| last.is_ascii_digit() != c.is_ascii_digit());
+            if boundary && !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            if c.is_ascii_alphanumeric() {
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            parts.push(current);
+        }
+        parts
+    };
+    let (a, b) = (parts(a), parts(b));
+    for index in 0..a.len().max(b.len()) {
+        let ordering = match (a.get(index), b.get(index)) {
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Greater,
+                (Err(_), Ok(_)) => Ordering::Less,
+                (Err(_), Err(_)) => a.to_lowercase().cmp(&b.to_lowercase()),
+            },
+            // 1.0 < 1.0.1, but 1.0 > 1.0-beta.
+            (Some(a), None) => if a.parse::<u64>().is_ok() { Ordering::Greater } else { Ordering::Less },
+            (None, Some(b)) => if b.parse::<u64>().is_ok() { Ordering::Less } else { Ordering::Greater },
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
@@ -0,0 +1,541 @@
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use serde_json::Value;
+use crate::dependencies::package_key;
+use crate::load_projects_db;
+use crate::package_request::Constraint;
+use crate::project_database::find_project_for_dir;
+use crate::sbom::{project_components, Component};
+use crate::util::{clean_path, compare_versions};
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> Option<Severity> {
+        match name.to_lowercase().as_str() {
+            "low" => Some(Severity::Low),
+            "medium" | "moderate" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    // The CVSS v3 qualitative rating scale.
+    fn from_score(score: f64) -> Severity {
+        match score {
+            score if score >= 9.0 => Severity::Critical,
+            score if score >= 7.0 => Severity::High,
+            score if score >= 4.0 => Severity::Medium,
+            score if score > 0.0 => Severity::Low,
+            _ => Severity::Unknown,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Severity::Unknown => "unknown",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// A package version an advisory affects.
+#[derive(Serialize)]
+pub struct Finding {
+    pub package: String,
+    pub version: String,
+    pub ecosystem: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    pub summary: String,
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Versions (OSV) or version requirements (RustSec) that fix it.
+    pub fixed: Vec<String>,
+}
+
+/// One advisory, from an OSV JSON file or a RustSec Markdown file, reduced to what matching needs.
+struct Advisory {
+    id: String,
+    aliases: Vec<String>,
+    summary: String,
+    severity: Severity,
+    score: Option<f64>,
+    affected: Vec<Affected>,
+}
+
+struct Affected {
+    /// The ecosystem, named as in `Component`.
+    ecosystem: &'static str,
+    name: String,
+    rule: AffectedRule,
+    severity: Option<Severity>,
+}
+
+enum AffectedRule {
+    /// OSV: listed versions plus introduced/fixed/last_affected ranges.
+    Osv { versions: Vec<String>, ranges: Vec<Vec<(String, String)>> },
+    /// RustSec: every version not matched by a patched or unaffected requirement.
+    RustSec { patched: Vec<String>, unaffected: Vec<String> },
+}
+
+#[derive(Serialize)]
+struct AuditReport<'a> {
+    database: String,
+    advisories: usize,
+    dependencies: usize,
+    findings: &'a [Finding],
+}
+
+pub fn audit_command(db: &str, fail_on: &str, format: &str) {
+    let current_dir = env::current_dir().unwrap();
+    let current_dir_str = clean_path(&current_dir);
+    let projects_db = load_projects_db();
+
+    let Some((_project_name, info)) = find_project_for_dir(&projects_db, &current_dir_str) else {
+        println!("Current directory is not a recognized UPM project.");
+        return;
+    };
+    let db_path = Path::new(db);
+    if !db_path.is_dir() {
+        eprintln!("Advisory database '{}' is not a directory.", db);
+        std::process::exit(1);
+    }
+    let threshold = Severity::from_name(fail_on).unwrap_or(Severity::Low);
+
+    let advisories = load_advisories(db_path);
+    let components = project_components(Path::new(&info.project_path));
+    let findings = match_advisories(&advisories, &components);
+
+    if format == "json" {
+        let report = AuditReport { database: clean_path(db_path), advisories: advisories.len(), dependencies: components.len(), findings: &findings };
+        println!("{}", serde_json::to_string_pretty(&report).expect("Failed to serialize the audit report"));
+    } else {
+        print_table(&findings, advisories.len(), components.len());
+    }
+
+    // Advisories without a severity can't be ranked, so only the strictest threshold fails on them.
+    let failing = findings.iter().filter(|finding| finding.severity >= threshold || (finding.severity == Severity::Unknown && threshold == Severity::Low)).count();
+    if failing > 0 {
+        eprintln!("{} finding(s) at or above {} severity.", failing, threshold.label());
+        std::process::exit(1);
+    }
+}
+
+fn print_table(findings: &[Finding], advisories: usize, dependencies: usize) {
+    if advisories == 0 {
+        println!("No advisories found in the database. Expected OSV JSON files or a RustSec advisory-db checkout.");
+    }
+    if findings.is_empty() {
+        println!("No known vulnerabilities in {} dependencies ({} advisories checked).", dependencies, advisories);
+        return;
+    }
+
+    let fixed = |finding: &Finding| if finding.fixed.is_empty() { "-".to_string() } else { finding.fixed.join(", ") };
+    let package_width = findings.iter().map(|finding| finding.package.len()).max().unwrap_or(0).max("PACKAGE".len());
+    let version_width = findings.iter().map(|finding| finding.version.len()).max().unwrap_or(0).max("VERSION".len());
+    let id_width = findings.iter().map(|finding| finding.id.len()).max().unwrap_or(0).max("ADVISORY".len());
+    let fixed_width = findings.iter().map(|finding| fixed(finding).len()).max().unwrap_or(0).max("FIXED".len());
+    println!("{:<package_width$}  {:<version_width$}  {:<id_width$}  {:<8}  {:<fixed_width$}  SUMMARY", "PACKAGE", "VERSION", "ADVISORY", "SEVERITY", "FIXED");
+    for finding in findings {
+        println!(
+            "{:<package_width$}  {:<version_width$}  {:<id_width$}  {:<8}  {:<fixed_width$}  {}",
+            finding.package,
+            finding.version,
+            finding.id,
+            finding.severity.label(),
+            fixed(finding),
+            finding.summary
+        );
+    }
+    println!();
+    println!("{} vulnerable package version(s) found in {} dependencies.", findings.len(), dependencies);
+}
+
+/// Matches every component against the advisories for its ecosystem and name, most severe first.
+fn match_advisories(advisories: &[Advisory], components: &[Component]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for component in components {
+        let key = package_key(component.ecosystem, &component.name);
+        for advisory in advisories {
+            for affected in advisory.affected.iter().filter(|affected| affected.ecosystem == component.ecosystem && package_key(affected.ecosystem, &affected.name) == key) {
+                if !affected.rule.affects(&component.version) {
+                    continue;
+                }
+                findings.push(Finding {
+                    package: component.name.clone(),
+                    version: component.version.clone(),
+                    ecosystem: component.ecosystem.to_string(),
+                    id: advisory.id.clone(),
+                    aliases: advisory.aliases.clone(),
+                    summary: advisory.summary.clone(),
+                    severity: affected.severity.unwrap_or(advisory.severity),
+                    score: advisory.score,
+                    fixed: affected.rule.fixed(),
+                });
+                break;
+            }
+        }
+    }
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.package.cmp(&b.package)).then_with(|| a.id.cmp(&b.id)));
+    findings
+}
+
+impl AffectedRule {
+    fn affects(&self, version: &str) -> bool {
+        match self {
+            AffectedRule::Osv { versions, ranges } => {
+                versions.iter().any(|affected| compare_versions(affected, version) == Ordering::Equal) || ranges.iter().any(|events| in_osv_range(events, version))
+            },
+            AffectedRule::RustSec { patched, unaffected } => {
+                !patched.iter().chain(unaffected).any(|requirement| matches_requirement(version, requirement))
+            },
+        }
+    }
+
+    fn fixed(&self) -> Vec<String> {
+        match self {
+            AffectedRule::Osv { ranges, .. } => ranges.iter().flatten().filter(|(event, _)| event == "fixed").map(|(_, version)| version.clone()).collect(),
+            AffectedRule::RustSec { patched, .. } => patched.clone(),
+        }
+    }
+}
+
+// OSV range events: a version is affected from an `introduced` until the next `fixed` (exclusive),
+// `last_affected` (inclusive) or `limit`. The spec leaves the events unordered, so they're sorted by version
+// first, with "0" lowest.
+fn in_osv_range(events: &[(String, String)], version: &str) -> bool {
+    let mut events: Vec<&(String, String)> = events.iter().collect();
+    events.sort_by(|(_, a), (_, b)| match (a == "0", b == "0") {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => compare_versions(a, b),
+    });
+    let mut affected = false;
+    for (event, bound) in events {
+        let ordering = if bound == "0" { Ordering::Greater } else { compare_versions(version, bound) };
+        match event.as_str() {
+            "introduced" if ordering != Ordering::Less => affected = true,
+            "fixed" | "limit" if ordering != Ordering::Less => affected = false,
+            "last_affected" if ordering == Ordering::Greater => affected = false,
+            _ => {},
+        }
+    }
+    affected
+}
+
+// A Cargo version requirement: comma-separated comparators, where a bare version means a caret.
+fn matches_requirement(version: &str, requirement: &str) -> bool {
+    requirement.split(',').map(str::trim).filter(|comparator| !comparator.is_empty()).all(|comparator| {
+        let operators = [(">=", [Ordering::Greater, Ordering::Equal]), ("<=", [Ordering::Less, Ordering::Equal]), (">", [Ordering::Greater; 2]), ("<", [Ordering::Less; 2]), ("=", [Ordering::Equal; 2])];
+        if let Some((operator, allowed)) = operators.iter().find(|(operator, _)| comparator.starts_with(operator)) {
+            return allowed.contains(&compare_versions(version, comparator[operator.len()..].trim()));
+        }
+        let constraint = match Constraint::parse(comparator) {
+            Some(Constraint::Exact(bare)) => Constraint::Caret(bare),
+            Some(constraint) => constraint,
+            None => return true,
+        };
+        match constraint.range() {
+            Some((lower, upper)) => compare_versions(version, &lower) != Ordering::Less && compare_versions(version, &upper) == Ordering::Less,
+            None => false,
+        }
+    })
+}
+
+/// Reads every advisory under `db`: OSV `.json` files (the OSV bulk export, or advisory-db's `osv` branch)
+/// and RustSec `.md` files with a TOML front matter (an advisory-db checkout).
+fn load_advisories(db: &Path) -> Vec<Advisory> {
+    let mut advisories = Vec::new();
+    let mut pending: Vec<PathBuf> = vec![db.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if !entry.file_name().to_string_lossy().starts_with('.') {
+                    pending.push(path);
+                }
+                continue;
+            }
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let advisory = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("json") => parse_osv(&contents),
+                Some("md") => parse_rustsec(&contents),
+                _ => None,
+            };
+            advisories.extend(advisory);
+        }
+    }
+    advisories
+}
+
+fn osv_ecosystem(ecosystem: &str) -> Option<&'static str> {
+    // Ecosystems can carry a suffix, e.g. "Debian:11" or "Go:stdlib".
+    match ecosystem.split(':').next()? {
+        "crates.io" => Some("cargo"),
+        "npm" => Some("npm"),
+        "PyPI" => Some("pypi"),
+        "Go" => Some("go"),
+        "RubyGems" => Some("rubygems"),
+        "NuGet" => Some("nuget"),
+        _ => None,
+    }
+}
+
+fn parse_osv(contents: &str) -> Option<Advisory> {
+    let advisory: Value = serde_json::from_str(contents).ok()?;
+    if advisory.get("withdrawn").is_some() || advisory.pointer("/database_specific/informational").is_some() {
+        return None;
+    }
+    let text = |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(String::from);
+    let database_severity = |value: &Value| value.pointer("/database_specific/severity").and_then(Value::as_str).and_then(Severity::from_name);
+
+    let score = advisory
+        .get("severity")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|severity| severity.get("type").and_then(Value::as_str) == Some("CVSS_V3"))
+        .find_map(|severity| severity.get("score").and_then(Value::as_str).and_then(cvss3_base_score));
+    let severity = database_severity(&advisory).or(score.map(Severity::from_score)).unwrap_or(Severity::Unknown);
+
+    let affected = advisory
+        .get("affected")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|affected| {
+            let package = affected.get("package")?;
+            let versions = affected.get("versions").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).map(String::from).collect();
+            let ranges = affected
+                .get("ranges")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                // Git ranges are commits, which lockfile versions can't be compared with.
+                .filter(|range| range.get("type").and_then(Value::as_str) != Some("GIT"))
+                .map(|range| {
+                    range
+                        .get("events")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|event| event.as_object()?.iter().next().and_then(|(kind, version)| Some((kind.clone(), version.as_str()?.to_string()))))
+                        .collect()
+                })
+                .collect();
+            Some(Affected {
+                ecosystem: osv_ecosystem(package.get("ecosystem")?.as_str()?)?,
+                name: package.get("name")?.as_str()?.to_string(),
+                rule: AffectedRule::Osv { versions, ranges },
+                severity: database_severity(affected).or(affected.pointer("/ecosystem_specific/severity").and_then(Value::as_str).and_then(Severity::from_name)),
+            })
+        })
+        .collect();
+
+    Some(Advisory {
+        id: text(&advisory, "id")?,
+        aliases: advisory.get("aliases").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).map(String::from).collect(),
+        summary: text(&advisory, "summary").or_else(|| text(&advisory, "details").map(|details| details.lines().next().unwrap_or_default().to_string())).unwrap_or_default(),
+        severity,
+        score,
+        affected,
+    })
+}
+
+// RustSec advisories are Markdown with a ```toml front matter; the first heading is the title.
+fn parse_rustsec(contents: &str) -> Option<Advisory> {
+    let front_matter = contents.strip_prefix("```toml")?;
+    let (front_matter, body) = front_matter.split_once("\n```")?;
+    let metadata: toml::Table = front_matter.parse().ok()?;
+    let advisory = metadata.get("advisory")?;
+    if advisory.get("withdrawn").is_some() || advisory.get("informational").is_some() {
+        return None;
+    }
+    let strings = |value: Option<&toml::Value>| -> Vec<String> {
+        value.and_then(|value| value.as_array()).into_iter().flatten().filter_map(|item| item.as_str()).map(String::from).collect()
+    };
+    let versions = metadata.get("versions");
+    let score = advisory.get("cvss").and_then(|cvss| cvss.as_str()).and_then(cvss3_base_score);
+
+    Some(Advisory {
+        id: advisory.get("id")?.as_str()?.to_string(),
+        aliases: strings(advisory.get("aliases")),
+        summary: body.lines().find_map(|line| line.strip_prefix("# ")).unwrap_or_default().trim().to_string(),
+        severity: score.map(Severity::from_score).unwrap_or(Severity::Unknown),
+        score,
+        affected: vec![Affected {
+            ecosystem: "cargo",
+            name: advisory.get("package")?.as_str()?.to_string(),
+            rule: AffectedRule::RustSec {
+                patched: strings(versions.and_then(|versions| versions.get("patched"))),
+                unaffected: strings(versions.and_then(|versions| versions.get("unaffected"))),
+            },
+            severity: None,
+        }],
+    })
+}
+
+/// The CVSS v3.0/v3.1 base score of a vector such as `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`.
+fn cvss3_base_score(vector: &str) -> Option<f64> {
+    let mut metrics = std::collections::HashMap::new();
+    let mut parts = vector.split('/');
+    if !parts.next()?.starts_with("CVSS:3") {
+        return None;
+    }
+    for part in parts {
+        let (metric, value) = part.split_once(':')?;
+        metrics.insert(metric, value);
+    }
+    let scope_changed = *metrics.get("S")? == "C";
+    let attack_vector = match *metrics.get("AV")? { "N" => 0.85, "A" => 0.62, "L" => 0.55, "P" => 0.2, _ => return None };
+    let attack_complexity = match *metrics.get("AC")? { "L" => 0.77, "H" => 0.44, _ => return None };
+    let privileges = match (*metrics.get("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let user_interaction = match *metrics.get("UI")? { "N" => 0.85, "R" => 0.62, _ => return None };
+    let impact_of = |metric: &str| -> Option<f64> {
+        match *metrics.get(metric)? { "H" => Some(0.56), "L" => Some(0.22), "N" => Some(0.0), _ => None }
+    };
+    let base_impact = 1.0 - (1.0 - impact_of("C")?) * (1.0 - impact_of("I")?) * (1.0 - impact_of("A")?);
+    let impact = if scope_changed { 7.52 * (base_impact - 0.029) - 3.25 * (base_impact - 0.02).powi(15) } else { 6.42 * base_impact };
+    let exploitability = 8.22 * attack_vector * attack_complexity * privileges * user_interaction;
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let score = if scope_changed { (1.08 * (impact + exploitability)).min(10.0) } else { (impact + exploitability).min(10.0) };
+    // CVSS 3.1 "round up" to one decimal, done in integers to avoid floating point artifacts.
+    let scaled = (score * 100_000.0).round() as i64;
+    Some(if scaled % 10_000 == 0 { scaled as f64 / 100_000.0 } else { ((scaled / 10_000) + 1) as f64 / 10.0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(events: &[(&str, &str)]) -> Vec<(String, String)> {
+        events.iter().map(|(event, version)| (event.to_string(), version.to_string())).collect()
+    }
+
+    fn component(name: &str, version: &str, ecosystem: &'static str) -> Component {
+        Component { name: name.to_string(), version: version.to_string(), ecosystem, hashes: Vec::new(), license: None }
+    }
+
+    #[test]
+    fn cvss3_known_vectors() {
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), Some(9.8));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"), Some(10.0));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"), Some(6.1));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:H/A:H"), Some(7.8));
+        assert_eq!(cvss3_base_score("CVSS:3.0/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:N/A:N"), Some(5.9));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:H"), Some(7.5));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:P/AC:H/PR:H/UI:R/S:U/C:L/I:N/A:N"), Some(1.6));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"), Some(0.0));
+        assert_eq!(cvss3_base_score("CVSS:2.0/AV:N/AC:L/Au:N/C:P/I:P/A:P"), None);
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H"), None);
+    }
+
+    #[test]
+    fn osv_ranges() {
+        let range = events(&[("introduced", "0"), ("fixed", "2.32.0")]);
+        assert!(in_osv_range(&range, "2.31.0"));
+        assert!(!in_osv_range(&range, "2.32.0"));
+
+        let range = events(&[("introduced", "1.2.0"), ("last_affected", "1.4.1")]);
+        assert!(!in_osv_range(&range, "1.1.9"));
+        assert!(in_osv_range(&range, "1.4.1"));
+        assert!(!in_osv_range(&range, "1.4.2"));
+
+        let range = events(&[("introduced", "1.0.0"), ("limit", "2.0.0")]);
+        assert!(in_osv_range(&range, "1.9.9"));
+        assert!(!in_osv_range(&range, "2.0.0"));
+    }
+
+    #[test]
+    fn osv_range_events_in_any_order() {
+        let range = events(&[("fixed", "1.5.0"), ("introduced", "2.0.0"), ("fixed", "2.1.3"), ("introduced", "0")]);
+        assert!(in_osv_range(&range, "1.4.0"));
+        assert!(!in_osv_range(&range, "1.5.0"));
+        assert!(!in_osv_range(&range, "1.9.0"));
+        assert!(in_osv_range(&range, "2.1.2"));
+        assert!(!in_osv_range(&range, "2.1.3"));
+    }
+
+    #[test]
+    fn cargo_requirements() {
+        assert!(matches_requirement("1.13.2", ">= 1.13.2"));
+        assert!(!matches_requirement("1.13.1", ">= 1.13.2"));
+        assert!(matches_requirement("0.9.0", "< 1.0.0"));
+        assert!(matches_requirement("0.8.5", ">= 0.8.5, < 0.9"));
+        assert!(!matches_requirement("0.9.1", ">= 0.8.5, < 0.9"));
+        assert!(matches_requirement("1.2.3", "^1.2"));
+        assert!(!matches_requirement("2.0.0", "^1.2"));
+        assert!(matches_requirement("0.3.9", "0.3.1"));
+        assert!(!matches_requirement("0.4.0", "0.3.1"));
+        assert!(matches_requirement("1.4.7", "~1.4"));
+        assert!(!matches_requirement("1.5.0", "~1.4"));
+    }
+
+    #[test]
+    fn osv_advisory() {
+        let advisory = parse_osv(include_str!("../tests/fixtures/audit/osv-ghsa.json")).unwrap();
+        assert_eq!(advisory.id, "GHSA-9wx4-h78v-vm56");
+        assert_eq!(advisory.aliases, ["CVE-2024-35195"]);
+        assert_eq!(advisory.score, Some(5.6));
+        assert!(advisory.severity == Severity::Medium);
+
+        let components = [component("requests", "2.31.0", "pypi"), component("Requests", "2.32.0", "pypi"), component("requests", "2.31.0", "npm")];
+        let findings = match_advisories(&[advisory], &components);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].package, "requests");
+        assert_eq!(findings[0].fixed, ["2.32.0"]);
+    }
+
+    #[test]
+    fn rustsec_advisory() {
+        let advisory = parse_rustsec(include_str!("../tests/fixtures/audit/rustsec.md")).unwrap();
+        assert_eq!(advisory.id, "RUSTSEC-2022-0013");
+        assert_eq!(advisory.summary, "Regexes with large repetitions on empty sub-expressions take a very long time to parse");
+        assert_eq!(advisory.score, Some(7.5));
+        assert!(advisory.severity == Severity::High);
+
+        let components = [component("regex", "1.5.4", "cargo"), component("regex", "1.5.5", "cargo"), component("regex", "0.2.11", "cargo")];
+        let findings = match_advisories(&[advisory], &components);
+        assert_eq!(findings.iter().map(|finding| finding.version.as_str()).collect::<Vec<_>>(), ["1.5.4", "0.2.11"]);
+        assert_eq!(findings[0].fixed, [">= 1.5.5"]);
+    }
+
+    #[test]
+    fn withdrawn_advisories_are_skipped() {
+        assert!(parse_osv(r#"{"id": "GHSA-x", "withdrawn": "2024-01-01T00:00:00Z", "affected": []}"#).is_none());
+        assert!(parse_rustsec("```toml\n[advisory]\nid = \"RUSTSEC-x\"\npackage = \"a\"\ninformational = \"unsound\"\n```\n").is_none());
+    }
+}
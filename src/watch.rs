@@ -0,0 +1,120 @@
+use std::path::Path;
+use std::process::{Child, Command};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::build::GENERATED_DIRS;
+use crate::child::{exit_code, interrupt_flag, spawn_group, stop_groups, StopSignal};
+
+// Changes closer together than this are handled as one restart.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+// How long a stopped process gets to exit after SIGTERM before it is killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Runs the command from `start`, and stops and starts it again whenever a watched file changes.
+/// `start` is called for every restart, so it can rebuild first; returning `None` waits for the next change.
+pub fn watch_and_restart(project_path: &Path, mut start: impl FnMut() -> Option<Command>) {
+    let (sender, receiver) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = match notify::recommended_watcher(move |event| {
+        let _ = sender.send(event);
+    }) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            eprintln!("Failed to start watching files: {}", err);
+            return;
+        },
+    };
+    if let Err(err) = watcher.watch(project_path, RecursiveMode::Recursive) {
+        eprintln!("Failed to watch {}: {}", project_path.display(), err);
+        return;
+    }
+
+    let ignored = ignore_rules(project_path);
+    // The watched process is in its own group, so Ctrl-C only reaches upman, which stops it before exiting.
+    let interrupted = interrupt_flag();
+    println!("[upman] watching {} for changes (Ctrl-C to stop)", project_path.display());
+
+    let mut child = spawn(&mut start);
+    loop {
+        if interrupted.load(Ordering::SeqCst) {
+            if let Some(running) = child.take() {
+                stop(running);
+            }
+            return;
+        }
+
+        match receiver.recv_timeout(Duration::from_millis(200)) {
+            Ok(Ok(event)) if is_relevant(&event, project_path, &ignored) => {
+                // Wait for the burst of events from a save or checkout to settle before restarting.
+                let mut quiet_since = Instant::now();
+                while quiet_since.elapsed() < DEBOUNCE {
+                    match receiver.recv_timeout(DEBOUNCE) {
+                        Ok(_) => quiet_since = Instant::now(),
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                println!("[upman] change detected, restarting");
+                if let Some(running) = child.take() {
+                    stop(running);
+                }
+                child = spawn(&mut start);
+            },
+            Ok(Ok(_)) => {},
+            Ok(Err(err)) => eprintln!("[upman] watch error: {}", err),
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        if let Some(running) = child.as_mut() {
+            if let Ok(Some(status)) = running.try_wait() {
+                println!("[upman] process exited with code {}; waiting for changes", exit_code(status));
+                child = None;
+            }
+        }
+    }
+}
+
+fn spawn(start: &mut impl FnMut() -> Option<Command>) -> Option<Child> {
+    let mut command = start()?;
+    match spawn_group(&mut command) {
+        Ok(child) => Some(child),
+        Err(err) => {
+            eprintln!("[upman] failed to start: {}", err);
+            None
+        },
+    }
+}
+
+fn stop(mut child: Child) {
+    stop_groups(&mut [&mut child], StopSignal::Terminate, STOP_TIMEOUT);
+}
+
+fn ignore_rules(project_path: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(project_path);
+    let gitignore = project_path.join(".gitignore");
+    if gitignore.is_file() {
+        if let Some(err) = builder.add(&gitignore) {
+            eprintln!("Failed to read {}: {}", gitignore.display(), err);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+fn is_relevant(event: &Event, project_path: &Path, ignored: &Gitignore) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    event.paths.iter().any(|path| {
+        let Ok(relative) = path.strip_prefix(project_path) else {
+            return false;
+        };
+        let in_generated_dir = relative
+            .components()
+            .any(|component| GENERATED_DIRS.contains(&component.as_os_str().to_string_lossy().as_ref()));
+        !in_generated_dir && !ignored.matched_path_or_any_parents(path, path.is_dir()).is_ignore()
+    })
+}